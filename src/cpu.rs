@@ -1,17 +1,24 @@
-use std::fs;
-use std::fs::OpenOptions;
-use std::io::prelude::*;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, prelude::*, BufWriter};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
 
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
 use crate::memory::MemoryDevice;
 
+/// Contains the [BitSet](coverage::BitSet) used for recording executed addresses
+pub mod coverage;
 /// Instructions can be executed to modify cpu state and memory
 pub mod instruction;
 /// Adds functions to memory to read and access interrupt flags from memory
 pub mod interrupt_controller;
 
+use self::coverage::BitSet;
 use self::instruction::decode;
+use self::instruction::Instruction;
 use self::instruction::InstructionEnum;
 use self::instruction::InterruptServiceRoutine;
 use self::interrupt_controller::InterruptController;
@@ -19,6 +26,7 @@ use self::interrupt_controller::InterruptController;
 /// The CpuState stores the internal state of the gameboy processor.
 ///
 /// This is basically just a data container, the actual CPU functionality is handled by [Instruction](instruction::Instruction).
+#[derive(Clone)]
 pub struct CpuState {
     program_counter: u16,
     stack_pointer: u16,
@@ -27,6 +35,45 @@ pub struct CpuState {
     // interrupt_enable: u8,
     // interrupt_flags: u8,
     interrupt_master_enable: bool,
+    /// Set by [EnableInterrupts](instruction::EnableInterrupts). The IME is enabled after the next opcode has been fetched.
+    interrupt_master_enable_pending: bool,
+    /// Set by [Halt](instruction::Halt) when the HALT bug occurs. The next opcode fetch does not increment the program counter.
+    halt_bug: bool,
+
+    /// Addresses from which opcodes were fetched. Empty if coverage is not enabled.
+    coverage: BitSet,
+
+    /// The file the state is traced to before each instruction. `None` if tracing is not enabled.
+    ///
+    /// Shared between clones, so tracing continues after a [MachineState](crate::emulator::machine_state::MachineState) is restored.
+    trace: Option<Arc<Mutex<BufWriter<File>>>>,
+    /// The kind of the error that stopped tracing
+    trace_error: Option<io::ErrorKind>,
+
+    /// The number of times [CpuState::load_instruction] was called. Used to detect the end of an instruction.
+    loaded_instructions: u64,
+    /// The number of clock cycles the CPU ran for. Counted by [CpuState::step] and [Emulator](crate::emulator::Emulator).
+    total_t_cycles: u64,
+    /// The instruction that was loaded at the end of the last [CpuState::step]
+    next_instruction: Option<InstructionEnum>,
+    /// The value of the program counter when the last instruction was loaded
+    instruction_address: u16,
+
+    /// [CpuState::step] stops before executing an instruction at one of these addresses
+    breakpoints: HashSet<u16>,
+    /// Set when [CpuState::step] stopped at a breakpoint. The next step executes the instruction instead of stopping again.
+    breakpoint_reported: bool,
+}
+
+/// The result of a [CpuState::step]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepResult {
+    /// An instruction was executed. Contains the number of machine cycles it took.
+    Executed(u8),
+    /// The next instruction is at a breakpoint and was not executed. Contains the address of the breakpoint.
+    ///
+    /// The instruction is executed by the next call to [CpuState::step].
+    BreakpointHit(u16),
 }
 
 impl CpuState {
@@ -42,7 +89,6 @@ impl CpuState {
     /// let cpuState = CpuState::new();
     /// ```
     pub fn new() -> Self {
-        fs::write("trace.txt", "").expect("Should be able to create empty trace");
         Self {
             program_counter: 0, // 0x0100
             stack_pointer: 0xFFFE,
@@ -51,13 +97,181 @@ impl CpuState {
             // interrupt_enable: 0,
             // interrupt_flags: 0,
             interrupt_master_enable: false,
+            interrupt_master_enable_pending: false,
+            halt_bug: false,
+
+            coverage: BitSet::new(0),
+
+            trace: None,
+            trace_error: None,
+
+            loaded_instructions: 0,
+            total_t_cycles: 0,
+            next_instruction: None,
+            instruction_address: 0,
+
+            breakpoints: HashSet::new(),
+            breakpoint_reported: false,
         }
     }
+
+    /// Initialize a new CPU state like [CpuState::new], but start executing at `program_counter`.
+    ///
+    /// ```
+    /// use rust_gameboy_library::cpu::{Cpu, CpuState};
+    ///
+    /// let cpu = CpuState::new_at(0x0150);
+    /// assert_eq!(cpu.read_program_counter(), 0x0150);
+    /// ```
+    pub fn new_at(program_counter: u16) -> Self {
+        let mut cpu = Self::new();
+        cpu.program_counter = program_counter;
+        cpu
+    }
+
+    /// Initialize a CPU state the way the DMG boot ROM leaves it when it hands over to the cartridge.
+    ///
+    /// The program counter is set to the cartridge entry point at `0x0100` and the stack pointer to `0xFFFE`.
+    /// The registers contain `AF=0x01B0`, `BC=0x0013`, `DE=0x00D8` and `HL=0x014D`.
+    ///
+    /// ```
+    /// use rust_gameboy_library::cpu::{Cpu, CpuState, DoubleRegister};
+    ///
+    /// let cpu = CpuState::new_post_boot();
+    /// assert_eq!(cpu.read_program_counter(), 0x0100);
+    /// assert_eq!(cpu.read_double_register(DoubleRegister::AF), 0x01B0);
+    /// ```
+    pub fn new_post_boot() -> Self {
+        let mut cpu = Self::new_at(0x0100);
+        cpu.stack_pointer = 0xFFFE;
+        cpu.write_double_register(DoubleRegister::AF, 0x01B0);
+        cpu.write_double_register(DoubleRegister::BC, 0x0013);
+        cpu.write_double_register(DoubleRegister::DE, 0x00D8);
+        cpu.write_double_register(DoubleRegister::HL, 0x014D);
+        cpu
+    }
+
+    /// Restore the power-on state that [CpuState::new] creates.
+    ///
+    /// The program counter, stack pointer, registers and the IME are reset. An instruction that was loaded by [CpuState::step] is discarded.
+    /// The counters for [CpuState::loaded_instructions] and [CpuState::read_t_cycles] start at `0` again.
+    /// Coverage, tracing and breakpoints stay enabled.
+    pub fn reset(&mut self) {
+        let initial = Self::new();
+        self.program_counter = initial.program_counter;
+        self.stack_pointer = initial.stack_pointer;
+        self.registers = initial.registers;
+        self.interrupt_master_enable = initial.interrupt_master_enable;
+        self.interrupt_master_enable_pending = initial.interrupt_master_enable_pending;
+        self.halt_bug = initial.halt_bug;
+        self.next_instruction = initial.next_instruction;
+        self.instruction_address = initial.instruction_address;
+        self.breakpoint_reported = initial.breakpoint_reported;
+        self.loaded_instructions = initial.loaded_instructions;
+        self.total_t_cycles = initial.total_t_cycles;
+    }
+
+    /// The number of bytes produced by [CpuState::to_bytes]
+    pub const SERIALIZED_SIZE: usize = 13;
+
+    /// Pack the architectural state of the CPU into a fixed layout.
+    ///
+    /// The layout is the program counter and the stack pointer as little endian words, followed by the 8 registers in [Register] order and a byte with flags.
+    /// Bit 0 of the flags is the IME, bit 1 is set if [EI](instruction::EnableInterrupts) is about to enable the IME and bit 2 is set if the HALT bug is about to occur.
+    /// Coverage, tracing and the instruction that is currently executing are not included.
+    ///
+    /// ```
+    /// use rust_gameboy_library::cpu::{Cpu, CpuState};
+    ///
+    /// let mut cpu = CpuState::new();
+    /// cpu.write_program_counter(0x0150);
+    ///
+    /// let restored = CpuState::from_bytes(&cpu.to_bytes());
+    /// assert_eq!(restored.read_program_counter(), 0x0150);
+    /// ```
+    pub fn to_bytes(&self) -> [u8; Self::SERIALIZED_SIZE] {
+        let mut bytes = [0; Self::SERIALIZED_SIZE];
+        bytes[0..2].copy_from_slice(&self.program_counter.to_le_bytes());
+        bytes[2..4].copy_from_slice(&self.stack_pointer.to_le_bytes());
+        bytes[4..12].copy_from_slice(&self.registers);
+        bytes[12] = self.interrupt_master_enable as u8
+            | (self.interrupt_master_enable_pending as u8) << 1
+            | (self.halt_bug as u8) << 2;
+        bytes
+    }
+
+    /// Create a CPU from bytes produced by [CpuState::to_bytes].
+    ///
+    /// Everything that is not part of the layout is initialized like in [CpuState::new].
+    pub fn from_bytes(bytes: &[u8; Self::SERIALIZED_SIZE]) -> Self {
+        let mut cpu = Self::new();
+        cpu.program_counter = u16::from_le_bytes([bytes[0], bytes[1]]);
+        cpu.stack_pointer = u16::from_le_bytes([bytes[2], bytes[3]]);
+        cpu.registers.copy_from_slice(&bytes[4..12]);
+        cpu.interrupt_master_enable = bytes[12] & 0b001 != 0;
+        cpu.interrupt_master_enable_pending = bytes[12] & 0b010 != 0;
+        cpu.halt_bug = bytes[12] & 0b100 != 0;
+        cpu
+    }
+
+    /// Start recording which addresses opcodes are fetched from.
+    ///
+    /// Only addresses below `size` are recorded, so pass the size of the ROM area (`0x8000`) to ignore code running from RAM.
+    /// Calling this again clears the recorded addresses.
+    pub fn enable_coverage(&mut self, size: usize) {
+        self.coverage = BitSet::new(size);
+    }
+
+    /// Start writing the state of the CPU to the file at `path` before each instruction.
+    ///
+    /// The file is created or truncated right away. Each line has the same format as the logs of [Gameboy Doctor](https://github.com/robert/gameboy-doctor), so traces can be compared with other emulators.
+    /// The output is buffered. If writing fails, tracing stops and the error is reported by [CpuState::trace_error].
+    pub fn enable_trace(&mut self, path: &Path) -> io::Result<()> {
+        let file = File::create(path)?;
+        self.trace = Some(Arc::new(Mutex::new(BufWriter::new(file))));
+        self.trace_error = None;
+        Ok(())
+    }
+
+    /// Stop tracing and write the remaining buffered output. See [CpuState::enable_trace].
+    pub fn disable_trace(&mut self) -> io::Result<()> {
+        match self.trace.take() {
+            Some(trace) => trace.lock().unwrap_or_else(PoisonError::into_inner).flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Get the kind of the error that stopped tracing. `None` if writing the trace did not fail.
+    pub fn trace_error(&self) -> Option<io::ErrorKind> {
+        self.trace_error
+    }
+
+    /// Make [CpuState::step] stop before executing the instruction at `address`
+    pub fn add_breakpoint(&mut self, address: u16) {
+        self.breakpoints.insert(address);
+    }
+
+    /// Remove a breakpoint added with [CpuState::add_breakpoint]
+    pub fn remove_breakpoint(&mut self, address: u16) {
+        self.breakpoints.remove(&address);
+    }
+
+    /// Get the addresses opcodes were fetched from since [CpuState::enable_coverage] was called.
+    ///
+    /// Only the first byte of each instruction is marked.
+    pub fn coverage(&self) -> &BitSet {
+        &self.coverage
+    }
     /// Load the next opcode
     ///
-    /// Also increments the program counter
+    /// Also increments the program counter, unless [CpuState::trigger_halt_bug] was called before.
     pub fn load_opcode<T: MemoryDevice>(&mut self, memory: &T) -> u8 {
-        let opcode = memory.read(self.advance_program_counter());
+        let address = match std::mem::take(&mut self.halt_bug) {
+            true => self.read_program_counter(),
+            false => self.advance_program_counter(),
+        };
+        self.coverage.insert(address as usize);
+        let opcode = memory.read(address);
         return opcode;
     }
 
@@ -66,10 +280,15 @@ impl CpuState {
     // TODO: Link to ISR instruction
     /// Returns a ISR, if there are pending interrupts and the [IME][self::Cpu::read_interrupt_master_enable] is set.
     ///
-    /// Also increments the program counter
+    /// Also increments the program counter.
+    /// If [CpuState::schedule_interrupt_master_enable] was called before, the IME is enabled after the instruction has been loaded.
     pub fn load_instruction<T: MemoryDevice>(&mut self, memory: &mut T) -> InstructionEnum {
+        self.loaded_instructions = self.loaded_instructions.wrapping_add(1);
+        self.instruction_address = self.program_counter;
+        self.breakpoint_reported = false;
+        let enable_interrupts = std::mem::take(&mut self.interrupt_master_enable_pending);
         let pending_interrupt = self.get_pending_interrupt(memory);
-        // self.trace_state(memory);
+        self.trace_state(memory);
         let loaded_instruction = match pending_interrupt {
             Some(interrupt) => interrupt,
             None => {
@@ -82,26 +301,135 @@ impl CpuState {
         //     self.read_program_counter() - 1,
         //     loaded_instruction
         // );
+        if enable_interrupts {
+            self.write_interrupt_master_enable(true);
+        }
         loaded_instruction
     }
 
-    #[allow(unused)]
-    fn trace_state<T: MemoryDevice>(&mut self, memory: &mut T) {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .append(true)
-            .open("trace.txt")
-            .unwrap();
+    /// Do not increment the program counter on the next opcode fetch.
+    ///
+    /// This emulates the HALT bug: if `HALT` is executed while the IME is disabled and an interrupt is already pending, the byte after `HALT` is read twice.
+    pub fn trigger_halt_bug(&mut self) {
+        self.halt_bug = true;
+    }
 
-        let state = self.summarize_state(memory);
+    /// Enable the IME once the next instruction has been loaded.
+    ///
+    /// This is how `EI` behaves on hardware: the instruction directly after `EI` can not be interrupted.
+    pub fn schedule_interrupt_master_enable(&mut self) {
+        self.interrupt_master_enable_pending = true;
+    }
 
-        writeln!(file, "{}", state);
+    /// Get the number of instructions that were loaded since the CPU was created. Interrupt service routines count as instructions.
+    pub fn loaded_instructions(&self) -> u64 {
+        self.loaded_instructions
+    }
+
+    /// Get the number of clock cycles (T-cycles) the CPU ran for. Each machine cycle takes four clock cycles.
+    ///
+    /// Only cycles run by [CpuState::step] or by an [Emulator](crate::emulator::Emulator) are counted, executing instructions manually does not advance this.
+    pub fn read_t_cycles(&self) -> u64 {
+        self.total_t_cycles
     }
 
-    fn summarize_state<T: MemoryDevice>(&mut self, memory: &mut T) -> String {
-        // Target:
-        // A: 01 F: B0 B: 00 C: 13 D: 00 E: D8 H: 01 L: 4D SP: FFFE PC: 00:0100 (00 C3 13 02)
-        format!("A: {:02X} F: {:02X} B: {:02X} C: {:02X} D: {:02X} E: {:02X} H: {:02X} L: {:02X} SP: {:04X} PC: 00:{:04X} ({:02X} {:02X} {:02X} {:02X})", self.read_register(Register::A), self.read_register(Register::F) , self.read_register(Register::B),self.read_register(Register::C),self.read_register(Register::D),self.read_register(Register::E),self.read_register(Register::H),self.read_register(Register::L),self.read_stack_pointer(),self.read_program_counter(), memory.read(self.read_program_counter()), memory.read(self.read_program_counter()+1),memory.read(self.read_program_counter()+2),memory.read(self.read_program_counter()+3))
+    /// Count one machine cycle for [CpuState::read_t_cycles]
+    pub(crate) fn count_machine_cycle(&mut self) {
+        self.total_t_cycles = self.total_t_cycles.wrapping_add(4);
+    }
+
+    /// Execute one complete instruction and return the number of machine cycles it took.
+    ///
+    /// Every instruction loads the next instruction in its last cycle. That instruction is kept until the next call, so calls to this can not be mixed with executing instructions manually.
+    /// [Halt](instruction::Halt), [Stop](instruction::Stop) and [HaltAndCatchFire](instruction::HaltAndCatchFire) return after a single cycle, because they keep running until an interrupt happens.
+    ///
+    /// If the instruction is at a breakpoint, nothing is executed and [StepResult::BreakpointHit] is returned. The next call executes the instruction.
+    ///
+    /// Only the CPU is advanced. Use [Emulator](crate::emulator::Emulator) to keep the peripherals in sync.
+    ///
+    /// ```
+    /// use rust_gameboy_library::cpu::{CpuState, StepResult};
+    /// use rust_gameboy_library::memory::MemoryController;
+    ///
+    /// // NOP; LD BC,0x1234
+    /// let mut memory = MemoryController::new_with_init(&[0x00, 0x01, 0x34, 0x12]);
+    /// let mut cpu = CpuState::new();
+    ///
+    /// assert_eq!(cpu.step(&mut memory), StepResult::Executed(1));
+    /// assert_eq!(cpu.step(&mut memory), StepResult::Executed(3));
+    /// ```
+    pub fn step<M: MemoryDevice>(&mut self, memory: &mut M) -> StepResult {
+        let mut instruction = match self.next_instruction.take() {
+            Some(instruction) => instruction,
+            None => self.load_instruction(memory),
+        };
+        if !self.breakpoint_reported && self.breakpoints.contains(&self.instruction_address) {
+            self.breakpoint_reported = true;
+            self.next_instruction = Some(instruction);
+            return StepResult::BreakpointHit(self.instruction_address);
+        }
+        let mut cycles = 0;
+        loop {
+            let loaded_instructions = self.loaded_instructions;
+            let waiting = matches!(
+                instruction,
+                InstructionEnum::Halt(_)
+                    | InstructionEnum::Stop(_)
+                    | InstructionEnum::HaltAndCatchFire(_)
+            );
+            instruction = instruction.execute(self, memory);
+            self.count_machine_cycle();
+            cycles += 1;
+            if waiting || self.loaded_instructions != loaded_instructions {
+                break;
+            }
+        }
+        self.next_instruction = Some(instruction);
+        StepResult::Executed(cycles)
+    }
+
+    /// Append the current state to the trace file. Does nothing if tracing is not enabled.
+    ///
+    /// Stops tracing if the state can not be written.
+    fn trace_state<T: MemoryDevice>(&mut self, memory: &mut T) {
+        let Some(trace) = &self.trace else {
+            return;
+        };
+        let state = self.summarize_state(memory);
+        let result = writeln!(
+            trace.lock().unwrap_or_else(PoisonError::into_inner),
+            "{}",
+            state
+        );
+        if let Err(error) = result {
+            self.trace = None;
+            self.trace_error = Some(error.kind());
+        }
+    }
+
+    /// Format the state like Gameboy Doctor, for example
+    /// `A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02`
+    fn summarize_state<T: MemoryDevice>(&self, memory: &T) -> String {
+        let program_counter = self.read_program_counter();
+        let [first, second, third, fourth] =
+            [0, 1, 2, 3].map(|offset| memory.read(program_counter.wrapping_add(offset)));
+        format!(
+            "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+            self.read_register(Register::A),
+            self.read_register(Register::F),
+            self.read_register(Register::B),
+            self.read_register(Register::C),
+            self.read_register(Register::D),
+            self.read_register(Register::E),
+            self.read_register(Register::H),
+            self.read_register(Register::L),
+            self.read_stack_pointer(),
+            program_counter,
+            first,
+            second,
+            third,
+            fourth
+        )
     }
 }
 
@@ -115,6 +443,22 @@ pub trait Cpu {
     fn read_program_counter(&self) -> u16;
     /// Set the address of the current instruction
     fn write_program_counter(&mut self, value: u16);
+    /// Move the program counter by a signed offset. Wraps around at the ends of the address space.
+    ///
+    /// Used by the relative jumps.
+    ///
+    /// ```
+    /// # use rust_gameboy_library::cpu::{CpuState, Cpu};
+    /// # let mut cpu = CpuState::new();
+    /// #
+    /// cpu.write_program_counter(0x0002);
+    /// cpu.offset_program_counter(-4);
+    /// assert_eq!(cpu.read_program_counter(), 0xFFFE);
+    /// ```
+    fn offset_program_counter(&mut self, offset: i8) {
+        let program_counter = self.read_program_counter();
+        self.write_program_counter(program_counter.wrapping_add_signed(offset as i16));
+    }
     /// Get the current stack pointer
     fn read_stack_pointer(&self) -> u16;
     /// Set the current stack pointer
@@ -138,6 +482,40 @@ pub trait Cpu {
     ///
     /// The documentation for [DoubleRegister] contains more information on the values.
     fn write_double_register(&mut self, register: DoubleRegister, value: u16) -> ();
+    /// Read any 16 bit register, including the stack pointer and the program counter.
+    ///
+    /// Intended for debuggers and other tooling. Instructions should use the more specific functions.
+    ///
+    /// ```
+    /// # use rust_gameboy_library::cpu::{CpuState, Cpu, Reg16};
+    /// # let mut cpu = CpuState::new();
+    /// #
+    /// cpu.write_stack_pointer(0xC000);
+    /// assert_eq!(cpu.read_register16(Reg16::SP), 0xC000);
+    /// ```
+    fn read_register16(&self, register: Reg16) -> u16 {
+        match register {
+            Reg16::BC => self.read_double_register(DoubleRegister::BC),
+            Reg16::DE => self.read_double_register(DoubleRegister::DE),
+            Reg16::HL => self.read_double_register(DoubleRegister::HL),
+            Reg16::AF => self.read_double_register(DoubleRegister::AF),
+            Reg16::SP => self.read_stack_pointer(),
+            Reg16::PC => self.read_program_counter(),
+        }
+    }
+    /// Write any 16 bit register, including the stack pointer and the program counter.
+    ///
+    /// See [Cpu::read_register16] for details.
+    fn write_register16(&mut self, register: Reg16, value: u16) {
+        match register {
+            Reg16::BC => self.write_double_register(DoubleRegister::BC, value),
+            Reg16::DE => self.write_double_register(DoubleRegister::DE, value),
+            Reg16::HL => self.write_double_register(DoubleRegister::HL, value),
+            Reg16::AF => self.write_double_register(DoubleRegister::AF, value),
+            Reg16::SP => self.write_stack_pointer(value),
+            Reg16::PC => self.write_program_counter(value),
+        }
+    }
     /// Read the value of a flag
     fn read_flag(&self, flag: Flag) -> bool;
     /// Write the value of a flag
@@ -147,35 +525,17 @@ pub trait Cpu {
     /// Set the IME. This is the only way to write the IME.
     fn write_interrupt_master_enable(&mut self, value: bool);
     /// Check if the IME is enabled. This is the only way to read the IME.
-    fn read_interrupt_master_enable(&mut self) -> bool;
+    fn read_interrupt_master_enable(&self) -> bool;
     // TODO: Understand HALT and STOP wakeup conditions.
     /// Get the instruction of a pending interrupt if there is one.
     fn get_pending_interrupt<M: MemoryDevice>(&mut self, memory: &mut M)
         -> Option<InstructionEnum>;
-    /// Similar to [Cpu::get_pending_interrupt()]
-    ///
-    /// Used to check if we should wakeup from stop.
+    /// Check if the CPU should wake up from [Stop](instruction::Stop).
     ///
-    /// I am unsure about the intended behaviour. The current behaviour is probably wrong.
-    ///
-    /// For now all interrupts except the Joypad interrupts are ignored. It could be that the interrupts are not ignored but they just dont get send, because the screen, timer and serialport are all powered off.
-    fn get_pending_stop_wakeup<M: MemoryDevice>(
-        &mut self,
-        memory: &mut M,
-    ) -> Option<InstructionEnum> {
-        let triggered_interrupts =
-            memory.read_interrupt_enable_register() & memory.read_interrupt_flag_register();
-
-        if triggered_interrupts == 0 {
-            return None;
-        }
-
-        if (triggered_interrupts & (Interrupt::Joypad as u8)) != 0 {
-            memory.write_interrupt_flag(Interrupt::Joypad, false);
-            return Some(InterruptServiceRoutine::create(0x0060).into());
-        }
-
-        return None;
+    /// Only pressing a button wakes the CPU. That requests a [Interrupt::Joypad], which does not need to be enabled.
+    /// The screen, timer and serial connection are powered off while the CPU is stopped, so their interrupts are ignored.
+    fn is_stop_wakeup_pending<M: MemoryDevice>(&self, memory: &M) -> bool {
+        memory.read_interrupt_flag_register() & (Interrupt::Joypad as u8) != 0
     }
 }
 
@@ -250,7 +610,7 @@ impl Cpu for CpuState {
     fn write_interrupt_master_enable(&mut self, value: bool) {
         self.interrupt_master_enable = value;
     }
-    fn read_interrupt_master_enable(&mut self) -> bool {
+    fn read_interrupt_master_enable(&self) -> bool {
         self.interrupt_master_enable
     }
     fn get_pending_interrupt<M: MemoryDevice>(
@@ -274,27 +634,37 @@ impl Cpu for CpuState {
 
         if (triggered_interrupts & (Interrupt::VBlank as u8)) != 0 {
             memory.write_interrupt_flag(Interrupt::VBlank, false);
-            return Some(InterruptServiceRoutine::create(0x0040).into());
+            return Some(
+                InterruptServiceRoutine::create(Interrupt::VBlank.handler_address()).into(),
+            );
         }
 
         if (triggered_interrupts & (Interrupt::LcdStat as u8)) != 0 {
             memory.write_interrupt_flag(Interrupt::LcdStat, false);
-            return Some(InterruptServiceRoutine::create(0x0048).into());
+            return Some(
+                InterruptServiceRoutine::create(Interrupt::LcdStat.handler_address()).into(),
+            );
         }
 
         if (triggered_interrupts & (Interrupt::Timer as u8)) != 0 {
             memory.write_interrupt_flag(Interrupt::Timer, false);
-            return Some(InterruptServiceRoutine::create(0x0050).into());
+            return Some(
+                InterruptServiceRoutine::create(Interrupt::Timer.handler_address()).into(),
+            );
         }
 
         if (triggered_interrupts & (Interrupt::Serial as u8)) != 0 {
             memory.write_interrupt_flag(Interrupt::Serial, false);
-            return Some(InterruptServiceRoutine::create(0x0058).into());
+            return Some(
+                InterruptServiceRoutine::create(Interrupt::Serial.handler_address()).into(),
+            );
         }
 
         if (triggered_interrupts & (Interrupt::Joypad as u8)) != 0 {
             memory.write_interrupt_flag(Interrupt::Joypad, false);
-            return Some(InterruptServiceRoutine::create(0x0060).into());
+            return Some(
+                InterruptServiceRoutine::create(Interrupt::Joypad.handler_address()).into(),
+            );
         }
 
         return None;
@@ -352,6 +722,23 @@ impl Register {
     }
 }
 
+/// Formats the register with its assembler name, for example `a`
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Register::B => "b",
+            Register::C => "c",
+            Register::D => "d",
+            Register::E => "e",
+            Register::H => "h",
+            Register::L => "l",
+            Register::F => "f",
+            Register::A => "a",
+        };
+        f.write_str(name)
+    }
+}
+
 struct RegisterCombination {
     lsb: Register,
     msb: Register,
@@ -375,8 +762,6 @@ pub enum DoubleRegister {
     ///
     /// Does not allow writing the bits 0-3 of F. See [Register::F] for details.
     AF = 3,
-    // /// A double register consisting of [Register::StackpointerLsb] and [Register::StackpointerMsb].
-    // SP = 3,
 }
 
 impl DoubleRegister {
@@ -406,6 +791,101 @@ impl DoubleRegister {
     }
 }
 
+/// Formats the double register with its assembler name, for example `hl`
+impl fmt::Display for DoubleRegister {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DoubleRegister::BC => "bc",
+            DoubleRegister::DE => "de",
+            DoubleRegister::HL => "hl",
+            DoubleRegister::AF => "af",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The operand of the 16 bit instructions that can access the stack pointer instead of [DoubleRegister::AF], like `INC SP` or `LD SP,nn`.
+///
+/// The stack pointer is encoded with the same value as [DoubleRegister::AF] in the other instructions.
+#[derive(TryFromPrimitive, Debug, IntoPrimitive, Clone, Copy)]
+#[repr(u8)]
+pub enum DoubleRegisterOrStackPointer {
+    /// See [DoubleRegister::BC]
+    BC = 0,
+    /// See [DoubleRegister::DE]
+    DE = 1,
+    /// See [DoubleRegister::HL]
+    HL = 2,
+    /// The stack pointer. It is not made of two 8 bit registers.
+    SP = 3,
+}
+
+impl DoubleRegisterOrStackPointer {
+    /// Get the [DoubleRegister] of the operand, or `None` if it is the stack pointer
+    ///
+    /// ```
+    /// # use rust_gameboy_library::cpu::{DoubleRegister, DoubleRegisterOrStackPointer};
+    /// #
+    /// assert!(matches!(DoubleRegisterOrStackPointer::DE.double_register(), Some(DoubleRegister::DE)));
+    /// assert!(DoubleRegisterOrStackPointer::SP.double_register().is_none());
+    /// ```
+    pub fn double_register(&self) -> Option<DoubleRegister> {
+        match self {
+            DoubleRegisterOrStackPointer::BC => Some(DoubleRegister::BC),
+            DoubleRegisterOrStackPointer::DE => Some(DoubleRegister::DE),
+            DoubleRegisterOrStackPointer::HL => Some(DoubleRegister::HL),
+            DoubleRegisterOrStackPointer::SP => None,
+        }
+    }
+
+    fn numerical_id(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl From<DoubleRegisterOrStackPointer> for Reg16 {
+    fn from(register: DoubleRegisterOrStackPointer) -> Self {
+        match register {
+            DoubleRegisterOrStackPointer::BC => Reg16::BC,
+            DoubleRegisterOrStackPointer::DE => Reg16::DE,
+            DoubleRegisterOrStackPointer::HL => Reg16::HL,
+            DoubleRegisterOrStackPointer::SP => Reg16::SP,
+        }
+    }
+}
+
+/// Formats the operand with its assembler name, for example `sp`
+impl fmt::Display for DoubleRegisterOrStackPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DoubleRegisterOrStackPointer::BC => "bc",
+            DoubleRegisterOrStackPointer::DE => "de",
+            DoubleRegisterOrStackPointer::HL => "hl",
+            DoubleRegisterOrStackPointer::SP => "sp",
+        };
+        f.write_str(name)
+    }
+}
+
+/// All 16 bit values of the cpu that can be accessed with [Cpu::read_register16] and [Cpu::write_register16].
+///
+/// Unlike [DoubleRegister] this also contains the stack pointer and the program counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reg16 {
+    /// See [DoubleRegister::BC]
+    BC,
+    /// See [DoubleRegister::DE]
+    DE,
+    /// See [DoubleRegister::HL]
+    HL,
+    /// See [DoubleRegister::AF]
+    AF,
+    /// The stack pointer
+    SP,
+    /// The program counter
+    PC,
+}
+
 /// Condition codes that are used in conditional jump opcodes
 #[derive(TryFromPrimitive, Debug, IntoPrimitive, Clone, Copy)]
 #[repr(u8)]
@@ -428,6 +908,19 @@ pub enum ConditionCode {
     CarryFlagSet = 0b11,
 }
 
+/// Formats the condition with its assembler name, for example `NZ`
+impl fmt::Display for ConditionCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConditionCode::ZeroFlagUnset => "NZ",
+            ConditionCode::ZeroFlagSet => "Z",
+            ConditionCode::CarryFlagUnset => "NC",
+            ConditionCode::CarryFlagSet => "C",
+        };
+        f.write_str(name)
+    }
+}
+
 /// Condition codes that are used in conditional jump opcodes
 ///
 /// The value of every element is a byte with a single bit set to 1. The set bit corresponds to the flags bit in the flags register.
@@ -445,6 +938,19 @@ pub enum Flag {
     Carry = 0b00010000,
 }
 
+/// Formats the flag with its usual single letter name, for example `z`
+impl fmt::Display for Flag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Flag::Zero => "z",
+            Flag::Subtract => "n",
+            Flag::HalfCarry => "h",
+            Flag::Carry => "c",
+        };
+        f.write_str(name)
+    }
+}
+
 /// Interrupt codes that can be used to enable and request interrupts from the CPU.
 ///
 /// You can use them with the matching methods on the CPU.
@@ -479,12 +985,41 @@ pub enum Interrupt {
     Joypad = 0b00010000,
 }
 
+impl Interrupt {
+    /// Get the address of the interrupt handler that gets called by the [InterruptServiceRoutine].
+    ///
+    /// The handlers are 8 bytes apart, starting at 0x0040 for [Interrupt::VBlank].
+    pub const fn handler_address(&self) -> u16 {
+        match self {
+            Interrupt::VBlank => 0x0040,
+            Interrupt::LcdStat => 0x0048,
+            Interrupt::Timer => 0x0050,
+            Interrupt::Serial => 0x0058,
+            Interrupt::Joypad => 0x0060,
+        }
+    }
+}
+
+// The handler address of an interrupt is determined by the position of its bit in IE and IF.
+const _: () = {
+    const fn expected_handler_address(interrupt: Interrupt) -> u16 {
+        0x0040 + 8 * (interrupt as u8).trailing_zeros() as u16
+    }
+    assert!(Interrupt::VBlank.handler_address() == expected_handler_address(Interrupt::VBlank));
+    assert!(Interrupt::LcdStat.handler_address() == expected_handler_address(Interrupt::LcdStat));
+    assert!(Interrupt::Timer.handler_address() == expected_handler_address(Interrupt::Timer));
+    assert!(Interrupt::Serial.handler_address() == expected_handler_address(Interrupt::Serial));
+    assert!(Interrupt::Joypad.handler_address() == expected_handler_address(Interrupt::Joypad));
+};
+
 /// Addresses that can be used with [instruction::Restart]
-#[derive(Debug, Clone, Copy)]
+///
+/// Can be created from the three bits of the opcode with [TryFrom].
+#[derive(TryFromPrimitive, Debug, Clone, Copy)]
 #[repr(u8)]
 pub enum RestartAddress {
     /// Restart at 0x00
-    A,
+    A = 0b000,
     /// Restart at 0x08
     B,
     /// Restart at 0x10
@@ -501,21 +1036,6 @@ pub enum RestartAddress {
     H,
 }
 
-impl Into<RestartAddress> for u8 {
-    fn into(self) -> RestartAddress {
-        match self {
-            0b000 => RestartAddress::A,
-            0b001 => RestartAddress::B,
-            0b010 => RestartAddress::C,
-            0b011 => RestartAddress::D,
-            0b100 => RestartAddress::E,
-            0b101 => RestartAddress::F,
-            0b110 => RestartAddress::G,
-            0b111 => RestartAddress::H,
-            _ => panic!("Can only convert opcode parts between 0b000 and 0b111 to RestartAddress"),
-        }
-    }
-}
 impl Into<u8> for RestartAddress {
     fn into(self) -> u8 {
         match self {
@@ -551,11 +1071,13 @@ impl RestartAddress {
 /// [Bit::Zero] is the least significant bit.
 ///
 /// [Bit::Seven] is the most significant bit.
-#[derive(Debug, Clone, Copy)]
+///
+/// Can be created from the position of the bit with [TryFrom].
+#[derive(TryFromPrimitive, Debug, Clone, Copy)]
 #[repr(u8)]
 pub enum Bit {
     /// The bit at position 0.
-    Zero,
+    Zero = 0b000,
     /// The bit at position 1.
     One,
     /// The bit at position 2.
@@ -572,22 +1094,6 @@ pub enum Bit {
     Seven,
 }
 
-impl Into<Bit> for u8 {
-    fn into(self) -> Bit {
-        match self {
-            0b000 => Bit::Zero,
-            0b001 => Bit::One,
-            0b010 => Bit::Two,
-            0b011 => Bit::Three,
-            0b100 => Bit::Four,
-            0b101 => Bit::Five,
-            0b110 => Bit::Six,
-            0b111 => Bit::Seven,
-            _ => panic!("Can only convert opcode parts between 0b000 and 0b111 to a Bit"),
-        }
-    }
-}
-
 impl Into<u8> for Bit {
     fn into(self) -> u8 {
         self as u8
@@ -611,9 +1117,13 @@ impl Bit {
 
 #[cfg(test)]
 mod tests {
-    use super::instruction::{InstructionEnum, LoadFromRegisterToRegister};
+    use super::instruction::Instruction;
+    use super::instruction::{decode, InstructionEnum, LoadFromRegisterToRegister};
     use super::Cpu;
-    use super::{CpuState, DoubleRegister};
+    use super::{
+        Bit, ConditionCode, CpuState, DoubleRegister, DoubleRegisterOrStackPointer, Flag,
+        Interrupt, Reg16, RestartAddress, StepResult,
+    };
     use crate::cpu::Register;
     use crate::memory::MemoryController;
 
@@ -641,6 +1151,282 @@ mod tests {
         assert_eq!(cpu.read_double_register(DoubleRegister::BC), 9874);
     }
 
+    #[test]
+    fn write_read_double_register_or_stack_pointer() {
+        for id in 0..4u8 {
+            let operand = DoubleRegisterOrStackPointer::try_from(id).unwrap();
+            let value = 0x1234 + id as u16;
+            let mut cpu = CpuState::new();
+            cpu.write_register16(operand.into(), value);
+            assert_eq!(cpu.read_register16(operand.into()), value);
+            match operand.double_register() {
+                Some(register) => assert_eq!(cpu.read_double_register(register), value),
+                None => assert_eq!(cpu.read_stack_pointer(), value),
+            }
+        }
+    }
+
+    #[test]
+    fn stack_pointer_operand_is_separate_from_af() {
+        let mut cpu = CpuState::new();
+        cpu.write_double_register(DoubleRegister::AF, 0x12FF);
+        cpu.write_register16(DoubleRegisterOrStackPointer::SP.into(), 0xDFFE);
+        assert_eq!(cpu.read_stack_pointer(), 0xDFFE);
+        assert_eq!(cpu.read_register16(Reg16::SP), 0xDFFE);
+        // AF still can not set the lower bits of F
+        assert_eq!(cpu.read_double_register(DoubleRegister::AF), 0x12F0);
+
+        let instruction = decode(0x33);
+        assert!(matches!(
+            instruction,
+            InstructionEnum::IncrementDoubleRegister(ref increment)
+                if matches!(increment.destination, DoubleRegisterOrStackPointer::SP)
+        ));
+        assert_eq!(instruction.encode(), [0x33]);
+        assert_eq!(DoubleRegisterOrStackPointer::SP.to_string(), "sp");
+    }
+
+    #[test]
+    fn step_returns_the_cycles_of_each_instruction() {
+        // NOP; LD BC,0x1234; PUSH BC; CALL 0x0010; ...; 0x0010: INC A; HALT
+        let mut program = vec![0x00, 0x01, 0x34, 0x12, 0xC5, 0xCD, 0x10, 0x00];
+        program.resize(0x10, 0x00);
+        program.extend([0x3C, 0x76]);
+        let mut memory = MemoryController::new_with_init(&program);
+        let mut cpu = CpuState::new();
+        cpu.write_stack_pointer(0xC000);
+
+        let cycles: Vec<StepResult> = (0..6).map(|_| cpu.step(&mut memory)).collect();
+        assert_eq!(cycles, [1, 3, 4, 6, 1, 1].map(StepResult::Executed));
+        assert_eq!(cpu.read_register(Register::A), 0x02);
+        assert_eq!(cpu.read_double_register(DoubleRegister::BC), 0x1234);
+        assert_eq!(cpu.read_stack_pointer(), 0xC000 - 4);
+        // The CPU stays halted
+        assert_eq!(cpu.step(&mut memory), StepResult::Executed(1));
+        assert_eq!(cpu.read_program_counter(), 0x12);
+    }
+
+    #[test]
+    fn nop_takes_four_clock_cycles() {
+        // NOP; LD BC,0x1234
+        let mut memory = MemoryController::new_with_init(&[0x00, 0x01, 0x34, 0x12]);
+        let mut cpu = CpuState::new();
+        assert_eq!(cpu.read_t_cycles(), 0);
+
+        cpu.step(&mut memory);
+        assert_eq!(cpu.read_t_cycles(), 4);
+        cpu.step(&mut memory);
+        assert_eq!(cpu.read_t_cycles(), 16);
+    }
+
+    #[test]
+    fn interrupt_master_enable_can_be_read_through_a_shared_reference() {
+        let mut cpu = CpuState::new();
+        cpu.write_interrupt_master_enable(true);
+        let shared_cpu: &CpuState = &cpu;
+        assert!(shared_cpu.read_interrupt_master_enable());
+    }
+
+    #[test]
+    fn enums_are_displayed_with_their_assembler_names() {
+        assert_eq!(format!("{}", ConditionCode::ZeroFlagUnset), "NZ");
+        assert_eq!(format!("{}", ConditionCode::CarryFlagSet), "C");
+        assert_eq!(format!("{}", Register::A), "a");
+        assert_eq!(format!("{}", DoubleRegister::HL), "hl");
+        assert_eq!(format!("{}", Flag::HalfCarry), "h");
+        // Debug keeps the full names
+        assert_eq!(
+            format!("{:?}", ConditionCode::ZeroFlagUnset),
+            "ZeroFlagUnset"
+        );
+    }
+
+    #[test]
+    fn restart_addresses_and_bits_are_only_created_from_three_bits() {
+        assert!(matches!(RestartAddress::try_from(7), Ok(RestartAddress::H)));
+        assert!(RestartAddress::try_from(8).is_err());
+        assert!(matches!(Bit::try_from(0), Ok(Bit::Zero)));
+        assert!(Bit::try_from(8).is_err());
+    }
+
+    #[test]
+    fn step_stops_at_breakpoints() {
+        // NOP; INC A; INC A; INC A
+        let mut memory = MemoryController::new_with_init(&[0x00, 0x3C, 0x3C, 0x3C]);
+        let mut cpu = CpuState::new();
+        cpu.write_register(Register::A, 0);
+        cpu.add_breakpoint(0x0002);
+
+        assert_eq!(cpu.step(&mut memory), StepResult::Executed(1));
+        assert_eq!(cpu.step(&mut memory), StepResult::Executed(1));
+        assert_eq!(cpu.step(&mut memory), StepResult::BreakpointHit(0x0002));
+        assert_eq!(cpu.read_register(Register::A), 1);
+
+        // Stepping again continues after the breakpoint
+        assert_eq!(cpu.step(&mut memory), StepResult::Executed(1));
+        assert_eq!(cpu.read_register(Register::A), 2);
+    }
+
+    #[test]
+    fn trace_is_only_written_when_enabled() {
+        let path = std::env::temp_dir().join("rust_gameboy_library_test_trace.txt");
+        let _ = std::fs::remove_file(&path);
+        let mut memory = MemoryController::new_with_init(&[0x00, 0x00]);
+
+        let mut cpu = CpuState::new();
+        cpu.load_instruction(&mut memory);
+        assert!(!path.exists());
+
+        cpu.enable_trace(&path).unwrap();
+        cpu.load_instruction(&mut memory);
+        cpu.disable_trace().unwrap();
+        cpu.load_instruction(&mut memory);
+
+        let trace = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            trace,
+            "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0001 PCMEM:00,00,00,00\n"
+        );
+        assert_eq!(cpu.trace_error(), None);
+    }
+
+    #[test]
+    fn trace_wraps_around_at_the_end_of_memory() {
+        let path = std::env::temp_dir().join("rust_gameboy_library_test_trace_wrap.txt");
+        let mut memory = MemoryController::new_with_init(&[0x12, 0x34]);
+
+        let mut cpu = CpuState::new_at(0xFFFE);
+        cpu.enable_trace(&path).unwrap();
+        cpu.load_instruction(&mut memory);
+        cpu.disable_trace().unwrap();
+
+        let trace = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(trace.ends_with("PC:FFFE PCMEM:00,00,12,34\n"), "{}", trace);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn trace_write_errors_are_reported() {
+        let mut memory = MemoryController::new_with_init(&[0x00]);
+        let mut cpu = CpuState::new();
+        cpu.enable_trace(std::path::Path::new("/dev/full")).unwrap();
+        cpu.load_instruction(&mut memory);
+        assert!(cpu.disable_trace().is_err());
+    }
+
+    #[test]
+    fn post_boot_state_matches_the_boot_rom_handoff() {
+        let cpu = CpuState::new_post_boot();
+        assert_eq!(cpu.read_register16(Reg16::PC), 0x0100);
+        assert_eq!(cpu.read_register16(Reg16::SP), 0xFFFE);
+        assert_eq!(cpu.read_register16(Reg16::AF), 0x01B0);
+        assert_eq!(cpu.read_register16(Reg16::BC), 0x0013);
+        assert_eq!(cpu.read_register16(Reg16::DE), 0x00D8);
+        assert_eq!(cpu.read_register16(Reg16::HL), 0x014D);
+
+        assert_eq!(CpuState::new_at(0x1234).read_program_counter(), 0x1234);
+        assert_eq!(CpuState::new().read_program_counter(), 0);
+    }
+
+    #[test]
+    fn reset_restores_the_initial_state() {
+        let mut cpu = CpuState::new();
+        let mut memory = MemoryController::new_for_tests();
+        cpu.step(&mut memory);
+        cpu.step(&mut memory);
+        cpu.write_program_counter(0x4321);
+        cpu.write_stack_pointer(0xC000);
+        cpu.write_double_register(DoubleRegister::AF, 0x1234);
+        cpu.write_double_register(DoubleRegister::BC, 0x5678);
+        cpu.write_double_register(DoubleRegister::DE, 0x9ABC);
+        cpu.write_double_register(DoubleRegister::HL, 0xDEF0);
+        cpu.write_interrupt_master_enable(true);
+
+        cpu.reset();
+
+        let fresh = CpuState::new();
+        for register in [
+            Reg16::AF,
+            Reg16::BC,
+            Reg16::DE,
+            Reg16::HL,
+            Reg16::SP,
+            Reg16::PC,
+        ] {
+            assert_eq!(
+                cpu.read_register16(register),
+                fresh.read_register16(register)
+            );
+        }
+        assert_eq!(
+            cpu.read_interrupt_master_enable(),
+            fresh.read_interrupt_master_enable()
+        );
+        assert_eq!(cpu.loaded_instructions(), 0);
+        assert_eq!(cpu.read_t_cycles(), 0);
+    }
+
+    #[test]
+    fn cpu_state_round_trips_through_bytes() {
+        let mut cpu = CpuState::new();
+        cpu.write_program_counter(0x4321);
+        cpu.write_stack_pointer(0xC0DE);
+        cpu.write_double_register(DoubleRegister::AF, 0x12B0);
+        cpu.write_double_register(DoubleRegister::BC, 0x3456);
+        cpu.write_double_register(DoubleRegister::DE, 0x789A);
+        cpu.write_double_register(DoubleRegister::HL, 0xBCDE);
+        cpu.write_interrupt_master_enable(true);
+
+        let bytes = cpu.to_bytes();
+        let restored = CpuState::from_bytes(&bytes);
+
+        assert_eq!(restored.to_bytes(), bytes);
+        assert_eq!(restored.read_register16(Reg16::PC), 0x4321);
+        assert_eq!(restored.read_register16(Reg16::SP), 0xC0DE);
+        assert_eq!(restored.read_register16(Reg16::AF), 0x12B0);
+        assert_eq!(restored.read_register16(Reg16::HL), 0xBCDE);
+        assert!(restored.read_interrupt_master_enable());
+    }
+
+    #[test]
+    fn pending_ime_and_halt_bug_round_trip_through_bytes() {
+        let mut memory = MemoryController::new_with_init(&[0x00, 0x00]);
+        let mut cpu = CpuState::new();
+        cpu.schedule_interrupt_master_enable();
+        cpu.trigger_halt_bug();
+
+        let bytes = cpu.to_bytes();
+        assert_eq!(bytes[12], 0b110);
+        let mut restored = CpuState::from_bytes(&bytes);
+        assert_eq!(restored.to_bytes(), bytes);
+
+        // The HALT bug keeps the program counter and the IME is enabled after the next fetch
+        assert!(!restored.read_interrupt_master_enable());
+        restored.load_instruction(&mut memory);
+        assert_eq!(restored.read_program_counter(), 0);
+        assert!(restored.read_interrupt_master_enable());
+    }
+
+    #[test]
+    fn register16_reads_and_writes_sp_and_pc() {
+        let mut cpu = CpuState::new();
+        cpu.write_stack_pointer(0xC123);
+        cpu.write_program_counter(0x0150);
+        assert_eq!(cpu.read_register16(Reg16::SP), 0xC123);
+        assert_eq!(cpu.read_register16(Reg16::PC), 0x0150);
+
+        cpu.write_register16(Reg16::SP, 0xDFFF);
+        cpu.write_register16(Reg16::PC, 0x4000);
+        assert_eq!(cpu.read_stack_pointer(), 0xDFFF);
+        assert_eq!(cpu.read_program_counter(), 0x4000);
+
+        cpu.write_register16(Reg16::HL, 0x1234);
+        assert_eq!(cpu.read_double_register(DoubleRegister::HL), 0x1234);
+        assert_eq!(cpu.read_register16(Reg16::HL), 0x1234);
+    }
+
     #[test]
     fn cpu_read_program_counter_works() {
         let mut cpu = CpuState::new();
@@ -654,6 +1440,42 @@ mod tests {
         assert_eq!(opcode, 8);
     }
 
+    #[test]
+    fn interrupts_map_to_their_documented_handlers() {
+        assert_eq!(Interrupt::VBlank.handler_address(), 0x0040);
+        assert_eq!(Interrupt::LcdStat.handler_address(), 0x0048);
+        assert_eq!(Interrupt::Timer.handler_address(), 0x0050);
+        assert_eq!(Interrupt::Serial.handler_address(), 0x0058);
+        assert_eq!(Interrupt::Joypad.handler_address(), 0x0060);
+    }
+
+    #[test]
+    fn coverage_marks_executed_addresses() {
+        let mut cpu = CpuState::new();
+        cpu.enable_coverage(0x8000);
+
+        // LD A,1; JR +2; LD A,2 (skipped); INC A; NOP
+        let mut memory =
+            MemoryController::new_with_init(&[0x3E, 0x01, 0x18, 0x02, 0x3E, 0x02, 0x3C]);
+        let mut instruction = cpu.load_instruction(&mut memory);
+        while cpu.read_program_counter() <= 7 {
+            instruction = instruction.execute(&mut cpu, &mut memory);
+        }
+
+        let coverage = cpu.coverage();
+        assert_eq!(coverage.iter().collect::<Vec<_>>(), vec![0, 2, 6, 7]);
+        assert!(!coverage.contains(1));
+        assert!(!coverage.contains(4));
+    }
+
+    #[test]
+    fn coverage_is_disabled_by_default() {
+        let mut cpu = CpuState::new();
+        let memory = MemoryController::new_with_init(&[0x00]);
+        cpu.load_opcode(&memory);
+        assert!(cpu.coverage().is_empty());
+    }
+
     #[test]
     fn cpu_can_fetch_and_decode_instructions() {
         let mut cpu = CpuState::new();
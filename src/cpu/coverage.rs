@@ -0,0 +1,77 @@
+/// A fixed size set of indices, stored as one bit per index.
+///
+/// Used by [CpuState::coverage](super::CpuState::coverage) to record which addresses were executed.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    /// Create a new empty set that can hold indices from `0` to `len - 1`
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    /// The number of indices this set can hold
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the set can not hold any indices
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add an index to the set.
+    ///
+    /// Indices outside of the set are ignored.
+    pub fn insert(&mut self, index: usize) {
+        if index < self.len {
+            self.words[index / 64] |= 1 << (index % 64);
+        }
+    }
+
+    /// Check if an index is in the set
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.len && (self.words[index / 64] & (1 << (index % 64))) != 0
+    }
+
+    /// Count the indices in the set
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterate over all indices in the set in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|index| self.contains(*index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitSet;
+
+    #[test]
+    fn bitset_contains_inserted_indices() {
+        let mut set = BitSet::new(100);
+        set.insert(0);
+        set.insert(64);
+        set.insert(99);
+        set.insert(100);
+
+        assert!(set.contains(0));
+        assert!(set.contains(64));
+        assert!(set.contains(99));
+        assert!(!set.contains(1));
+        assert!(!set.contains(100));
+        assert_eq!(set.count(), 3);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0, 64, 99]);
+    }
+}
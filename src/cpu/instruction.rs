@@ -2,15 +2,29 @@ use super::CpuState;
 use crate::memory::MemoryDevice;
 use enum_dispatch::enum_dispatch;
 
+/// Contains the number of machine cycles of each instruction
+pub mod cycles;
 mod decode;
 mod decode_cb;
+mod decode_full;
+/// Turns the binary representation of instructions into assembler mnemonics
+pub mod disassemble;
+mod encoded_instruction;
+/// Describes how instructions affect the flags
+pub mod flag_effects;
 /// Really hacky macro for generating arithmetic instructions
 pub mod generate_instruction;
 /// Different phases for instructions
 pub mod phases;
+/// Describes which registers instructions read and write
+pub mod register_effects;
 
 pub use decode::decode;
 pub use decode_cb::decode_cb;
+pub use decode_full::decode_full;
+pub use encoded_instruction::EncodedInstruction;
+pub use flag_effects::{FlagEffect, FlagEffects};
+pub use register_effects::RegisterSet;
 
 macro_rules! generate_instruction_enum {
     ($enum_name:ident, $( ( $module_path:ident, $( $instruction:ident ),* ) ),+) => {
@@ -30,7 +44,7 @@ macro_rules! generate_instruction_enum {
 
         /// Contains a variant for every [Instruction]
         #[enum_dispatch]
-        #[derive(Debug)]
+        #[derive(Debug, Clone)]
         pub enum $enum_name {
             $(
                 $(
@@ -260,6 +274,51 @@ pub trait Instruction: Sized {
     /// ```
     /// # use rust_gameboy_library::cpu::Register;
     /// # use rust_gameboy_library::cpu::instruction::LoadFromRegisterToRegister;
+    /// # use rust_gameboy_library::cpu::instruction::{EncodedInstruction, Instruction};
+    /// #
+    /// let instruction = LoadFromRegisterToRegister {
+    ///     source: Register::A,
+    ///     destination: Register::C,
+    /// };
+    ///
+    /// let encoded: EncodedInstruction = instruction.encode();
+    /// assert_eq!(encoded, [0b01001111u8]);
+    /// ```
+    fn encode(&self) -> EncodedInstruction;
+    /// Encode a instruction into it's complete binary representation, regardless of the current phase.
+    ///
+    /// Immediate values that have not been read yet are encoded with their current value, which is `0` for freshly decoded instructions.
+    /// Use this for assembling and disassembling, where [Instruction::encode] would return too few bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_gameboy_library::cpu::DoubleRegisterOrStackPointer;
+    /// # use rust_gameboy_library::cpu::instruction::LoadImmediateToDoubleRegister;
+    /// # use rust_gameboy_library::cpu::instruction::Instruction;
+    /// # use rust_gameboy_library::cpu::instruction::phases::ThreePhases;
+    /// #
+    /// let instruction = LoadImmediateToDoubleRegister {
+    ///     destination: DoubleRegisterOrStackPointer::HL,
+    ///     value: 0x1234,
+    ///     phase: ThreePhases::First,
+    /// };
+    ///
+    /// assert_eq!(instruction.encode(), Vec::from([0x21]));
+    /// assert_eq!(instruction.encode_full(), Vec::from([0x21, 0x34, 0x12]));
+    /// ```
+    fn encode_full(&self) -> EncodedInstruction {
+        self.encode()
+    }
+    /// Get the assembler mnemonic of the instruction, like `LD C,A` or `JP 0x1234`.
+    ///
+    /// Immediate values that have not been read yet are shown with their current value, see [Instruction::encode_full].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_gameboy_library::cpu::Register;
+    /// # use rust_gameboy_library::cpu::instruction::LoadFromRegisterToRegister;
     /// # use rust_gameboy_library::cpu::instruction::Instruction;
     /// #
     /// let instruction = LoadFromRegisterToRegister {
@@ -267,17 +326,634 @@ pub trait Instruction: Sized {
     ///     destination: Register::C,
     /// };
     ///
-    /// let encoded: Vec<u8> = instruction.encode();
-    /// assert_eq!(encoded, Vec::from([0b01001111u8]));
+    /// assert_eq!(instruction.disassemble(), "LD C,A");
     /// ```
-    fn encode(&self) -> Vec<u8>;
+    fn disassemble(&self) -> String {
+        disassemble::disassemble(&self.encode_full())
+    }
+    /// Get the number of machine cycles it takes to execute the instruction from start to end.
+    ///
+    /// Conditional instructions report the number of cycles they take when the condition is met. See [cycles::cycles] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_gameboy_library::cpu::instruction::{decode, Instruction};
+    /// #
+    /// // CALL a16
+    /// assert_eq!(decode(0xCD).cycles(), 6);
+    /// ```
+    fn cycles(&self) -> u8 {
+        cycles::cycles(&self.encode())
+    }
+    /// Describe which flags the instruction sets, resets, modifies or leaves unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_gameboy_library::cpu::Register;
+    /// # use rust_gameboy_library::cpu::instruction::{AddRegister, FlagEffects, Instruction, LoadFromRegisterToRegister};
+    /// #
+    /// let load = LoadFromRegisterToRegister {
+    ///     source: Register::B,
+    ///     destination: Register::A,
+    /// };
+    /// assert_eq!(load.affects_flags(), FlagEffects::NONE);
+    ///
+    /// let add = AddRegister {
+    ///     operand: Register::B,
+    /// };
+    /// assert!(add.affects_flags().affects_any());
+    /// ```
+    fn affects_flags(&self) -> FlagEffects {
+        FlagEffects::NONE
+    }
+    /// Get the 8 bit registers the instruction reads and the ones it writes, in that order.
+    ///
+    /// [Register::F](super::Register::F) is read if the result depends on a flag and written if any flag can change.
+    /// Registers that are only used to address memory are reads as well. Useful for dependency analysis over a sequence of instructions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rust_gameboy_library::cpu::Register;
+    /// # use rust_gameboy_library::cpu::instruction::{Instruction, LoadFromRegisterToRegister, RegisterSet};
+    /// #
+    /// let load = LoadFromRegisterToRegister {
+    ///     source: Register::B,
+    ///     destination: Register::A,
+    /// };
+    /// let (reads, writes) = load.register_effects();
+    /// assert_eq!(reads, RegisterSet::new().with(Register::B));
+    /// assert_eq!(writes, RegisterSet::new().with(Register::A));
+    /// ```
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (RegisterSet::EMPTY, RegisterSet::EMPTY)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::cpu::instruction::Instruction;
-    use crate::cpu::{Cpu, CpuState, Register};
-    use crate::memory::MemoryController;
+    use super::{decode, decode_cb, FlagEffect, FlagEffects, InstructionEnum, RegisterSet};
+    use crate::cpu::instruction::phases::{
+        FivePhases, FourPhases, SixPhases, ThreePhases, TwoPhases,
+    };
+    use crate::cpu::instruction::{
+        AddImmediateOffsetToSp, Call, Instruction, InterruptServiceRoutine, JumpToImmediateAddress,
+        LoadAccumulatorToHlAndDecrement, LoadAccumulatorToHlAndIncrement,
+        LoadHlToAccumulatorAndDecrement, LoadHlToAccumulatorAndIncrement,
+        LoadImmediateToDoubleRegister, LoadSpToImmediateAddress, PopDoubleRegister,
+        PushDoubleRegister, Restart, ReturnFromInterrupt,
+    };
+    use crate::cpu::{
+        Cpu, CpuState, DoubleRegister, DoubleRegisterOrStackPointer, Flag, Register, RestartAddress,
+    };
+    use crate::memory::serial::serial_connection::LoggerSerialConnection;
+    use crate::memory::video::display_connection::DummyDisplayConnection;
+    use crate::memory::{MemoryController, MemoryDevice};
+    use std::mem::discriminant;
+
+    /// Get the index of the current phase of an instruction, starting at 0 for the first phase.
+    ///
+    /// Instructions without phases are always in their first phase.
+    fn phase_index(instruction: &InstructionEnum) -> usize {
+        match instruction {
+            InstructionEnum::AddFromHl(instruction) => instruction.phase.index(),
+            InstructionEnum::AddImmediate(instruction) => instruction.phase.index(),
+            InstructionEnum::AddWithCarryFromHl(instruction) => instruction.phase.index(),
+            InstructionEnum::AddWithCarryImmediate(instruction) => instruction.phase.index(),
+            InstructionEnum::SubtractFromHl(instruction) => instruction.phase.index(),
+            InstructionEnum::SubtractImmediate(instruction) => instruction.phase.index(),
+            InstructionEnum::SubtractWithCarryFromHl(instruction) => instruction.phase.index(),
+            InstructionEnum::SubtractWithCarryImmediate(instruction) => instruction.phase.index(),
+            InstructionEnum::BitwiseAndFromHl(instruction) => instruction.phase.index(),
+            InstructionEnum::BitwiseAndImmediate(instruction) => instruction.phase.index(),
+            InstructionEnum::IncrementAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::DecrementAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::BitwiseOrFromHl(instruction) => instruction.phase.index(),
+            InstructionEnum::BitwiseOrImmediate(instruction) => instruction.phase.index(),
+            InstructionEnum::BitwiseExclusiveOrFromHl(instruction) => instruction.phase.index(),
+            InstructionEnum::BitwiseExclusiveOrImmediate(instruction) => instruction.phase.index(),
+            InstructionEnum::RotateLeftAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::RotateRightAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::RotateLeftThroughCarryAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::RotateRightThroughCarryAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::ShiftLeftAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::ShiftRightAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::SwapNibblesAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::CheckBitAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::SetBitAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::ResetBitAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::ShiftRightLogicalAtHl(instruction) => instruction.phase.index(),
+            InstructionEnum::CompareFromHl(instruction) => instruction.phase.index(),
+            InstructionEnum::CompareImmediate(instruction) => instruction.phase.index(),
+            InstructionEnum::Call(instruction) => instruction.phase.index(),
+            InstructionEnum::CallConditional(instruction) => instruction.phase.index(),
+            InstructionEnum::InterruptServiceRoutine(instruction) => instruction.phase.index(),
+            InstructionEnum::JumpByImmediateOffset(instruction) => instruction.phase.index(),
+            InstructionEnum::JumpByImmediateOffsetConditional(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::JumpToImmediateAddress(instruction) => instruction.phase.index(),
+            InstructionEnum::JumpToImmediateAddressConditional(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadAccumulatorToDoubleRegister(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadAccumulatorToHlAndDecrement(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadAccumulatorToHlAndIncrement(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadAccumulatorToImmediateAddress(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadAccumulatorToImmediateOffset(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadAccumulatorToRegisterCOffset(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadFromDoubleRegisterToAccumulator(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadFromHlToRegister(instruction) => instruction.phase.index(),
+            InstructionEnum::LoadFromImmediateAddressToAccumulator(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadFromImmediateOffsetToAccumulator(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadFromRegisterCOffsetToAccumulator(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadHlToAccumulatorAndDecrement(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadHlToAccumulatorAndIncrement(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadHlToSp(instruction) => instruction.phase.index(),
+            InstructionEnum::LoadImmediateToDoubleRegister(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::LoadImmediateToHl(instruction) => instruction.phase.index(),
+            InstructionEnum::LoadImmediateToRegister(instruction) => instruction.phase.index(),
+            InstructionEnum::LoadRegisterToHl(instruction) => instruction.phase.index(),
+            InstructionEnum::LoadSpToImmediateAddress(instruction) => instruction.phase.index(),
+            InstructionEnum::PopDoubleRegister(instruction) => instruction.phase.index(),
+            InstructionEnum::PushDoubleRegister(instruction) => instruction.phase.index(),
+            InstructionEnum::ReturnConditional(instruction) => instruction.phase.index(),
+            InstructionEnum::Return(instruction) => instruction.phase.index(),
+            InstructionEnum::ReturnFromInterrupt(instruction) => instruction.phase.index(),
+            InstructionEnum::AddImmediateOffsetToSp(instruction) => instruction.phase.index(),
+            InstructionEnum::LoadSpPlusImmediateOffsetToHl(instruction) => {
+                instruction.phase.index()
+            }
+            InstructionEnum::AddDoubleRegisterToHl(instruction) => instruction.phase.index(),
+            InstructionEnum::IncrementDoubleRegister(instruction) => instruction.phase.index(),
+            InstructionEnum::DecrementDoubleRegister(instruction) => instruction.phase.index(),
+            InstructionEnum::Restart(instruction) => instruction.phase.index(),
+            InstructionEnum::AddRegister(_)
+            | InstructionEnum::AddWithCarryRegister(_)
+            | InstructionEnum::SubtractRegister(_)
+            | InstructionEnum::SubtractWithCarryRegister(_)
+            | InstructionEnum::BitwiseAndRegister(_)
+            | InstructionEnum::IncrementRegister(_)
+            | InstructionEnum::DecrementRegister(_)
+            | InstructionEnum::BitwiseOrRegister(_)
+            | InstructionEnum::BitwiseExclusiveOrRegister(_)
+            | InstructionEnum::RotateLeftRegister(_)
+            | InstructionEnum::RotateRightRegister(_)
+            | InstructionEnum::RotateLeftThroughCarryRegister(_)
+            | InstructionEnum::RotateRightThroughCarryRegister(_)
+            | InstructionEnum::ShiftLeftRegister(_)
+            | InstructionEnum::ShiftRightRegister(_)
+            | InstructionEnum::SwapNibblesRegister(_)
+            | InstructionEnum::CheckBitRegister(_)
+            | InstructionEnum::SetBitRegister(_)
+            | InstructionEnum::ResetBitRegister(_)
+            | InstructionEnum::ShiftRightLogicalRegister(_)
+            | InstructionEnum::CompareRegister(_)
+            | InstructionEnum::Complement(_)
+            | InstructionEnum::DisableInterrupts(_)
+            | InstructionEnum::EnableInterrupts(_)
+            | InstructionEnum::Halt(_)
+            | InstructionEnum::InvertCarry(_)
+            | InstructionEnum::JumpToHl(_)
+            | InstructionEnum::LoadFromRegisterToRegister(_)
+            | InstructionEnum::Nop(_)
+            | InstructionEnum::SetCarry(_)
+            | InstructionEnum::Stop(_)
+            | InstructionEnum::ToBinaryCodedDecimal(_)
+            | InstructionEnum::HaltAndCatchFire(_)
+            | InstructionEnum::RotateAccumulatorLeft(_)
+            | InstructionEnum::RotateAccumulatorLeftThroughCarry(_)
+            | InstructionEnum::RotateAccumulatorRight(_)
+            | InstructionEnum::RotateAccumulatorRightThroughCarry(_)
+            | InstructionEnum::PrefixCb(_) => 0,
+        }
+    }
+
+    /// Drive an instruction from its first phase to completion.
+    ///
+    /// Memory is filled with [Nop](super::Nop)s, so every immediate value is `0` and every jump lands on a `NOP`.
+    /// Asserts that every intermediate step returns the same instruction in a later phase
+    /// and that the final step returns the freshly loaded `NOP`.
+    ///
+    /// Returns the number of phases the instruction took.
+    pub(crate) fn assert_phase_progression(instruction: impl Into<InstructionEnum>) -> usize {
+        let mut cpu = CpuState::new();
+        let mut memory = MemoryController::new_for_tests();
+        // Keep the stack away from the end of the address space, so pops don't overflow
+        cpu.write_stack_pointer(0xC000);
+        let mut instruction: InstructionEnum = instruction.into();
+        assert_eq!(
+            phase_index(&instruction),
+            0,
+            "{:?} should start in its first phase",
+            instruction
+        );
+
+        for phases in 1..=6 {
+            let next = instruction.execute(&mut cpu, &mut memory);
+            if discriminant(&next) != discriminant(&instruction) {
+                assert!(
+                    matches!(next, InstructionEnum::Nop(_)),
+                    "{:?} finished by returning {:?} instead of the next loaded instruction",
+                    instruction,
+                    next
+                );
+                return phases;
+            }
+            assert!(
+                phase_index(&next) > phase_index(&instruction),
+                "{:?} did not advance its phase, got {:?}",
+                instruction,
+                next
+            );
+            instruction = next;
+        }
+        panic!("{:?} did not finish within six phases", instruction);
+    }
+
+    #[test]
+    fn all_instructions_can_be_debug_printed() {
+        assert!(format!("{:?}", decode(0xCD)).starts_with("Call("));
+        for opcode in 0..=255u8 {
+            assert!(!format!("{:?}", decode(opcode)).is_empty());
+            assert!(!format!("{:?}", decode_cb(opcode)).is_empty());
+        }
+        let interrupt: InstructionEnum = InterruptServiceRoutine::create(0x40).into();
+        assert!(format!("{:?}", interrupt).contains("address: 64"));
+    }
+
+    #[test]
+    fn encode_full_returns_complete_instructions() {
+        for opcode in 0..=255u8 {
+            let instruction = decode(opcode);
+            if matches!(
+                instruction,
+                InstructionEnum::HaltAndCatchFire(_) | InstructionEnum::PrefixCb(_)
+            ) {
+                continue;
+            }
+            let expected_length = match opcode {
+                // Instructions with a 16 bit immediate
+                0x01 | 0x11 | 0x21 | 0x31 | 0x08 | 0xC2 | 0xC3 | 0xC4 | 0xCA | 0xCC | 0xCD
+                | 0xD2 | 0xD4 | 0xDA | 0xDC | 0xEA | 0xFA => 3,
+                // Instructions with an 8 bit immediate
+                0x06 | 0x0E | 0x16 | 0x1E | 0x26 | 0x2E | 0x36 | 0x3E | 0x18 | 0x20 | 0x28
+                | 0x30 | 0x38 | 0xC6 | 0xCE | 0xD6 | 0xDE | 0xE6 | 0xEE | 0xF6 | 0xFE | 0xE0
+                | 0xF0 | 0xE8 | 0xF8 => 2,
+                // STOP is followed by an unused byte
+                0x10 => 2,
+                _ => 1,
+            };
+            let encoded = instruction.encode_full();
+            assert_eq!(encoded[0], opcode);
+            assert_eq!(
+                encoded.len(),
+                expected_length,
+                "Wrong length for opcode {:#04x}",
+                opcode
+            );
+        }
+    }
+
+    /// Execute every instruction with different initial flags and check that the flags change like [Instruction::affects_flags] says.
+    #[test]
+    fn affects_flags_matches_execution() {
+        let decoders: [fn(u8) -> InstructionEnum; 2] = [decode, decode_cb];
+        for (decoder, opcode) in decoders
+            .into_iter()
+            .flat_map(|decoder| (0..=255u8).map(move |opcode| (decoder, opcode)))
+        {
+            let instruction = decoder(opcode);
+            if matches!(
+                instruction,
+                InstructionEnum::HaltAndCatchFire(_)
+                    | InstructionEnum::Halt(_)
+                    | InstructionEnum::Stop(_)
+                    | InstructionEnum::PrefixCb(_)
+            ) {
+                continue;
+            }
+            let effects = instruction.affects_flags();
+            for register_value in [0x00, 0x01, 0x0F, 0x80, 0xFF] {
+                for initial_flags in [false, true] {
+                    let mut cpu = CpuState::new();
+                    let mut memory = MemoryController::new_for_tests();
+                    for register in [
+                        Register::A,
+                        Register::B,
+                        Register::C,
+                        Register::D,
+                        Register::E,
+                    ] {
+                        cpu.write_register(register, register_value);
+                    }
+                    cpu.write_double_register(DoubleRegister::HL, 0xC100);
+                    cpu.write_stack_pointer(0xC000);
+                    memory.write(0xC100, register_value);
+                    let flags = [Flag::Zero, Flag::Subtract, Flag::HalfCarry, Flag::Carry];
+                    for flag in flags {
+                        cpu.write_flag(flag, initial_flags);
+                    }
+
+                    let mut current = decoder(opcode);
+                    for _ in 0..6 {
+                        let next = current.execute(&mut cpu, &mut memory);
+                        if discriminant(&next) != discriminant(&current) {
+                            break;
+                        }
+                        current = next;
+                    }
+
+                    for flag in flags {
+                        let value = cpu.read_flag(flag);
+                        let consistent = match effects.get(flag) {
+                            FlagEffect::Unchanged => value == initial_flags,
+                            FlagEffect::Set => value,
+                            FlagEffect::Reset => !value,
+                            FlagEffect::Modified => true,
+                        };
+                        assert!(
+                            consistent,
+                            "{:?} should leave {:?} {:?}, but it is {} (register values {:#04x}, flags {})",
+                            instruction,
+                            flag,
+                            effects.get(flag),
+                            value,
+                            register_value,
+                            initial_flags
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn load_reports_no_flag_effects_while_add_reports_all() {
+        let load = decode(0x78);
+        assert!(matches!(
+            load,
+            InstructionEnum::LoadFromRegisterToRegister(_)
+        ));
+        assert_eq!(load.affects_flags(), FlagEffects::NONE);
+
+        let add = decode(0x80);
+        assert!(matches!(add, InstructionEnum::AddRegister(_)));
+        let effects = add.affects_flags();
+        assert_eq!(effects.zero, FlagEffect::Modified);
+        assert_eq!(effects.subtract, FlagEffect::Reset);
+        assert_eq!(effects.half_carry, FlagEffect::Modified);
+        assert_eq!(effects.carry, FlagEffect::Modified);
+        assert!(effects.affects_any());
+    }
+
+    const ALL_REGISTERS: [Register; 8] = [
+        Register::B,
+        Register::C,
+        Register::D,
+        Register::E,
+        Register::H,
+        Register::L,
+        Register::F,
+        Register::A,
+    ];
+
+    /// Execute all phases of `instruction` with the given register values (in the order of [ALL_REGISTERS])
+    fn execute_with_registers(
+        instruction: &InstructionEnum,
+        registers: [u8; 8],
+    ) -> (
+        CpuState,
+        MemoryController<LoggerSerialConnection, DummyDisplayConnection>,
+    ) {
+        let mut cpu = CpuState::new();
+        let mut memory = MemoryController::new_for_tests();
+        for (register, value) in ALL_REGISTERS.into_iter().zip(registers) {
+            cpu.write_register(register, value);
+        }
+        cpu.write_stack_pointer(0xC000);
+        memory.write(0xC100, 0x42);
+
+        let mut current = instruction.clone();
+        for _ in 0..6 {
+            let next = current.execute(&mut cpu, &mut memory);
+            if discriminant(&next) != discriminant(&current) {
+                break;
+            }
+            current = next;
+        }
+        (cpu, memory)
+    }
+
+    #[test]
+    fn register_effects_match_execution() {
+        let decoders: [fn(u8) -> InstructionEnum; 2] = [decode, decode_cb];
+        for (decoder, opcode) in decoders
+            .into_iter()
+            .flat_map(|decoder| (0..=255u8).map(move |opcode| (decoder, opcode)))
+        {
+            let instruction = decoder(opcode);
+            if matches!(
+                instruction,
+                InstructionEnum::HaltAndCatchFire(_)
+                    | InstructionEnum::Halt(_)
+                    | InstructionEnum::Stop(_)
+                    | InstructionEnum::PrefixCb(_)
+            ) {
+                continue;
+            }
+            let (reads, writes) = instruction.register_effects();
+            // HL points to RAM, C to HRAM
+            for flags in [0x00, 0xF0] {
+                let initial = [0x12, 0x80, 0xC2, 0x34, 0xC1, 0x00, flags, 0x3C];
+                let (cpu, memory) = execute_with_registers(&instruction, initial);
+
+                for (index, register) in ALL_REGISTERS.into_iter().enumerate() {
+                    assert!(
+                        writes.contains(register) || cpu.read_register(register) == initial[index],
+                        "{:?} changed {:?} but does not report writing it",
+                        instruction,
+                        register
+                    );
+                }
+
+                // Changing a register that is not read must not change anything else
+                for (index, register) in ALL_REGISTERS.into_iter().enumerate() {
+                    if reads.contains(register) {
+                        continue;
+                    }
+                    for change in [0x01, 0x80, 0xF0] {
+                        let mut modified = initial;
+                        modified[index] ^= change;
+                        let (other_cpu, other_memory) =
+                            execute_with_registers(&instruction, modified);
+                        for other_register in ALL_REGISTERS {
+                            if other_register as u8 == register as u8 {
+                                continue;
+                            }
+                            assert_eq!(
+                                cpu.read_register(other_register),
+                                other_cpu.read_register(other_register),
+                                "{:?} depends on {:?} but does not report reading it",
+                                instruction,
+                                register
+                            );
+                        }
+                        assert!(
+                            memory.memory.data == other_memory.memory.data,
+                            "{:?} depends on {:?} but does not report reading it",
+                            instruction,
+                            register
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn register_effects_of_load_and_add() {
+        let load = decode(0x78);
+        assert!(matches!(
+            load,
+            InstructionEnum::LoadFromRegisterToRegister(_)
+        ));
+        let (reads, writes) = load.register_effects();
+        assert_eq!(reads, RegisterSet::new().with(Register::B));
+        assert_eq!(writes, RegisterSet::new().with(Register::A));
+        assert!(!writes.contains(Register::F));
+
+        let add = decode(0x81);
+        assert!(matches!(add, InstructionEnum::AddRegister(_)));
+        let (reads, writes) = add.register_effects();
+        assert_eq!(
+            reads,
+            RegisterSet::new().with(Register::A).with(Register::C)
+        );
+        assert_eq!(
+            writes,
+            RegisterSet::new().with(Register::A).with(Register::F)
+        );
+    }
+
+    #[test]
+    fn multi_phase_instructions_progress_through_their_phases() {
+        assert_eq!(
+            assert_phase_progression(LoadHlToAccumulatorAndDecrement {
+                phase: TwoPhases::First,
+            }),
+            2
+        );
+        assert_eq!(
+            assert_phase_progression(LoadHlToAccumulatorAndIncrement {
+                phase: TwoPhases::First,
+            }),
+            2
+        );
+        assert_eq!(
+            assert_phase_progression(LoadAccumulatorToHlAndDecrement {
+                phase: TwoPhases::First,
+            }),
+            2
+        );
+        assert_eq!(
+            assert_phase_progression(LoadAccumulatorToHlAndIncrement {
+                phase: TwoPhases::First,
+            }),
+            2
+        );
+        assert_eq!(
+            assert_phase_progression(LoadImmediateToDoubleRegister {
+                destination: DoubleRegisterOrStackPointer::BC,
+                value: 0,
+                phase: ThreePhases::First,
+            }),
+            3
+        );
+        assert_eq!(
+            assert_phase_progression(PopDoubleRegister {
+                destination: DoubleRegister::DE,
+                phase: ThreePhases::First,
+            }),
+            3
+        );
+        assert_eq!(
+            assert_phase_progression(PushDoubleRegister {
+                source: DoubleRegister::HL,
+                phase: FourPhases::First,
+            }),
+            4
+        );
+        assert_eq!(
+            assert_phase_progression(JumpToImmediateAddress {
+                address: 0,
+                phase: FourPhases::First,
+            }),
+            4
+        );
+        assert_eq!(
+            assert_phase_progression(ReturnFromInterrupt {
+                phase: FourPhases::First,
+            }),
+            4
+        );
+        assert_eq!(
+            assert_phase_progression(Restart {
+                address: RestartAddress::H,
+                phase: FourPhases::First,
+            }),
+            4
+        );
+        assert_eq!(
+            assert_phase_progression(AddImmediateOffsetToSp {
+                offset: 0,
+                phase: FourPhases::First,
+            }),
+            4
+        );
+        assert_eq!(
+            assert_phase_progression(LoadSpToImmediateAddress {
+                address: 0,
+                phase: FivePhases::First,
+            }),
+            5
+        );
+        assert_eq!(
+            assert_phase_progression(Call {
+                address: 0,
+                phase: SixPhases::First,
+            }),
+            6
+        );
+    }
 
     #[test]
     fn load_from_hl_works() {
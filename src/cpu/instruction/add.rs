@@ -16,6 +16,9 @@ generate_instruction!(
         AddImmediate
     ),
     0b10000000,
+    flags: [Modified, Reset, Modified, Modified],
+    reads_carry: false,
+    writes_result: true,
     cpu,
     memory,
     operand,
@@ -1,13 +1,15 @@
+use super::flag_effects::{FlagEffect, FlagEffects};
 use super::phases::TwoPhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
+use crate::cpu::Register;
 use crate::{
-    cpu::{Cpu, DoubleRegister, Flag},
+    cpu::{Cpu, DoubleRegister, DoubleRegisterOrStackPointer, Flag},
     memory::MemoryDevice,
 };
 
-/// Adds a [DoubleRegister] to [DoubleRegister::HL].
-///
-/// Setting the operand to DoubleRegister::AF actually means adding the stackpointer the stackpointer
+/// Adds a [DoubleRegisterOrStackPointer] to [DoubleRegister::HL].
 ///
 /// | [Zero](Flag::Zero)  | [Subtract](Flag::Subtract) | [HalfCarry](Flag::HalfCarry)             | [Carry](Flag::Carry)                  |
 /// |---------------------|----------------------------|------------------------------------------|---------------------------------------|
@@ -17,10 +19,10 @@ use crate::{
 #[doc(alias = "ADD HL,DE")]
 #[doc(alias = "ADD HL,HL")]
 #[doc(alias = "ADD HL,SP")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AddDoubleRegisterToHl {
     /// The destination double register.
-    pub operand: DoubleRegister,
+    pub operand: DoubleRegisterOrStackPointer,
     /// The current phase of the instruction.
     pub phase: TwoPhases,
 }
@@ -33,10 +35,7 @@ impl Instruction for AddDoubleRegisterToHl {
     ) -> super::InstructionEnum {
         match self.phase {
             TwoPhases::First => {
-                let operand = match self.operand {
-                    DoubleRegister::AF => cpu.read_stack_pointer(),
-                    _ => cpu.read_double_register(self.operand),
-                };
+                let operand = cpu.read_register16(self.operand.into());
                 let previous_value = cpu.read_double_register(DoubleRegister::HL);
                 let (result, carry_flag) = previous_value.overflowing_add(operand);
                 let half_carry_flag = (previous_value.to_le_bytes()[1]
@@ -62,10 +61,27 @@ impl Instruction for AddDoubleRegisterToHl {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         let register_part = self.operand.numerical_id() << 4;
         let opcode = 0b00001001 | register_part;
-        Vec::from([opcode])
+        EncodedInstruction::from([opcode])
+    }
+    fn affects_flags(&self) -> FlagEffects {
+        FlagEffects::new(
+            FlagEffect::Unchanged,
+            FlagEffect::Reset,
+            FlagEffect::Modified,
+            FlagEffect::Modified,
+        )
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        let registers = RegisterSet::new().with_double_or_stack_pointer(self.operand);
+        (
+            registers.with_double(DoubleRegister::HL),
+            RegisterSet::new()
+                .with_double(DoubleRegister::HL)
+                .with(Register::F),
+        )
     }
 }
 
@@ -74,7 +90,7 @@ mod tests {
     use super::AddDoubleRegisterToHl;
     use crate::cpu::instruction::phases::TwoPhases;
     use crate::cpu::instruction::{Instruction, InstructionEnum};
-    use crate::cpu::{Cpu, CpuState, DoubleRegister};
+    use crate::cpu::{Cpu, CpuState, DoubleRegister, DoubleRegisterOrStackPointer};
     use crate::memory::MemoryController;
 
     #[test]
@@ -85,7 +101,7 @@ mod tests {
         cpu.write_double_register(DoubleRegister::BC, 788);
 
         let instruction = AddDoubleRegisterToHl {
-            operand: DoubleRegister::BC,
+            operand: DoubleRegisterOrStackPointer::BC,
             phase: TwoPhases::First,
         };
 
@@ -95,7 +111,7 @@ mod tests {
             instruction,
             InstructionEnum::AddDoubleRegisterToHl(AddDoubleRegisterToHl {
                 phase: TwoPhases::Second,
-                operand: DoubleRegister::BC,
+                operand: DoubleRegisterOrStackPointer::BC,
             })
         ));
 
@@ -112,7 +128,7 @@ mod tests {
         cpu.write_double_register(DoubleRegister::HL, 324);
 
         let instruction = AddDoubleRegisterToHl {
-            operand: DoubleRegister::AF,
+            operand: DoubleRegisterOrStackPointer::SP,
             phase: TwoPhases::First,
         };
 
@@ -122,7 +138,7 @@ mod tests {
             instruction,
             InstructionEnum::AddDoubleRegisterToHl(AddDoubleRegisterToHl {
                 phase: TwoPhases::Second,
-                operand: DoubleRegister::AF,
+                operand: DoubleRegisterOrStackPointer::SP,
             })
         ));
 
@@ -1,5 +1,9 @@
+use super::flag_effects::{FlagEffect, FlagEffects};
 use super::phases::FourPhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
+use crate::cpu::Register;
 use crate::{
     cpu::{Cpu, Flag},
     memory::MemoryDevice,
@@ -23,7 +27,7 @@ use crate::{
 /// | false               | false                      | true if the nibble overflowed on the LSB | true if a overflow occured on the LSB |
 #[doc(alias = "ADD")]
 #[doc(alias = "ADD SP,n")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AddImmediateOffsetToSp {
     /// The immediate offset. Will only valid after the first phase.
     pub offset: i8,
@@ -85,12 +89,26 @@ impl Instruction for AddImmediateOffsetToSp {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         match self.phase {
-            FourPhases::First => Vec::from([0b11101000]),
-            _ => Vec::from([0b11101000, self.offset.to_ne_bytes()[0]]),
+            FourPhases::First => EncodedInstruction::from([0b11101000]),
+            _ => EncodedInstruction::from([0b11101000, self.offset.to_ne_bytes()[0]]),
         }
     }
+    fn affects_flags(&self) -> FlagEffects {
+        FlagEffects::new(
+            FlagEffect::Reset,
+            FlagEffect::Reset,
+            FlagEffect::Modified,
+            FlagEffect::Modified,
+        )
+    }
+    fn encode_full(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b11101000, self.offset.to_ne_bytes()[0]])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (RegisterSet::new(), RegisterSet::new().with(Register::F))
+    }
 }
 
 #[cfg(test)]
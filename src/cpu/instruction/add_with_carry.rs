@@ -18,6 +18,9 @@ generate_instruction!(
         AddWithCarryImmediate
     ),
     0b10001000,
+    flags: [Modified, Reset, Modified, Modified],
+    reads_carry: true,
+    writes_result: true,
     cpu,
     memory,
     operand,
@@ -16,6 +16,9 @@ generate_instruction!(
         BitwiseAndImmediate
     ),
     0b10100000,
+    flags: [Modified, Reset, Set, Reset],
+    reads_carry: false,
+    writes_result: true,
     cpu,
     memory,
     operand,
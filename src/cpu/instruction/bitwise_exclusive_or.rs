@@ -16,6 +16,9 @@ generate_instruction!(
         BitwiseExclusiveOrImmediate
     ),
     0b10101000,
+    flags: [Modified, Reset, Reset, Reset],
+    reads_carry: false,
+    writes_result: true,
     cpu,
     memory,
     operand,
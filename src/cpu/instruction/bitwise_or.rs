@@ -16,6 +16,9 @@ generate_instruction!(
         BitwiseOrImmediate
     ),
     0b10110000,
+    flags: [Modified, Reset, Reset, Reset],
+    reads_carry: false,
+    writes_result: true,
     cpu,
     memory,
     operand,
@@ -1,4 +1,5 @@
 use super::phases::SixPhases;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
@@ -8,7 +9,7 @@ use crate::{cpu::Cpu, memory::MemoryDevice};
 ///
 /// The value pushed to the stack points to the next instruction directly after this one.
 #[doc(alias = "CALL")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Call {
     /// The immediate address. Will only valid after the second phase.
     pub address: u16,
@@ -80,17 +81,26 @@ impl Instruction for Call {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         match self.phase {
-            SixPhases::First => Vec::from([0b11001101]),
-            SixPhases::Second => Vec::from([0b11001101, self.address.to_le_bytes()[0]]),
-            _ => Vec::from([
+            SixPhases::First => EncodedInstruction::from([0b11001101]),
+            SixPhases::Second => {
+                EncodedInstruction::from([0b11001101, self.address.to_le_bytes()[0]])
+            }
+            _ => EncodedInstruction::from([
                 0b11001101,
                 self.address.to_le_bytes()[0],
                 self.address.to_le_bytes()[1],
             ]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        EncodedInstruction::from([
+            0b11001101,
+            self.address.to_le_bytes()[0],
+            self.address.to_le_bytes()[1],
+        ])
+    }
 }
 
 #[cfg(test)]
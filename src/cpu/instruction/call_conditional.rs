@@ -1,5 +1,8 @@
 use super::phases::SixPhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
+use crate::cpu::Register;
 use crate::{
     cpu::{ConditionCode, Cpu},
     memory::MemoryDevice,
@@ -11,7 +14,7 @@ use crate::{
 ///
 /// The value pushed to the stack points to the next instruction directly after this one.
 #[doc(alias = "CALL")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CallConditional {
     /// The call is only made if the condition is fullfilled in the third phase.
     pub condition: ConditionCode,
@@ -95,20 +98,33 @@ impl Instruction for CallConditional {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         let condition_code_part = ((self.condition as u8) << 3) & 0b00011000;
         let opcode = 0b11000100 | condition_code_part;
 
         match self.phase {
-            SixPhases::First => Vec::from([opcode]),
-            SixPhases::Second => Vec::from([opcode, self.address.to_le_bytes()[0]]),
-            _ => Vec::from([
+            SixPhases::First => EncodedInstruction::from([opcode]),
+            SixPhases::Second => EncodedInstruction::from([opcode, self.address.to_le_bytes()[0]]),
+            _ => EncodedInstruction::from([
                 opcode,
                 self.address.to_le_bytes()[0],
                 self.address.to_le_bytes()[1],
             ]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        let condition_code_part = ((self.condition as u8) << 3) & 0b00011000;
+        let opcode = 0b11000100 | condition_code_part;
+
+        EncodedInstruction::from([
+            opcode,
+            self.address.to_le_bytes()[0],
+            self.address.to_le_bytes()[1],
+        ])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (RegisterSet::new().with(Register::F), RegisterSet::new())
+    }
 }
 
 #[cfg(test)]
@@ -25,6 +25,9 @@ generate_instruction!(
     ),
     cb,
     0b01000000,
+    flags: [Modified, Reset, Set, Unchanged],
+    reads_carry: false,
+    writes_result: false,
     cpu,
     memory,
     operand,
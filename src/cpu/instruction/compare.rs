@@ -22,6 +22,9 @@ generate_instruction!(
         CompareImmediate
     ),
     0b10111000,
+    flags: [Modified, Set, Modified, Modified],
+    reads_carry: false,
+    writes_result: false,
     cpu,
     memory,
     operand,
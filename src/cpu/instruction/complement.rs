@@ -1,3 +1,6 @@
+use super::flag_effects::{FlagEffect, FlagEffects};
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{
     cpu::{Cpu, Flag, Register},
@@ -32,7 +35,7 @@ use crate::{
 /// |---------------------|----------------------------|------------------------------|----------------------|
 /// | unchanged           | true                       | true                         | unchanged            |
 #[doc(alias = "CPL")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Complement {}
 
 impl Instruction for Complement {
@@ -50,8 +53,22 @@ impl Instruction for Complement {
 
         return cpu.load_instruction(memory);
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b00101111])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b00101111])
+    }
+    fn affects_flags(&self) -> FlagEffects {
+        FlagEffects::new(
+            FlagEffect::Unchanged,
+            FlagEffect::Set,
+            FlagEffect::Set,
+            FlagEffect::Unchanged,
+        )
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with(Register::A),
+            RegisterSet::new().with(Register::A).with(Register::F),
+        )
     }
 }
 
@@ -0,0 +1,118 @@
+/// The number of machine cycles of each unprefixed opcode. Conditional instructions use the number of cycles when the condition is met.
+///
+/// Opcodes that do not exist are listed with a single cycle. The tests check every entry against the execution of the instruction.
+#[rustfmt::skip]
+const CYCLES: [u8; 256] = [
+//  x0 x1 x2 x3 x4 x5 x6 x7 x8 x9 xA xB xC xD xE xF
+    1, 3, 2, 2, 1, 1, 2, 1, 5, 2, 2, 2, 1, 1, 2, 1, // 0x
+    1, 3, 2, 2, 1, 1, 2, 1, 3, 2, 2, 2, 1, 1, 2, 1, // 1x
+    3, 3, 2, 2, 1, 1, 2, 1, 3, 2, 2, 2, 1, 1, 2, 1, // 2x
+    3, 3, 2, 2, 3, 3, 3, 1, 3, 2, 2, 2, 1, 1, 2, 1, // 3x
+    1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 4x
+    1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 5x
+    1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 6x
+    2, 2, 2, 2, 2, 2, 1, 2, 1, 1, 1, 1, 1, 1, 2, 1, // 7x
+    1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 8x
+    1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 9x
+    1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // Ax
+    1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // Bx
+    5, 3, 4, 4, 6, 4, 2, 4, 5, 4, 4, 1, 6, 6, 2, 4, // Cx
+    5, 3, 4, 1, 6, 4, 2, 4, 5, 4, 4, 1, 6, 1, 2, 4, // Dx
+    3, 3, 2, 1, 1, 4, 2, 4, 4, 1, 4, 1, 1, 1, 2, 4, // Ex
+    3, 3, 2, 1, 1, 4, 2, 4, 3, 2, 4, 1, 1, 1, 2, 4, // Fx
+];
+
+/// Get the number of machine cycles it takes to execute the instruction with the given binary representation.
+///
+/// Instructions prefixed by `0xCB` include the cycle for fetching the prefix. If only the prefix is given, it counts as a single cycle.
+/// Conditional jumps, calls and returns report the number of cycles they take when the condition is met.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_gameboy_library::cpu::instruction::cycles::cycles;
+/// #
+/// assert_eq!(cycles(&[0x00]), 1);
+/// assert_eq!(cycles(&[0xCD, 0x34, 0x12]), 6);
+/// assert_eq!(cycles(&[0xCB, 0x46]), 3);
+/// ```
+pub fn cycles(bytes: &[u8]) -> u8 {
+    match bytes {
+        [0xCB, cb_opcode, ..] => {
+            let reads_hl = cb_opcode & 0b111 == 0b110;
+            // BIT n,(HL) does not write the result back
+            let writes_hl = reads_hl && cb_opcode >> 6 != 0b01;
+            2 + reads_hl as u8 + writes_hl as u8
+        }
+        [opcode, ..] => CYCLES[*opcode as usize],
+        [] => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cycles;
+    use crate::cpu::instruction::phases::{FivePhases, SixPhases};
+    use crate::cpu::instruction::{
+        decode, decode_cb, Call, Instruction, InterruptServiceRoutine, Nop,
+    };
+    use crate::cpu::{Cpu, CpuState, Register};
+    use crate::emulator::Emulator;
+    use crate::memory::MemoryController;
+
+    /// Execute the first instruction of `program` with the flags register set to `flags` and return the number of cycles it took
+    fn execute_first_instruction(program: &[u8], flags: u8) -> u8 {
+        let mut cpu = CpuState::new();
+        cpu.write_register(Register::F, flags);
+        cpu.write_stack_pointer(0xC100);
+        let mut emulator = Emulator::new(cpu, MemoryController::new_with_init(program));
+        emulator.step() as u8
+    }
+
+    #[test]
+    fn instructions_report_their_cycles() {
+        let call = Call {
+            address: 0x1234,
+            phase: SixPhases::First,
+        };
+        assert_eq!(call.cycles(), 6);
+        assert_eq!(Nop {}.cycles(), 1);
+        assert_eq!(decode(0x7E).cycles(), 2);
+        assert_eq!(decode(0xC9).cycles(), 4);
+        assert_eq!(decode_cb(0x37).cycles(), 2);
+        assert_eq!(decode_cb(0x36).cycles(), 4);
+        assert_eq!(decode(0xCB).cycles(), 1);
+
+        let interrupt = InterruptServiceRoutine {
+            address: 0x0040,
+            phase: FivePhases::First,
+        };
+        assert_eq!(interrupt.cycles(), 5);
+    }
+
+    #[test]
+    fn cycles_match_the_phases_of_unconditional_instructions() {
+        assert_eq!(cycles(&[0xC3]), 4);
+        assert_eq!(cycles(&[0xC5]), 4);
+        assert_eq!(cycles(&[0xC1]), 3);
+        assert_eq!(cycles(&[0x08]), 5);
+        assert_eq!(cycles(&[0xE8]), 4);
+        assert_eq!(cycles(&[0xF8]), 3);
+    }
+
+    #[test]
+    fn cycles_match_the_execution_of_every_opcode() {
+        for opcode in 0..=255u8 {
+            let program = [opcode, 0x00, 0x00];
+            // Conditional instructions are slower if the condition is met, so the slower run is the one that met it
+            let executed = execute_first_instruction(&program, 0b00000000)
+                .max(execute_first_instruction(&program, 0b11110000));
+            assert_eq!(cycles(&program), executed, "Opcode {:#04x}", opcode);
+        }
+        for cb_opcode in 0..=255u8 {
+            let program = [0xCB, cb_opcode];
+            let executed = execute_first_instruction(&program, 0b00000000);
+            assert_eq!(cycles(&program), executed, "Opcode 0xCB {:#04x}", cb_opcode);
+        }
+    }
+}
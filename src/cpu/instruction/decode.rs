@@ -2,8 +2,11 @@ use crate::cpu::instruction::{
     phases::{ThreePhases, TwoPhases},
     InstructionEnum,
 };
-use crate::cpu::{ConditionCode, DoubleRegister, Register};
+use crate::cpu::{
+    ConditionCode, DoubleRegister, DoubleRegisterOrStackPointer, Register, RestartAddress,
+};
 use bitmatch::bitmatch;
+use std::sync::OnceLock;
 
 use super::{
     load_accumulator_to_hl_and_decrement::LoadAccumulatorToHlAndDecrement,
@@ -101,8 +104,18 @@ macro_rules! decode_arithmetic_immediate {
 ///     })
 /// ))
 /// ```
-#[bitmatch]
 pub fn decode(byte: u8) -> InstructionEnum {
+    let table =
+        DECODE_TABLE.get_or_init(|| std::array::from_fn(|opcode| decode_opcode(opcode as u8)));
+    table[byte as usize].clone()
+}
+
+/// Every opcode decoded with [decode_opcode]. Filled on the first call to [decode].
+static DECODE_TABLE: OnceLock<[InstructionEnum; 256]> = OnceLock::new();
+
+/// Create a instruction from an opcode by matching its bits. Used to fill the [DECODE_TABLE].
+#[bitmatch]
+fn decode_opcode(byte: u8) -> InstructionEnum {
     #[bitmatch]
     // We probably cannot get rid of this massive match clause
     match byte {
@@ -190,8 +203,8 @@ pub fn decode(byte: u8) -> InstructionEnum {
         .into(),
         "11110011" => DisableInterrupts {}.into(),
         "11111011" => EnableInterrupts {}.into(),
-        "01110110" => Halt {}.into(),
-        "00010000" => Stop {}.into(),
+        "01110110" => Halt { halted: false }.into(),
+        "00010000" => Stop { stopped: false }.into(),
         "00000000" => Nop {}.into(),
         "00100111" => ToBinaryCodedDecimal {}.into(),
         "00101111" => Complement {}.into(),
@@ -227,17 +240,6 @@ pub fn decode(byte: u8) -> InstructionEnum {
         "00010111" => RotateAccumulatorLeftThroughCarry {}.into(),
         "00001111" => RotateAccumulatorRight {}.into(),
         "00011111" => RotateAccumulatorRightThroughCarry {}.into(),
-        "11010011" => HaltAndCatchFire { opcode: byte }.into(),
-        "11011011" => HaltAndCatchFire { opcode: byte }.into(),
-        "11011101" => HaltAndCatchFire { opcode: byte }.into(),
-        "11100011" => HaltAndCatchFire { opcode: byte }.into(),
-        "11100100" => HaltAndCatchFire { opcode: byte }.into(),
-        "11101011" => HaltAndCatchFire { opcode: byte }.into(),
-        "11101100" => HaltAndCatchFire { opcode: byte }.into(),
-        "11101101" => HaltAndCatchFire { opcode: byte }.into(),
-        "11110100" => HaltAndCatchFire { opcode: byte }.into(),
-        "11111100" => HaltAndCatchFire { opcode: byte }.into(),
-        "11111101" => HaltAndCatchFire { opcode: byte }.into(),
         "11001011" => PrefixCb {}.into(),
         "000a1010" => LoadFromDoubleRegisterToAccumulator {
             address_register: match a {
@@ -256,19 +258,19 @@ pub fn decode(byte: u8) -> InstructionEnum {
         }
         .into(),
         "00aa0011" => IncrementDoubleRegister {
-            destination: DoubleRegister::try_from(a)
+            destination: DoubleRegisterOrStackPointer::try_from(a)
                 .expect("2 bit value should always correspond to a double register"),
             phase: TwoPhases::First,
         }
         .into(),
         "00aa1011" => DecrementDoubleRegister {
-            destination: DoubleRegister::try_from(a)
+            destination: DoubleRegisterOrStackPointer::try_from(a)
                 .expect("2 bit value should always correspond to a double register"),
             phase: TwoPhases::First,
         }
         .into(),
         "00aa1001" => AddDoubleRegisterToHl {
-            operand: DoubleRegister::try_from(a)
+            operand: DoubleRegisterOrStackPointer::try_from(a)
                 .expect("2 bit value should always correspond to a double register"),
             phase: TwoPhases::First,
         }
@@ -281,7 +283,7 @@ pub fn decode(byte: u8) -> InstructionEnum {
         }
         .into(),
         "00aa0001" => LoadImmediateToDoubleRegister {
-            destination: DoubleRegister::try_from(a)
+            destination: DoubleRegisterOrStackPointer::try_from(a)
                 .expect("3 bit value should always correspond to a register"),
             value: 0,
             phase: ThreePhases::First,
@@ -329,10 +331,13 @@ pub fn decode(byte: u8) -> InstructionEnum {
         "00aaa100" => decode_operand_arithmetic!(a, IncrementRegister, IncrementAtHl),
         "00aaa101" => decode_operand_arithmetic!(a, DecrementRegister, DecrementAtHl),
         "11aaa111" => Restart {
-            address: a.into(),
+            address: RestartAddress::try_from(a)
+                .expect("3 bit value should always correspond to a restart address"),
             phase: FourPhases::First,
         }
         .into(),
+        // In the LD block the register encoding 0b110 means (HL) instead of Register::F.
+        // LD (HL),(HL) is HALT and matched above, so the (HL) variants have to come before the register to register moves.
         "01aaa110" => LoadFromHlToRegister {
             destination: Register::try_from(a)
                 .expect("3 bit value should always correspond to a register"),
@@ -352,21 +357,22 @@ pub fn decode(byte: u8) -> InstructionEnum {
                 .expect("3 bit value should always correspond to a register"),
         }
         .into(),
-        _ => panic!("Failed to encode opcode {:#010b}", byte),
+        // 0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC and 0xFD are not used by the gameboy CPU
+        _ => HaltAndCatchFire { opcode: byte }.into(),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::decode;
+    use super::{decode, decode_opcode};
     use crate::cpu::{
         instruction::{
-            load_from_hl_to_register::LoadFromHlToRegister,
+            decode_cb, load_from_hl_to_register::LoadFromHlToRegister,
             load_from_register_to_register::LoadFromRegisterToRegister,
-            load_immediate_to_register::LoadImmediateToRegister, Instruction, InstructionEnum,
-            LoadAccumulatorToHlAndDecrement, LoadAccumulatorToHlAndIncrement,
+            load_immediate_to_register::LoadImmediateToRegister, HaltAndCatchFire, Instruction,
+            InstructionEnum, LoadAccumulatorToHlAndDecrement, LoadAccumulatorToHlAndIncrement,
             LoadAccumulatorToImmediateOffset, LoadFromImmediateOffsetToAccumulator,
-            LoadHlToAccumulatorAndDecrement, LoadHlToAccumulatorAndIncrement,
+            LoadHlToAccumulatorAndDecrement, LoadHlToAccumulatorAndIncrement, LoadRegisterToHl,
         },
         Register,
     };
@@ -488,24 +494,108 @@ mod tests {
 
     #[test]
     fn all_opcodes_reencode_to_the_same_opcode() {
-        let opcodes = 0u8..255u8;
-        let instructions = opcodes
-            .map(|opcode| decode(opcode))
-            .collect::<Vec<InstructionEnum>>();
-        for expected_opcode in 0u8..255u8 {
-            let decoded_instruction = instructions
-                .get(expected_opcode as usize)
-                .expect("should always match");
+        let mut undefined_opcodes = 0;
+        for opcode in 0u8..=255u8 {
+            let instruction = decode(opcode);
+            let encoded = instruction.encode();
+            assert_eq!(
+                encoded[0], opcode,
+                "Expected opcode {:#04x}, got opcode {:#04x}",
+                opcode, encoded[0]
+            );
+
+            match instruction {
+                // Undefined opcodes keep the byte they were decoded from
+                InstructionEnum::HaltAndCatchFire(HaltAndCatchFire { opcode: decoded }) => {
+                    assert_eq!(decoded, opcode);
+                    assert_eq!(encoded, [opcode]);
+                    undefined_opcodes += 1;
+                }
+                // The prefix is only the first byte, the second byte is decoded separately
+                InstructionEnum::PrefixCb(_) => {
+                    assert_eq!(opcode, 0xCB);
+                    assert_eq!(encoded, [0xCB]);
+                    for cb_opcode in 0u8..=255u8 {
+                        assert_eq!(decode_cb(cb_opcode).encode(), [0xCB, cb_opcode]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(undefined_opcodes, 11);
+    }
+
+    #[test]
+    fn decode_load_from_hl_to_register_for_all_register_encodings() {
+        for encoding in 0..8u8 {
+            let opcode = 0b01000110 | (encoding << 3);
+            let instruction = decode(opcode);
+            if encoding == 0b110 {
+                assert!(matches!(instruction, InstructionEnum::Halt(_)));
+                continue;
+            }
+            match instruction {
+                InstructionEnum::LoadFromHlToRegister(LoadFromHlToRegister {
+                    destination,
+                    phase: _,
+                }) => assert_eq!(destination as u8, encoding),
+                other => panic!("{:#04x} decoded to {:?}", opcode, other),
+            }
+        }
+    }
+
+    #[test]
+    fn decode_load_register_to_hl_for_all_register_encodings() {
+        for encoding in 0..8u8 {
+            let opcode = 0b01110000 | encoding;
+            let instruction = decode(opcode);
+            if encoding == 0b110 {
+                assert!(matches!(instruction, InstructionEnum::Halt(_)));
+                continue;
+            }
+            match instruction {
+                InstructionEnum::LoadRegisterToHl(LoadRegisterToHl { source, phase: _ }) => {
+                    assert_eq!(source as u8, encoding)
+                }
+                other => panic!("{:#04x} decoded to {:?}", opcode, other),
+            }
+        }
+    }
+
+    #[test]
+    fn register_to_register_loads_never_use_register_f() {
+        for opcode in 0x40..=0x7Fu8 {
+            if let InstructionEnum::LoadFromRegisterToRegister(LoadFromRegisterToRegister {
+                source,
+                destination,
+            }) = decode(opcode)
+            {
+                assert!(!matches!(source, Register::F), "{:#04x}", opcode);
+                assert!(!matches!(destination, Register::F), "{:#04x}", opcode);
+            }
+        }
+    }
 
-            let reencoded_opcode = *decoded_instruction
-                .encode()
-                .get(0)
-                .expect("opcode should not be empty");
+    #[test]
+    fn undefined_opcodes_halt_and_catch_fire() {
+        for opcode in [
+            0xD3u8, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD,
+        ] {
+            assert!(matches!(
+                decode(opcode),
+                InstructionEnum::HaltAndCatchFire(HaltAndCatchFire { opcode: decoded }) if decoded == opcode
+            ));
+        }
+    }
 
+    #[test]
+    fn table_matches_the_bit_patterns() {
+        for opcode in 0..=255u8 {
             assert_eq!(
-                expected_opcode, reencoded_opcode,
-                "Expected opcode {:#010b}, got opcode {:#010b}",
-                expected_opcode, reencoded_opcode
+                format!("{:?}", decode(opcode)),
+                format!("{:?}", decode_opcode(opcode)),
+                "{:#04x}",
+                opcode
             );
         }
     }
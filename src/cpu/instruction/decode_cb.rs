@@ -1,5 +1,5 @@
 use crate::cpu::instruction::phases::ThreePhases;
-use crate::cpu::Register;
+use crate::cpu::{Bit, Register};
 use bitmatch::bitmatch;
 
 use super::{HaltAndCatchFire, InstructionEnum};
@@ -27,13 +27,13 @@ macro_rules! decode_operand_arithmetic_with_bit {
             0b00000110 => super::$hl_instruction {
                 phase: ThreePhases::First,
                 operand: 0,
-                bit: $b.into(),
+                bit: Bit::try_from($b).expect("3 bit value should always correspond to a bit"),
             }
             .into(),
             _ => super::$register_instruction {
                 operand: Register::try_from($a)
                     .expect("3 bit value should always correspond to a register"),
-                bit: $b.into(),
+                bit: Bit::try_from($b).expect("3 bit value should always correspond to a bit"),
             }
             .into(),
         }
@@ -0,0 +1,236 @@
+use super::{
+    decode, decode_cb, AddImmediate, AddImmediateOffsetToSp, AddWithCarryImmediate,
+    BitwiseAndImmediate, BitwiseExclusiveOrImmediate, BitwiseOrImmediate, Call, CallConditional,
+    CompareImmediate, Instruction, InstructionEnum, JumpByImmediateOffset,
+    JumpByImmediateOffsetConditional, JumpToImmediateAddress, JumpToImmediateAddressConditional,
+    LoadAccumulatorToImmediateAddress, LoadAccumulatorToImmediateOffset,
+    LoadFromImmediateAddressToAccumulator, LoadFromImmediateOffsetToAccumulator,
+    LoadImmediateToDoubleRegister, LoadImmediateToHl, LoadImmediateToRegister,
+    LoadSpPlusImmediateOffsetToHl, LoadSpToImmediateAddress, SubtractImmediate,
+    SubtractWithCarryImmediate,
+};
+use crate::memory::MemoryDevice;
+
+/// Decode the instruction at `address` including all of its immediate values.
+///
+/// Unlike [decode] this reads the immediate bytes following the opcode from memory, so the returned instruction is fully populated. Instructions prefixed by `0xCB` are decoded with [decode_cb] right away.
+///
+/// Returns the instruction and the address of the following opcode. Useful for building a disassembly listing of a ROM.
+///
+/// ```
+/// # use rust_gameboy_library::cpu::instruction::{decode_full, InstructionEnum, JumpToImmediateAddress};
+/// # use rust_gameboy_library::memory::{MemoryController, MemoryDevice};
+/// #
+/// # let mut memory = MemoryController::new_for_tests();
+/// #
+/// memory.write(0x0100, 0xC3);
+/// memory.write(0x0101, 0x50);
+/// memory.write(0x0102, 0x01);
+///
+/// let (instruction, next_address) = decode_full(&memory, 0x0100);
+///
+/// assert!(matches!(
+///     instruction,
+///     InstructionEnum::JumpToImmediateAddress(JumpToImmediateAddress { address: 0x0150, .. })
+/// ));
+/// assert_eq!(next_address, 0x0103);
+/// ```
+pub fn decode_full<M: MemoryDevice>(memory: &M, address: u16) -> (InstructionEnum, u16) {
+    let byte = memory.read(address.wrapping_add(1));
+    let word = memory.read_u16(address.wrapping_add(1));
+    let offset = i8::from_ne_bytes([byte]);
+
+    let instruction: InstructionEnum = match decode(memory.read(address)) {
+        InstructionEnum::PrefixCb(_) => decode_cb(byte),
+        InstructionEnum::AddImmediate(instruction) => AddImmediate {
+            value: byte,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::AddWithCarryImmediate(instruction) => AddWithCarryImmediate {
+            value: byte,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::SubtractImmediate(instruction) => SubtractImmediate {
+            value: byte,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::SubtractWithCarryImmediate(instruction) => SubtractWithCarryImmediate {
+            value: byte,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::BitwiseAndImmediate(instruction) => BitwiseAndImmediate {
+            value: byte,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::BitwiseOrImmediate(instruction) => BitwiseOrImmediate {
+            value: byte,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::BitwiseExclusiveOrImmediate(instruction) => BitwiseExclusiveOrImmediate {
+            value: byte,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::CompareImmediate(instruction) => CompareImmediate {
+            value: byte,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::LoadImmediateToRegister(instruction) => LoadImmediateToRegister {
+            value: byte,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::LoadImmediateToHl(instruction) => LoadImmediateToHl {
+            value: byte,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::LoadAccumulatorToImmediateOffset(instruction) => {
+            LoadAccumulatorToImmediateOffset {
+                offset: byte,
+                ..instruction
+            }
+            .into()
+        }
+        InstructionEnum::LoadFromImmediateOffsetToAccumulator(instruction) => {
+            LoadFromImmediateOffsetToAccumulator {
+                offset: byte,
+                ..instruction
+            }
+            .into()
+        }
+        InstructionEnum::JumpByImmediateOffset(instruction) => JumpByImmediateOffset {
+            offset,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::JumpByImmediateOffsetConditional(instruction) => {
+            JumpByImmediateOffsetConditional {
+                offset,
+                ..instruction
+            }
+            .into()
+        }
+        InstructionEnum::AddImmediateOffsetToSp(instruction) => AddImmediateOffsetToSp {
+            offset,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::LoadSpPlusImmediateOffsetToHl(instruction) => {
+            LoadSpPlusImmediateOffsetToHl {
+                offset,
+                ..instruction
+            }
+            .into()
+        }
+        InstructionEnum::LoadImmediateToDoubleRegister(instruction) => {
+            LoadImmediateToDoubleRegister {
+                value: word,
+                ..instruction
+            }
+            .into()
+        }
+        InstructionEnum::JumpToImmediateAddress(instruction) => JumpToImmediateAddress {
+            address: word,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::JumpToImmediateAddressConditional(instruction) => {
+            JumpToImmediateAddressConditional {
+                address: word,
+                ..instruction
+            }
+            .into()
+        }
+        InstructionEnum::Call(instruction) => Call {
+            address: word,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::CallConditional(instruction) => CallConditional {
+            address: word,
+            ..instruction
+        }
+        .into(),
+        InstructionEnum::LoadAccumulatorToImmediateAddress(instruction) => {
+            LoadAccumulatorToImmediateAddress {
+                address: word,
+                ..instruction
+            }
+            .into()
+        }
+        InstructionEnum::LoadFromImmediateAddressToAccumulator(instruction) => {
+            LoadFromImmediateAddressToAccumulator {
+                address: word,
+                ..instruction
+            }
+            .into()
+        }
+        InstructionEnum::LoadSpToImmediateAddress(instruction) => LoadSpToImmediateAddress {
+            address: word,
+            ..instruction
+        }
+        .into(),
+        instruction => instruction,
+    };
+
+    let length = instruction.encode_full().len() as u16;
+    (instruction, address.wrapping_add(length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_full;
+    use crate::cpu::instruction::{
+        Instruction, InstructionEnum, JumpToImmediateAddress, LoadImmediateToRegister,
+    };
+    use crate::cpu::Register;
+    use crate::memory::{MemoryController, MemoryDevice};
+
+    #[test]
+    fn decode_full_reads_the_immediates() {
+        let mut memory = MemoryController::new_for_tests();
+        // JP 0x1234
+        memory.write(0x0100, 0xC3);
+        memory.write(0x0101, 0x34);
+        memory.write(0x0102, 0x12);
+        // LD B,0x42
+        memory.write(0x0103, 0x06);
+        memory.write(0x0104, 0x42);
+        // SWAP A
+        memory.write(0x0105, 0xCB);
+        memory.write(0x0106, 0x37);
+
+        let (instruction, next_address) = decode_full(&memory, 0x0100);
+        assert!(matches!(
+            instruction,
+            InstructionEnum::JumpToImmediateAddress(JumpToImmediateAddress {
+                address: 0x1234,
+                ..
+            })
+        ));
+        assert_eq!(next_address, 0x0103);
+
+        let (instruction, next_address) = decode_full(&memory, next_address);
+        assert!(matches!(
+            instruction,
+            InstructionEnum::LoadImmediateToRegister(LoadImmediateToRegister {
+                destination: Register::B,
+                value: 0x42,
+                ..
+            })
+        ));
+        assert_eq!(next_address, 0x0105);
+
+        let (instruction, next_address) = decode_full(&memory, next_address);
+        assert_eq!(instruction.disassemble(), "SWAP A");
+        assert_eq!(next_address, 0x0107);
+    }
+}
@@ -16,6 +16,9 @@ generate_instruction!(
         DecrementAtHl
     ),
     0b00000101,
+    flags: [Modified, Set, Modified, Unchanged],
+    reads_carry: false,
+    writes_result: true,
     3,
     cpu,
     memory,
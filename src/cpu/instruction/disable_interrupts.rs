@@ -1,3 +1,4 @@
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
@@ -8,7 +9,7 @@ use crate::{cpu::Cpu, memory::MemoryDevice};
 /// Cancels pending [EnableInterrupts](super::EnableInterrupts).
 /// In our case we dont need to do anything special for that, as that happens implicitly.
 #[doc(alias = "DI")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DisableInterrupts {}
 
 impl Instruction for DisableInterrupts {
@@ -20,8 +21,8 @@ impl Instruction for DisableInterrupts {
         cpu.write_interrupt_master_enable(false);
         return cpu.load_instruction(memory);
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b11110011])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b11110011])
     }
 }
 
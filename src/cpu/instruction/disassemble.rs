@@ -0,0 +1,199 @@
+/// The operands encoded by the three register bits of an opcode
+const REGISTERS: [&str; 8] = ["B", "C", "D", "E", "H", "L", "(HL)", "A"];
+/// The double registers used by loads and 16 bit arithmetic
+const DOUBLE_REGISTERS: [&str; 4] = ["BC", "DE", "HL", "SP"];
+/// The double registers used by PUSH and POP
+const STACK_DOUBLE_REGISTERS: [&str; 4] = ["BC", "DE", "HL", "AF"];
+/// The conditions of conditional jumps, calls and returns
+const CONDITIONS: [&str; 4] = ["NZ", "Z", "NC", "C"];
+/// The 8 bit arithmetic instructions, including the accumulator operand if the syntax needs it
+const ARITHMETIC: [&str; 8] = [
+    "ADD A,", "ADC A,", "SUB ", "SBC A,", "AND ", "XOR ", "OR ", "CP ",
+];
+/// The rotate and shift instructions behind the 0xCB prefix
+const ROTATIONS: [&str; 8] = ["RLC", "RRC", "RL", "RR", "SLA", "SRA", "SWAP", "SRL"];
+
+/// Format a signed offset with an explicit sign
+fn signed(offset: i8) -> String {
+    if offset < 0 {
+        format!("-{}", offset.unsigned_abs())
+    } else {
+        format!("+{}", offset)
+    }
+}
+
+/// Get the assembler mnemonic for the binary representation of an instruction, like `LD C,A` or `JP 0x1234`.
+///
+/// Missing immediate bytes are treated as `0`. Opcodes that do not exist are shown as `DB` directives.
+///
+/// # Examples
+///
+/// ```
+/// # use rust_gameboy_library::cpu::instruction::disassemble::disassemble;
+/// #
+/// assert_eq!(disassemble(&[0x4F]), "LD C,A");
+/// assert_eq!(disassemble(&[0xC3, 0x34, 0x12]), "JP 0x1234");
+/// assert_eq!(disassemble(&[0xCB, 0x7C]), "BIT 7,H");
+/// ```
+pub fn disassemble(bytes: &[u8]) -> String {
+    let opcode = bytes.first().copied().unwrap_or(0);
+    let immediate = bytes.get(1).copied().unwrap_or(0);
+    let address = u16::from_le_bytes([immediate, bytes.get(2).copied().unwrap_or(0)]);
+    let offset = immediate as i8;
+
+    let y = ((opcode >> 3) & 0b111) as usize;
+    let z = (opcode & 0b111) as usize;
+    let p = y >> 1;
+    let odd = y & 1 == 1;
+
+    match (opcode >> 6, z) {
+        (0, 0) => match y {
+            0 => "NOP".to_string(),
+            1 => format!("LD ({:#06x}),SP", address),
+            2 => "STOP".to_string(),
+            3 => format!("JR {}", signed(offset)),
+            _ => format!("JR {},{}", CONDITIONS[y - 4], signed(offset)),
+        },
+        (0, 1) if odd => format!("ADD HL,{}", DOUBLE_REGISTERS[p]),
+        (0, 1) => format!("LD {},{:#06x}", DOUBLE_REGISTERS[p], address),
+        (0, 2) => {
+            let memory = ["(BC)", "(DE)", "(HL+)", "(HL-)"][p];
+            if odd {
+                format!("LD A,{}", memory)
+            } else {
+                format!("LD {},A", memory)
+            }
+        }
+        (0, 3) if odd => format!("DEC {}", DOUBLE_REGISTERS[p]),
+        (0, 3) => format!("INC {}", DOUBLE_REGISTERS[p]),
+        (0, 4) => format!("INC {}", REGISTERS[y]),
+        (0, 5) => format!("DEC {}", REGISTERS[y]),
+        (0, 6) => format!("LD {},{:#04x}", REGISTERS[y], immediate),
+        (0, _) => ["RLCA", "RRCA", "RLA", "RRA", "DAA", "CPL", "SCF", "CCF"][y].to_string(),
+        (1, 6) if y == 6 => "HALT".to_string(),
+        (1, _) => format!("LD {},{}", REGISTERS[y], REGISTERS[z]),
+        (2, _) => format!("{}{}", ARITHMETIC[y], REGISTERS[z]),
+        (_, 0) => match y {
+            0..=3 => format!("RET {}", CONDITIONS[y]),
+            4 => format!("LDH ({:#04x}),A", immediate),
+            5 => format!("ADD SP,{}", signed(offset)),
+            6 => format!("LDH A,({:#04x})", immediate),
+            _ => format!("LD HL,SP{}", signed(offset)),
+        },
+        (_, 1) if odd => ["RET", "RETI", "JP HL", "LD SP,HL"][p].to_string(),
+        (_, 1) => format!("POP {}", STACK_DOUBLE_REGISTERS[p]),
+        (_, 2) => match y {
+            0..=3 => format!("JP {},{:#06x}", CONDITIONS[y], address),
+            4 => "LD (C),A".to_string(),
+            5 => format!("LD ({:#06x}),A", address),
+            6 => "LD A,(C)".to_string(),
+            _ => format!("LD A,({:#06x})", address),
+        },
+        (_, 3) => match y {
+            0 => format!("JP {:#06x}", address),
+            1 => match bytes.get(1) {
+                Some(cb_opcode) => disassemble_cb(*cb_opcode),
+                None => "PREFIX CB".to_string(),
+            },
+            6 => "DI".to_string(),
+            7 => "EI".to_string(),
+            _ => format!("DB {:#04x}", opcode),
+        },
+        (_, 4) if y < 4 => format!("CALL {},{:#06x}", CONDITIONS[y], address),
+        (_, 5) if !odd => format!("PUSH {}", STACK_DOUBLE_REGISTERS[p]),
+        (_, 5) if y == 1 => format!("CALL {:#06x}", address),
+        (_, 6) => format!("{}{:#04x}", ARITHMETIC[y], immediate),
+        (_, 7) => format!("RST {:#04x}", y * 8),
+        _ => format!("DB {:#04x}", opcode),
+    }
+}
+
+/// Get the assembler mnemonic for the byte following the 0xCB prefix
+fn disassemble_cb(opcode: u8) -> String {
+    let y = ((opcode >> 3) & 0b111) as usize;
+    let register = REGISTERS[(opcode & 0b111) as usize];
+    match opcode >> 6 {
+        0 => format!("{} {}", ROTATIONS[y], register),
+        1 => format!("BIT {},{}", y, register),
+        2 => format!("RES {},{}", y, register),
+        _ => format!("SET {},{}", y, register),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::disassemble;
+    use crate::cpu::instruction::phases::{FourPhases, ThreePhases, TwoPhases};
+    use crate::cpu::instruction::{
+        decode, decode_cb, Instruction, JumpByImmediateOffsetConditional, JumpToImmediateAddress,
+        LoadImmediateToRegister,
+    };
+    use crate::cpu::{ConditionCode, Register};
+
+    #[test]
+    fn decoded_opcodes_have_the_right_mnemonics() {
+        let expected = [
+            (0x00, "NOP"),
+            (0x4F, "LD C,A"),
+            (0x7E, "LD A,(HL)"),
+            (0x76, "HALT"),
+            (0x22, "LD (HL+),A"),
+            (0x3A, "LD A,(HL-)"),
+            (0x09, "ADD HL,BC"),
+            (0x3B, "DEC SP"),
+            (0x86, "ADD A,(HL)"),
+            (0x90, "SUB B"),
+            (0xAF, "XOR A"),
+            (0xC1, "POP BC"),
+            (0xF5, "PUSH AF"),
+            (0xC9, "RET"),
+            (0xD9, "RETI"),
+            (0xE9, "JP HL"),
+            (0xE2, "LD (C),A"),
+            (0xFF, "RST 0x38"),
+            (0x2F, "CPL"),
+            (0xF3, "DI"),
+            (0xD3, "DB 0xd3"),
+        ];
+        for (opcode, mnemonic) in expected {
+            assert_eq!(decode(opcode).disassemble(), mnemonic);
+        }
+        assert_eq!(decode_cb(0x37).disassemble(), "SWAP A");
+        assert_eq!(decode_cb(0x46).disassemble(), "BIT 0,(HL)");
+        assert_eq!(decode_cb(0xFF).disassemble(), "SET 7,A");
+    }
+
+    #[test]
+    fn immediates_are_part_of_the_mnemonic() {
+        let jump = JumpToImmediateAddress {
+            address: 0x1234,
+            phase: FourPhases::First,
+        };
+        assert_eq!(jump.disassemble(), "JP 0x1234");
+
+        let load = LoadImmediateToRegister {
+            destination: Register::B,
+            value: 0x42,
+            phase: TwoPhases::First,
+        };
+        assert_eq!(load.disassemble(), "LD B,0x42");
+
+        let jump = JumpByImmediateOffsetConditional {
+            condition: ConditionCode::ZeroFlagUnset,
+            offset: -5,
+            phase: ThreePhases::First,
+        };
+        assert_eq!(jump.disassemble(), "JR NZ,-5");
+
+        assert_eq!(disassemble(&[0xF8, 0x02]), "LD HL,SP+2");
+        assert_eq!(disassemble(&[0xE0, 0x40]), "LDH (0x40),A");
+    }
+
+    #[test]
+    fn every_opcode_can_be_disassembled() {
+        for opcode in 0..=255u8 {
+            assert!(!decode(opcode).disassemble().is_empty());
+            assert!(!decode_cb(opcode).disassemble().is_empty());
+        }
+    }
+}
@@ -1,11 +1,12 @@
+use super::EncodedInstruction;
 use super::Instruction;
-use crate::{cpu::Cpu, memory::MemoryDevice};
+use crate::memory::MemoryDevice;
 
 /// Enable interrupts after the next instruction has finished.
 ///
 /// Sets IME to true.
 #[doc(alias = "EI")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EnableInterrupts {}
 
 impl Instruction for EnableInterrupts {
@@ -14,13 +15,12 @@ impl Instruction for EnableInterrupts {
         cpu: &mut crate::cpu::CpuState,
         memory: &mut T,
     ) -> super::InstructionEnum {
-        // Interrupts are only enabled after the next instruction
-        let next_instruction = cpu.load_instruction(memory);
-        cpu.write_interrupt_master_enable(true);
-        return next_instruction;
+        // Interrupts are only enabled after the next instruction has been fetched
+        cpu.schedule_interrupt_master_enable();
+        cpu.load_instruction(memory)
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b11111011])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b11111011])
     }
 }
 
@@ -28,7 +28,8 @@ impl Instruction for EnableInterrupts {
 mod tests {
     use super::EnableInterrupts;
     use crate::cpu::instruction::Instruction;
-    use crate::cpu::{Cpu, CpuState};
+    use crate::cpu::interrupt_controller::InterruptController;
+    use crate::cpu::{Cpu, CpuState, Interrupt, StepResult};
     use crate::memory::MemoryController;
 
     #[test]
@@ -44,4 +45,29 @@ mod tests {
 
         assert_eq!(cpu.read_interrupt_master_enable(), true);
     }
+
+    #[test]
+    fn instruction_after_enable_interrupts_is_not_interrupted() {
+        // EI; NOP; NOP
+        let mut memory = MemoryController::new_with_init(&[0xFB, 0x00, 0x00]);
+        let mut cpu = CpuState::new();
+        cpu.write_stack_pointer(0xff00);
+        memory.write_interrupt_enable(Interrupt::VBlank, true);
+
+        assert_eq!(cpu.step(&mut memory), StepResult::Executed(1));
+        // The interrupt is requested after EI, while the first NOP is already loaded
+        memory.write_interrupt_flag(Interrupt::VBlank, true);
+        assert!(cpu.read_interrupt_master_enable());
+
+        // The first NOP still runs, the interrupt is only dispatched after it
+        assert_eq!(cpu.step(&mut memory), StepResult::Executed(1));
+        assert_eq!(cpu.read_program_counter(), 0x0002);
+
+        cpu.step(&mut memory);
+        assert_eq!(
+            cpu.read_program_counter(),
+            Interrupt::VBlank.handler_address() + 1
+        );
+        assert!(!cpu.read_interrupt_master_enable());
+    }
 }
@@ -0,0 +1,99 @@
+use std::fmt;
+use std::ops::Deref;
+
+/// The binary representation of an instruction, as returned by [Instruction::encode](super::Instruction::encode).
+///
+/// Instructions are at most [EncodedInstruction::MAX_LENGTH] bytes long, so the bytes are stored inline instead of in a [Vec].
+/// Dereferences to a slice of the bytes.
+///
+/// ```
+/// # use rust_gameboy_library::cpu::instruction::EncodedInstruction;
+/// #
+/// let encoded = EncodedInstruction::from([0xC3, 0x34, 0x12]);
+/// assert_eq!(encoded.len(), 3);
+/// assert_eq!(encoded[1], 0x34);
+/// assert_eq!(encoded, [0xC3, 0x34, 0x12]);
+/// ```
+#[derive(Clone, Copy)]
+pub struct EncodedInstruction {
+    bytes: [u8; EncodedInstruction::MAX_LENGTH],
+    length: usize,
+}
+
+impl EncodedInstruction {
+    /// The length of the longest instructions
+    pub const MAX_LENGTH: usize = 3;
+}
+
+impl<const N: usize> From<[u8; N]> for EncodedInstruction {
+    fn from(value: [u8; N]) -> Self {
+        assert!(
+            N <= Self::MAX_LENGTH,
+            "Instructions are at most {} bytes long",
+            Self::MAX_LENGTH
+        );
+        let mut bytes = [0; Self::MAX_LENGTH];
+        bytes[..N].copy_from_slice(&value);
+        Self { bytes, length: N }
+    }
+}
+
+impl Deref for EncodedInstruction {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.length]
+    }
+}
+
+impl fmt::Debug for EncodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl PartialEq for EncodedInstruction {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for EncodedInstruction {}
+
+impl<const N: usize> PartialEq<[u8; N]> for EncodedInstruction {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<Vec<u8>> for EncodedInstruction {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        **self == **other
+    }
+}
+
+impl From<EncodedInstruction> for Vec<u8> {
+    fn from(value: EncodedInstruction) -> Self {
+        value.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EncodedInstruction;
+
+    #[test]
+    fn only_the_used_bytes_are_visible() {
+        let encoded = EncodedInstruction::from([0x3E, 0x42]);
+        assert_eq!(encoded.len(), 2);
+        assert_eq!(&*encoded, &[0x3E, 0x42]);
+        assert_eq!(encoded, Vec::from([0x3E, 0x42]));
+        assert_ne!(encoded, EncodedInstruction::from([0x3E, 0x42, 0x00]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Instructions are at most 3 bytes long")]
+    fn instructions_can_not_be_longer_than_three_bytes() {
+        let _ = EncodedInstruction::from([0x00, 0x00, 0x00, 0x00]);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::cpu::Flag;
+
+/// How an instruction affects a single flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagEffect {
+    /// The flag keeps its value
+    Unchanged,
+    /// The flag is always set to `1`
+    Set,
+    /// The flag is always set to `0`
+    Reset,
+    /// The flag depends on the result of the instruction
+    Modified,
+}
+
+/// Describes how an instruction affects each of the four flags.
+///
+/// This is the machine readable version of the flag tables in the documentation of the instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagEffects {
+    /// The effect on [Flag::Zero]
+    pub zero: FlagEffect,
+    /// The effect on [Flag::Subtract]
+    pub subtract: FlagEffect,
+    /// The effect on [Flag::HalfCarry]
+    pub half_carry: FlagEffect,
+    /// The effect on [Flag::Carry]
+    pub carry: FlagEffect,
+}
+
+impl FlagEffects {
+    /// The effects of an instruction that does not touch any flags
+    pub const NONE: FlagEffects = FlagEffects::new(
+        FlagEffect::Unchanged,
+        FlagEffect::Unchanged,
+        FlagEffect::Unchanged,
+        FlagEffect::Unchanged,
+    );
+
+    /// The effects of an instruction that can change every flag
+    pub const ALL: FlagEffects = FlagEffects::new(
+        FlagEffect::Modified,
+        FlagEffect::Modified,
+        FlagEffect::Modified,
+        FlagEffect::Modified,
+    );
+
+    /// Create a new [FlagEffects] with the effects in the order of the flags in the flags register.
+    pub const fn new(
+        zero: FlagEffect,
+        subtract: FlagEffect,
+        half_carry: FlagEffect,
+        carry: FlagEffect,
+    ) -> FlagEffects {
+        FlagEffects {
+            zero,
+            subtract,
+            half_carry,
+            carry,
+        }
+    }
+
+    /// Get the effect on a specific flag
+    pub fn get(&self, flag: Flag) -> FlagEffect {
+        match flag {
+            Flag::Zero => self.zero,
+            Flag::Subtract => self.subtract,
+            Flag::HalfCarry => self.half_carry,
+            Flag::Carry => self.carry,
+        }
+    }
+
+    /// Check if any flag can be changed
+    pub fn affects_any(&self) -> bool {
+        *self != FlagEffects::NONE
+    }
+}
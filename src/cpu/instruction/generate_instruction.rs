@@ -7,6 +7,9 @@ macro_rules! generate_instruction {
     $immediate_instruction_name:ident )? ),
     $( $cb_prefix:ident, )?
     $opcode:literal,
+    flags: [$zero_effect:ident, $subtract_effect:ident, $half_carry_effect:ident, $carry_effect:ident],
+    reads_carry: $reads_carry:literal,
+    writes_result: $writes_result:literal,
     $( $register_part_offset:literal, )?
     $cpu:ident,
     $memory:ident,
@@ -25,11 +28,50 @@ macro_rules! generate_instruction {
         $(consume_first!{$accumulator use super::phases::TwoPhases;})?
         use super::phases::ThreePhases;
 
-        use super::Instruction;
+        use super::{EncodedInstruction, Instruction};
+        use super::flag_effects::{FlagEffect, FlagEffects};
+        use super::register_effects::RegisterSet;
+
+        const FLAG_EFFECTS: FlagEffects = FlagEffects::new(
+            FlagEffect::$zero_effect,
+            FlagEffect::$subtract_effect,
+            FlagEffect::$half_carry_effect,
+            FlagEffect::$carry_effect,
+        );
+
+        /// The registers read and written by the instructions in this module. `operand` is `None` if the operand is not a register.
+        fn register_effects(operand: Option<Register>) -> (RegisterSet, RegisterSet) {
+            const USES_ACCUMULATOR: bool = [$(consume_first!($accumulator true),)? false][0];
+            const READS_CARRY: bool = $reads_carry;
+            const WRITES_RESULT: bool = $writes_result;
+
+            let mut reads = RegisterSet::new();
+            let mut writes = RegisterSet::new();
+            if let Some(operand) = operand {
+                reads = reads.with(operand);
+            }
+            if USES_ACCUMULATOR {
+                reads = reads.with(Register::A);
+            }
+            if READS_CARRY {
+                reads = reads.with(Register::F);
+            }
+            if WRITES_RESULT {
+                if USES_ACCUMULATOR {
+                    writes = writes.with(Register::A);
+                } else if let Some(operand) = operand {
+                    writes = writes.with(operand);
+                }
+            }
+            if FLAG_EFFECTS.affects_any() {
+                writes = writes.with(Register::F);
+            }
+            (reads, writes)
+        }
 
         $(#[$register_instruction_docs])*
         $(#[$shared_docs])*
-        #[derive(Debug)]
+        #[derive(Debug, Clone)]
 pub struct $register_instruction_name {
             /// The operand register
             pub operand: Register,
@@ -65,7 +107,13 @@ pub struct $register_instruction_name {
 
                 return $cpu.load_instruction($memory);
             }
-            fn encode(&self) -> Vec<u8> {
+            fn affects_flags(&self) -> FlagEffects {
+                FLAG_EFFECTS
+            }
+            fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+                register_effects(Some(self.operand))
+            }
+            fn encode(&self) -> EncodedInstruction {
                 if(matches!(self.operand, Register::F)){
                     panic!(stringify!(Arithmetic instructions do not have an opcode for operating on Register::F. That opcode is used for $hl_instruction_name))
                 }
@@ -78,13 +126,13 @@ pub struct $register_instruction_name {
                     let opcode = opcode | selected_bit_code;
                 )?
 
-                Vec::from([$(consume_first!($cb_prefix 0xcb) , )? opcode])
+                EncodedInstruction::from([$(consume_first!($cb_prefix 0xcb) , )? opcode])
             }
         }
 
         $(#[$hl_instruction_docs])*
         $(#[$shared_docs])*
-        #[derive(Debug)]
+        #[derive(Debug, Clone)]
 pub struct $hl_instruction_name {
             /// The current phase of the instruction.
             pub phase: ThreePhases,
@@ -167,7 +215,14 @@ pub struct $hl_instruction_name {
 
 
             }
-            fn encode(&self) -> Vec<u8> {
+            fn affects_flags(&self) -> FlagEffects {
+                FLAG_EFFECTS
+            }
+            fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+                let (reads, writes) = register_effects(None);
+                (reads.with_double(DoubleRegister::HL), writes)
+            }
+            fn encode(&self) -> EncodedInstruction {
 
                 let base_code = $opcode & !(0b00000111u8 << [$($register_part_offset ,)? 0][0]);
                 let operand_code = 0b00000110 << [$($register_part_offset ,)? 0][0];
@@ -178,7 +233,7 @@ pub struct $hl_instruction_name {
                     let opcode = opcode | selected_bit_code;
                 )?
 
-                Vec::from([$(consume_first!($cb_prefix 0xcb) , )? opcode])
+                EncodedInstruction::from([$(consume_first!($cb_prefix 0xcb) , )? opcode])
             }
         }
 
@@ -188,7 +243,7 @@ pub struct $hl_instruction_name {
         )?
             $(#[$shared_docs])*
         $(
-            #[derive(Debug)]
+            #[derive(Debug, Clone)]
 pub struct $immediate_instruction_name {
                 /// The immediate value. Will only valid in the second phase.
                 pub value: u8,
@@ -224,13 +279,23 @@ pub struct $immediate_instruction_name {
 
 
                 }
-                fn encode(&self) -> Vec<u8> {
+                fn affects_flags(&self) -> FlagEffects {
+                    FLAG_EFFECTS
+                }
+                fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+                    register_effects(None)
+                }
+                fn encode(&self) -> EncodedInstruction {
                     let opcode_immediate = $opcode + 0b01000110;
                     match self.phase {
-                        TwoPhases::First => Vec::from([opcode_immediate]),
-                        TwoPhases::Second => Vec::from([opcode_immediate, self.value]),
+                        TwoPhases::First => EncodedInstruction::from([opcode_immediate]),
+                        TwoPhases::Second => EncodedInstruction::from([opcode_immediate, self.value]),
                     }
                 }
+                fn encode_full(&self) -> EncodedInstruction {
+                    let opcode_immediate = $opcode + 0b01000110;
+                    EncodedInstruction::from([opcode_immediate, self.value])
+                }
             }
         )?
         struct __DocCommentBlackHole {}
@@ -1,15 +1,24 @@
+use super::EncodedInstruction;
 use super::Instruction;
-use crate::{cpu::Cpu, memory::MemoryDevice};
+use crate::{
+    cpu::{interrupt_controller::InterruptController, Cpu},
+    memory::MemoryDevice,
+};
 
 /// Halt the execution until the next interrupt.
 ///
 /// This is achieved by returning Halt instructions until a interrupt is pending
 ///
-// TODO: The halt instruction on gameboy apparently has some weird bug that is not implemented for now.
+/// If the IME is disabled and an interrupt is already pending when HALT is executed, the CPU does not halt.
+/// Instead the HALT bug occurs: the program counter is not incremented when the next opcode is fetched, so the byte after HALT is read twice.
+///
 // TODO: It also has slightly different behaviour than this, but I did not understand what exactly is different. See https://gbdev.io/pandocs/halt.html and https://www.reddit.com/r/EmuDev/comments/5bfb2t/comment/d9oqrwo/
 #[doc(alias = "HALT")]
-#[derive(Debug)]
-pub struct Halt {}
+#[derive(Debug, Clone)]
+pub struct Halt {
+    /// Whether the CPU is already halted. This is false for the first cycle of the instruction.
+    pub halted: bool,
+}
 
 impl Instruction for Halt {
     fn execute<T: MemoryDevice>(
@@ -17,14 +26,22 @@ impl Instruction for Halt {
         cpu: &mut crate::cpu::CpuState,
         memory: &mut T,
     ) -> super::InstructionEnum {
+        if !self.halted && !cpu.read_interrupt_master_enable() {
+            let triggered_interrupts =
+                memory.read_interrupt_enable_register() & memory.read_interrupt_flag_register();
+            if triggered_interrupts != 0 {
+                cpu.trigger_halt_bug();
+                return cpu.load_instruction(memory);
+            }
+        }
         let interrupt = cpu.get_pending_interrupt(memory);
         match interrupt {
             Some(instruction) => instruction,
-            None => (Self {}).into(),
+            None => (Self { halted: true }).into(),
         }
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b01110110])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b01110110])
     }
 }
 
@@ -33,7 +50,7 @@ mod tests {
     use super::Halt;
     use crate::cpu::instruction::{Instruction, InstructionEnum};
     use crate::cpu::interrupt_controller::InterruptController;
-    use crate::cpu::{Cpu, CpuState, Interrupt};
+    use crate::cpu::{Cpu, CpuState, Interrupt, Register};
     use crate::memory::MemoryController;
 
     #[test]
@@ -43,7 +60,7 @@ mod tests {
 
         cpu.write_interrupt_master_enable(false);
 
-        let instruction = Halt {};
+        let instruction = Halt { halted: false };
 
         let instruction = instruction.execute(&mut cpu, &mut memory);
         let instruction = instruction.execute(&mut cpu, &mut memory);
@@ -52,7 +69,10 @@ mod tests {
         let instruction = instruction.execute(&mut cpu, &mut memory);
         let instruction = instruction.execute(&mut cpu, &mut memory);
 
-        assert!(matches!(instruction, InstructionEnum::Halt(Halt {})));
+        assert!(matches!(
+            instruction,
+            InstructionEnum::Halt(Halt { halted: true })
+        ));
 
         cpu.write_interrupt_master_enable(true);
         memory.write_interrupt_enable(Interrupt::VBlank, true);
@@ -67,4 +87,42 @@ mod tests {
 
         assert_eq!(cpu.read_interrupt_master_enable(), true);
     }
+
+    #[test]
+    fn halt_bug_reads_the_next_byte_twice() {
+        // HALT; INC A; NOP
+        let mut memory = MemoryController::new_with_init(&[0x76, 0x3C, 0x00]);
+        let mut cpu = CpuState::new();
+        cpu.write_register(Register::A, 0);
+        memory.write_interrupt_enable(Interrupt::VBlank, true);
+        memory.write_interrupt_flag(Interrupt::VBlank, true);
+
+        cpu.step(&mut memory);
+        assert_eq!(cpu.read_program_counter(), 0x0001);
+        cpu.step(&mut memory);
+        assert_eq!(cpu.read_program_counter(), 0x0002);
+        cpu.step(&mut memory);
+        assert_eq!(cpu.read_program_counter(), 0x0003);
+
+        assert_eq!(cpu.read_register(Register::A), 2);
+    }
+
+    #[test]
+    fn halt_without_pending_interrupt_has_no_bug() {
+        // HALT; INC A; NOP
+        let mut memory = MemoryController::new_with_init(&[0x76, 0x3C, 0x00]);
+        let mut cpu = CpuState::new();
+        cpu.write_register(Register::A, 0);
+        memory.write_interrupt_enable(Interrupt::VBlank, true);
+
+        for _ in 0..4 {
+            cpu.step(&mut memory);
+        }
+        memory.write_interrupt_flag(Interrupt::VBlank, true);
+        for _ in 0..3 {
+            cpu.step(&mut memory);
+        }
+
+        assert_eq!(cpu.read_register(Register::A), 1);
+    }
 }
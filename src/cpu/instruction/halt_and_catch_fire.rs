@@ -1,9 +1,10 @@
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::memory::MemoryDevice;
 
 /// Illegal instruction. Lock up cpu.
 #[doc(alias = "HCF")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HaltAndCatchFire {
     /// The opcode that triggered this.
     pub opcode: u8,
@@ -24,8 +25,8 @@ impl Instruction for HaltAndCatchFire {
         }
         .into();
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([self.opcode])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([self.opcode])
     }
 }
 
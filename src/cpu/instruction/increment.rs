@@ -16,6 +16,9 @@ generate_instruction!(
         IncrementAtHl
     ),
     0b00000100,
+    flags: [Modified, Reset, Modified, Unchanged],
+    reads_carry: false,
+    writes_result: true,
     3,
     cpu,
     memory,
@@ -1,11 +1,11 @@
 use super::phases::TwoPhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
-use crate::cpu::DoubleRegister;
+use crate::cpu::DoubleRegisterOrStackPointer;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
-/// Increment a [DoubleRegister] by `1`.
-///
-/// Setting destination to DoubleRegister::AF actually means incrementing the stackpointer
+/// Increment a [DoubleRegisterOrStackPointer] by `1`.
 ///
 /// | [Zero](Flag::Zero)  | [Subtract](Flag::Subtract) | [HalfCarry](Flag::HalfCarry) | [Carry](Flag::Carry) |
 /// |---------------------|----------------------------|------------------------------|----------------------|
@@ -15,10 +15,10 @@ use crate::{cpu::Cpu, memory::MemoryDevice};
 #[doc(alias = "INC DE")]
 #[doc(alias = "INC HL")]
 #[doc(alias = "INC SP")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct IncrementDoubleRegister {
     /// The destination double register.
-    pub destination: DoubleRegister,
+    pub destination: DoubleRegisterOrStackPointer,
     /// The current phase of the instruction.
     pub phase: TwoPhases,
 }
@@ -31,15 +31,8 @@ impl Instruction for IncrementDoubleRegister {
     ) -> super::InstructionEnum {
         match self.phase {
             TwoPhases::First => {
-                match self.destination {
-                    DoubleRegister::AF => {
-                        cpu.write_stack_pointer(cpu.read_stack_pointer().wrapping_add(1))
-                    }
-                    _ => cpu.write_double_register(
-                        self.destination,
-                        cpu.read_double_register(self.destination).wrapping_add(1),
-                    ),
-                };
+                let register = self.destination.into();
+                cpu.write_register16(register, cpu.read_register16(register).wrapping_add(1));
 
                 Self {
                     destination: self.destination,
@@ -52,10 +45,14 @@ impl Instruction for IncrementDoubleRegister {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         let register_part = self.destination.numerical_id() << 4;
         let opcode = 0b00000011 | register_part;
-        Vec::from([opcode])
+        EncodedInstruction::from([opcode])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        let registers = RegisterSet::new().with_double_or_stack_pointer(self.destination);
+        (registers, registers)
     }
 }
 
@@ -64,7 +61,7 @@ mod tests {
     use super::IncrementDoubleRegister;
     use crate::cpu::instruction::phases::TwoPhases;
     use crate::cpu::instruction::{Instruction, InstructionEnum};
-    use crate::cpu::{Cpu, CpuState, DoubleRegister};
+    use crate::cpu::{Cpu, CpuState, DoubleRegister, DoubleRegisterOrStackPointer};
     use crate::memory::MemoryController;
 
     #[test]
@@ -74,7 +71,7 @@ mod tests {
         cpu.write_double_register(DoubleRegister::BC, 788);
 
         let instruction = IncrementDoubleRegister {
-            destination: DoubleRegister::BC,
+            destination: DoubleRegisterOrStackPointer::BC,
             phase: TwoPhases::First,
         };
 
@@ -84,7 +81,7 @@ mod tests {
             instruction,
             InstructionEnum::IncrementDoubleRegister(IncrementDoubleRegister {
                 phase: TwoPhases::Second,
-                destination: DoubleRegister::BC,
+                destination: DoubleRegisterOrStackPointer::BC,
             })
         ));
 
@@ -99,7 +96,7 @@ mod tests {
         cpu.write_double_register(DoubleRegister::AF, 0b0011110000000000);
 
         let instruction = IncrementDoubleRegister {
-            destination: DoubleRegister::AF,
+            destination: DoubleRegisterOrStackPointer::SP,
             phase: TwoPhases::First,
         };
 
@@ -109,7 +106,7 @@ mod tests {
             instruction,
             InstructionEnum::IncrementDoubleRegister(IncrementDoubleRegister {
                 phase: TwoPhases::Second,
-                destination: DoubleRegister::AF,
+                destination: DoubleRegisterOrStackPointer::SP,
             })
         ));
 
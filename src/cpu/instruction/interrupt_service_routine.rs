@@ -1,4 +1,5 @@
 use super::phases::FivePhases;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
@@ -11,7 +12,7 @@ use crate::{cpu::Cpu, memory::MemoryDevice};
 /// It also disables the interrupt master enable. That needs to be enabled again before the net interrupt can be processed.
 ///
 /// For some reason this is one phase shorter than [Call](super::Call), idk why maybe the docs are wrong.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InterruptServiceRoutine {
     /// The address of the interrupt handler.
     pub address: u16,
@@ -74,13 +75,21 @@ impl Instruction for InterruptServiceRoutine {
             FivePhases::Fifth => {
                 cpu.write_program_counter(self.address);
 
+                // The IME was already disabled in the first phase, so this fetches the first opcode of the handler even if other interrupts are pending.
                 return cpu.load_instruction(memory);
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         panic!("The interrupt service routine does not have an opcode.")
     }
+    fn disassemble(&self) -> String {
+        // There is no mnemonic, so describe what happens instead
+        format!("INT {:#06x}", self.address)
+    }
+    fn cycles(&self) -> u8 {
+        5
+    }
 }
 
 #[cfg(test)]
@@ -88,7 +97,8 @@ mod tests {
     use super::InterruptServiceRoutine;
     use crate::cpu::instruction::phases::FivePhases;
     use crate::cpu::instruction::{Instruction, InstructionEnum};
-    use crate::cpu::{Cpu, CpuState};
+    use crate::cpu::interrupt_controller::InterruptController;
+    use crate::cpu::{Cpu, CpuState, Interrupt, Register};
     use crate::memory::MemoryController;
     use crate::memory::MemoryDevice;
 
@@ -132,6 +142,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn instruction_after_interrupt_is_fetched_from_the_handler() {
+        let mut cpu = CpuState::new();
+        let mut memory = MemoryController::new_for_tests();
+        // LD A,0x42 at the VBlank handler
+        memory.write(Interrupt::VBlank.handler_address(), 0x3E);
+        memory.write(Interrupt::VBlank.handler_address() + 1, 0x42);
+        cpu.write_stack_pointer(0xff00);
+        cpu.write_interrupt_master_enable(true);
+        memory.write_interrupt_enable(Interrupt::VBlank, true);
+        memory.write_interrupt_enable(Interrupt::Timer, true);
+        memory.write_interrupt_flag(Interrupt::VBlank, true);
+        memory.write_interrupt_flag(Interrupt::Timer, true);
+
+        let mut instruction = cpu.load_instruction(&mut memory);
+        assert!(matches!(
+            instruction,
+            InstructionEnum::InterruptServiceRoutine(InterruptServiceRoutine { address: 0x40, .. })
+        ));
+        for _ in 0..5 {
+            instruction = instruction.execute(&mut cpu, &mut memory);
+        }
+
+        // The pending timer interrupt must not be dispatched before the first instruction of the handler
+        assert!(matches!(
+            instruction,
+            InstructionEnum::LoadImmediateToRegister(_)
+        ));
+        assert_eq!(cpu.read_program_counter(), 0x0041);
+        assert!(!cpu.read_interrupt_master_enable());
+        assert!(memory.read_interrupt_flag(Interrupt::Timer));
+        assert!(!memory.read_interrupt_flag(Interrupt::VBlank));
+
+        let instruction = instruction.execute(&mut cpu, &mut memory);
+        instruction.execute(&mut cpu, &mut memory);
+        assert_eq!(cpu.read_register(Register::A), 0x42);
+    }
+
     #[test]
     #[should_panic]
     fn encode_interrupt_service_routine() {
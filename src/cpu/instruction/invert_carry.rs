@@ -1,4 +1,8 @@
+use super::flag_effects::{FlagEffect, FlagEffects};
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
+use crate::cpu::Register;
 use crate::{
     cpu::{Cpu, Flag},
     memory::MemoryDevice,
@@ -29,7 +33,7 @@ use crate::{
 /// |---------------------|----------------------------|------------------------------|----------------------------|
 /// | unchanged           | false                      | false                        | true if carry was set      |
 #[doc(alias = "CCF")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InvertCarry {}
 
 impl Instruction for InvertCarry {
@@ -44,8 +48,22 @@ impl Instruction for InvertCarry {
 
         return cpu.load_instruction(memory);
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b00111111])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b00111111])
+    }
+    fn affects_flags(&self) -> FlagEffects {
+        FlagEffects::new(
+            FlagEffect::Unchanged,
+            FlagEffect::Reset,
+            FlagEffect::Reset,
+            FlagEffect::Modified,
+        )
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with(Register::F),
+            RegisterSet::new().with(Register::F),
+        )
     }
 }
 
@@ -1,10 +1,11 @@
 use super::phases::ThreePhases;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
 /// Jumps by a signed offset specified in the byte following the opcode.
 #[doc(alias = "JR")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct JumpByImmediateOffset {
     /// The immediate offset. Will only valid after the first phase.
     pub offset: i8,
@@ -30,9 +31,7 @@ impl Instruction for JumpByImmediateOffset {
                 .into()
             }
             ThreePhases::Second => {
-                cpu.write_program_counter(
-                    cpu.read_program_counter().wrapping_add(self.offset as u16),
-                );
+                cpu.offset_program_counter(self.offset);
 
                 Self {
                     phase: ThreePhases::Third,
@@ -45,12 +44,15 @@ impl Instruction for JumpByImmediateOffset {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         match self.phase {
-            ThreePhases::First => Vec::from([0b00011000]),
-            _ => Vec::from([0b00011000, self.offset.to_ne_bytes()[0]]),
+            ThreePhases::First => EncodedInstruction::from([0b00011000]),
+            _ => EncodedInstruction::from([0b00011000, self.offset.to_ne_bytes()[0]]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b00011000, self.offset.to_ne_bytes()[0]])
+    }
 }
 
 #[cfg(test)]
@@ -96,6 +98,22 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn jump_by_negative_offset_wraps_around() {
+        let mut cpu = CpuState::new();
+        let mut memory = MemoryController::new_with_init(&[0xFC]);
+        assert_eq!(cpu.read_program_counter(), 0x0000);
+
+        let instruction = JumpByImmediateOffset {
+            offset: 0,
+            phase: ThreePhases::First,
+        };
+        let instruction = instruction.execute(&mut cpu, &mut memory);
+        instruction.execute(&mut cpu, &mut memory);
+
+        assert_eq!(cpu.read_program_counter(), 0xFFFD);
+    }
+
     #[test]
     fn encode_jump_by_immediate_address() {
         // Write 42 to A and then copy A to C
@@ -1,5 +1,8 @@
 use super::phases::ThreePhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
+use crate::cpu::Register;
 use crate::{
     cpu::{ConditionCode, Cpu},
     memory::MemoryDevice,
@@ -11,7 +14,7 @@ use crate::{
 ///
 /// The condition is evaluated in the second phase
 #[doc(alias = "JR")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct JumpByImmediateOffsetConditional {
     /// The jump is only made if the condition is fullfilled in the third phase.
     pub condition: ConditionCode,
@@ -45,9 +48,7 @@ impl Instruction for JumpByImmediateOffsetConditional {
                     return cpu.load_instruction(memory);
                 }
 
-                cpu.write_program_counter(
-                    cpu.read_program_counter().wrapping_add(self.offset as u16),
-                );
+                cpu.offset_program_counter(self.offset);
 
                 Self {
                     condition: self.condition,
@@ -61,15 +62,24 @@ impl Instruction for JumpByImmediateOffsetConditional {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         let condition_code_part = ((self.condition as u8) << 3) & 0b00011000;
         let opcode = 0b00100000 | condition_code_part;
 
         match self.phase {
-            ThreePhases::First => Vec::from([opcode]),
-            _ => Vec::from([opcode, self.offset.to_ne_bytes()[0]]),
+            ThreePhases::First => EncodedInstruction::from([opcode]),
+            _ => EncodedInstruction::from([opcode, self.offset.to_ne_bytes()[0]]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        let condition_code_part = ((self.condition as u8) << 3) & 0b00011000;
+        let opcode = 0b00100000 | condition_code_part;
+
+        EncodedInstruction::from([opcode, self.offset.to_ne_bytes()[0]])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (RegisterSet::new().with(Register::F), RegisterSet::new())
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{
     cpu::{Cpu, DoubleRegister},
@@ -6,7 +8,7 @@ use crate::{
 
 /// Jumps to the address stored in [DoubleRegister::HL].
 #[doc(alias = "JP")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct JumpToHl {}
 
 impl Instruction for JumpToHl {
@@ -20,8 +22,14 @@ impl Instruction for JumpToHl {
 
         return cpu.load_instruction(memory);
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b11101001])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b11101001])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with_double(DoubleRegister::HL),
+            RegisterSet::new(),
+        )
     }
 }
 
@@ -1,10 +1,11 @@
 use super::phases::FourPhases;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
 /// Jumps to the address specified in the two bytes following the opcode
 #[doc(alias = "JP")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct JumpToImmediateAddress {
     /// The immediate address. Will only valid after the second phase.
     pub address: u16,
@@ -53,17 +54,26 @@ impl Instruction for JumpToImmediateAddress {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         match self.phase {
-            FourPhases::First => Vec::from([0b11000011]),
-            FourPhases::Second => Vec::from([0b11000011, self.address.to_le_bytes()[0]]),
-            _ => Vec::from([
+            FourPhases::First => EncodedInstruction::from([0b11000011]),
+            FourPhases::Second => {
+                EncodedInstruction::from([0b11000011, self.address.to_le_bytes()[0]])
+            }
+            _ => EncodedInstruction::from([
                 0b11000011,
                 self.address.to_le_bytes()[0],
                 self.address.to_le_bytes()[1],
             ]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        EncodedInstruction::from([
+            0b11000011,
+            self.address.to_le_bytes()[0],
+            self.address.to_le_bytes()[1],
+        ])
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,8 @@
 use super::phases::FourPhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
+use crate::cpu::Register;
 use crate::{
     cpu::{ConditionCode, Cpu},
     memory::MemoryDevice,
@@ -11,7 +14,7 @@ use crate::{
 ///
 /// The condition is evaluated in the third phase
 #[doc(alias = "JP")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct JumpToImmediateAddressConditional {
     /// The jump is only made if the condition is fullfilled in the third phase.
     pub condition: ConditionCode,
@@ -70,20 +73,33 @@ impl Instruction for JumpToImmediateAddressConditional {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         let condition_code_part = ((self.condition as u8) << 3) & 0b00011000;
         let opcode = 0b11000010 | condition_code_part;
 
         match self.phase {
-            FourPhases::First => Vec::from([opcode]),
-            FourPhases::Second => Vec::from([opcode, self.address.to_le_bytes()[0]]),
-            _ => Vec::from([
+            FourPhases::First => EncodedInstruction::from([opcode]),
+            FourPhases::Second => EncodedInstruction::from([opcode, self.address.to_le_bytes()[0]]),
+            _ => EncodedInstruction::from([
                 opcode,
                 self.address.to_le_bytes()[0],
                 self.address.to_le_bytes()[1],
             ]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        let condition_code_part = ((self.condition as u8) << 3) & 0b00011000;
+        let opcode = 0b11000010 | condition_code_part;
+
+        EncodedInstruction::from([
+            opcode,
+            self.address.to_le_bytes()[0],
+            self.address.to_le_bytes()[1],
+        ])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (RegisterSet::new().with(Register::F), RegisterSet::new())
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::{phases::TwoPhases, Instruction};
 use crate::{
     cpu::{Cpu, DoubleRegister, Register},
@@ -8,7 +10,7 @@ use crate::{
 #[doc(alias = "LD")]
 #[doc(alias = "LD (BC),A")]
 #[doc(alias = "LD (DE),A")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadAccumulatorToDoubleRegister {
     /// The double register containing the address
     pub address_register: DoubleRegister,
@@ -37,15 +39,23 @@ impl Instruction for LoadAccumulatorToDoubleRegister {
             TwoPhases::Second => cpu.load_instruction(memory),
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         match self.address_register {
-            DoubleRegister::BC => Vec::from([0b00000010]),
-            DoubleRegister::DE => Vec::from([0b00010010]),
+            DoubleRegister::BC => EncodedInstruction::from([0b00000010]),
+            DoubleRegister::DE => EncodedInstruction::from([0b00010010]),
             _ => panic!(
                 "Cannot only encode LoadAccumulatorToDoubleRegister for DoubleRegisters BC and DE"
             ),
         }
     }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new()
+                .with(Register::A)
+                .with_double(self.address_register),
+            RegisterSet::new(),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::{phases::ThreePhases, Instruction};
 use crate::{
     cpu::{Cpu, Register},
@@ -9,7 +11,7 @@ use crate::{
 #[doc(alias = "LDH (n),A")]
 #[doc(alias = "LD")]
 #[doc(alias = "LD ($FF00+n),A")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadAccumulatorToImmediateOffset {
     /// The memory address offset from 0xff00. Only valid after the first phase.
     pub offset: u8,
@@ -49,12 +51,18 @@ impl Instruction for LoadAccumulatorToImmediateOffset {
             ThreePhases::Third => cpu.load_instruction(memory),
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         match self.phase {
-            ThreePhases::First => Vec::from([0b11100000]),
-            _ => Vec::from([0b11100000, self.offset]),
+            ThreePhases::First => EncodedInstruction::from([0b11100000]),
+            _ => EncodedInstruction::from([0b11100000, self.offset]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b11100000, self.offset])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (RegisterSet::new().with(Register::A), RegisterSet::new())
+    }
 }
 
 #[cfg(test)]
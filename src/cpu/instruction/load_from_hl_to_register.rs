@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::{phases::TwoPhases, Instruction};
 use crate::{
     cpu::{Cpu, DoubleRegister, Register},
@@ -7,7 +9,7 @@ use crate::{
 /// Loads from memory at the address stored in [DoubleRegister::HL] to a register.
 #[doc(alias = "LD")]
 #[doc(alias = "LD R,(HL)")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadFromHlToRegister {
     /// The destination register.
     pub destination: Register,
@@ -23,6 +25,10 @@ impl Instruction for LoadFromHlToRegister {
     ) -> super::InstructionEnum {
         match self.phase {
             TwoPhases::First => {
+                // The register encoding of F (0b110) means (HL), so this can never be decoded
+                if matches!(self.destination, Register::F) {
+                    panic!("Cannot load from hl to Register::F")
+                }
                 let address = cpu.read_double_register(DoubleRegister::HL);
                 let data = memory.read(address);
                 // This should probably happen in the next phase of this instruction
@@ -36,14 +42,20 @@ impl Instruction for LoadFromHlToRegister {
             TwoPhases::Second => cpu.load_instruction(memory),
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         if matches!(self.destination, Register::F) {
             panic!("Cannot encode load from hl for Register::F")
         }
         let base_code = 0b01000110;
         let destination_code = (self.destination.id() << 3) & 0b00111000u8;
         let opcode = base_code | destination_code;
-        Vec::from([opcode])
+        EncodedInstruction::from([opcode])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with_double(DoubleRegister::HL),
+            RegisterSet::new().with(self.destination),
+        )
     }
 }
 
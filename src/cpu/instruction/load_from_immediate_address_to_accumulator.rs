@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::{phases::FourPhases, Instruction};
 use crate::{
     cpu::{Cpu, Register},
@@ -9,7 +11,7 @@ use crate::{
 /// Reads from program counter: `opcode` `address lsb` `address msb`
 #[doc(alias = "LD")]
 #[doc(alias = "LD A,(nn)")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadFromImmediateAddressToAccumulator {
     /// The memory address. Only valid after the second phase.
     pub address: u16,
@@ -57,17 +59,29 @@ impl Instruction for LoadFromImmediateAddressToAccumulator {
             FourPhases::Fourth => cpu.load_instruction(memory),
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         match self.phase {
-            FourPhases::First => Vec::from([0b11111010]),
-            FourPhases::Second => Vec::from([0b11111010, self.address.to_le_bytes()[0]]),
-            _ => Vec::from([
+            FourPhases::First => EncodedInstruction::from([0b11111010]),
+            FourPhases::Second => {
+                EncodedInstruction::from([0b11111010, self.address.to_le_bytes()[0]])
+            }
+            _ => EncodedInstruction::from([
                 0b11111010,
                 self.address.to_le_bytes()[0],
                 self.address.to_le_bytes()[1],
             ]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        EncodedInstruction::from([
+            0b11111010,
+            self.address.to_le_bytes()[0],
+            self.address.to_le_bytes()[1],
+        ])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (RegisterSet::new(), RegisterSet::new().with(Register::A))
+    }
 }
 
 #[cfg(test)]
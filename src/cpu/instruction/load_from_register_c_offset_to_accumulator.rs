@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::{phases::TwoPhases, Instruction};
 use crate::{
     cpu::{Cpu, Register},
@@ -8,7 +10,7 @@ use crate::{
 #[doc(alias = "LD")]
 #[doc(alias = "LD A,(C)")]
 #[doc(alias = "LD A,($FF00+C)")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadFromRegisterCOffsetToAccumulator {
     /// The current phase of the instruction.
     pub phase: TwoPhases,
@@ -35,8 +37,14 @@ impl Instruction for LoadFromRegisterCOffsetToAccumulator {
             TwoPhases::Second => cpu.load_instruction(memory),
         }
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b11110010])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b11110010])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with(Register::C),
+            RegisterSet::new().with(Register::A),
+        )
     }
 }
 
@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{cpu::Register, memory::MemoryDevice};
 
@@ -6,7 +8,7 @@ use crate::{cpu::Register, memory::MemoryDevice};
 /// Cannot be used with [Register::F]
 #[doc(alias = "LD")]
 #[doc(alias = "LD R,R")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadFromRegisterToRegister {
     /// The source register
     pub source: Register,
@@ -26,7 +28,7 @@ impl Instruction for LoadFromRegisterToRegister {
         cpu.registers[self.destination as usize] = cpu.registers[self.source as usize];
         return cpu.load_instruction(memory);
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         if matches!(self.destination, Register::F) {
             panic!(
                 "Cannot encode load from register to register for destination register Register::F"
@@ -39,7 +41,13 @@ impl Instruction for LoadFromRegisterToRegister {
         let source_code = self.source.id() & 0b00000111u8;
         let destination_code = (self.destination.id() << 3) & 0b00111000u8;
         let opcode = base_code | source_code | destination_code;
-        Vec::from([opcode])
+        EncodedInstruction::from([opcode])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with(self.source),
+            RegisterSet::new().with(self.destination),
+        )
     }
 }
 
@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::{phases::TwoPhases, Instruction};
 use crate::{
     cpu::{Cpu, DoubleRegister, Register},
@@ -10,7 +12,7 @@ use crate::{
 #[doc(alias = "LD A,(HLI)")]
 #[doc(alias = "LDI")]
 #[doc(alias = "LDI A,(HL)")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadHlToAccumulatorAndIncrement {
     /// The current phase of the instruction.
     pub phase: TwoPhases,
@@ -38,8 +40,16 @@ impl Instruction for LoadHlToAccumulatorAndIncrement {
             TwoPhases::Second => cpu.load_instruction(memory),
         }
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b00101010])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b00101010])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with_double(DoubleRegister::HL),
+            RegisterSet::new()
+                .with(Register::A)
+                .with_double(DoubleRegister::HL),
+        )
     }
 }
 
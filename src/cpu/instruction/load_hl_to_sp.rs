@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::{phases::TwoPhases, Instruction};
 use crate::{
     cpu::{Cpu, DoubleRegister},
@@ -7,7 +9,7 @@ use crate::{
 /// Copies the data stored in [DoubleRegister::HL] to the stackpointer register
 #[doc(alias = "LD")]
 #[doc(alias = "LD SP,HL")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadHlToSp {
     /// The current phase of the instruction.
     pub phase: TwoPhases,
@@ -32,8 +34,14 @@ impl Instruction for LoadHlToSp {
             TwoPhases::Second => cpu.load_instruction(memory),
         }
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b11111001])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b11111001])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with_double(DoubleRegister::HL),
+            RegisterSet::new(),
+        )
     }
 }
 
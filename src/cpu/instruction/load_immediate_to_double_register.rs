@@ -1,20 +1,20 @@
 use super::phases::ThreePhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
-use crate::cpu::DoubleRegister;
+use crate::cpu::DoubleRegisterOrStackPointer;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
 /// Loads the two bytes following the opcode of the instruction to a double register
-///
-/// Setting destination to DoubleRegister::AF actually means loading them to the stackpointer
 #[doc(alias = "LD")]
 #[doc(alias = "LD BC,nn")]
 #[doc(alias = "LD DE,nn")]
 #[doc(alias = "LD HL,nn")]
 #[doc(alias = "LD SP,nn")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadImmediateToDoubleRegister {
     /// The destination double register.
-    pub destination: DoubleRegister,
+    pub destination: DoubleRegisterOrStackPointer,
     /// The immediate value. Will only valid after the second phase.
     pub value: u16,
     /// The current phase of the instruction.
@@ -43,10 +43,7 @@ impl Instruction for LoadImmediateToDoubleRegister {
                 let program_counter = cpu.advance_program_counter();
                 let value_msb = memory.read(program_counter) as u16;
                 let data = self.value | ((value_msb) << 8);
-                match self.destination {
-                    DoubleRegister::AF => cpu.write_stack_pointer(data),
-                    _ => cpu.write_double_register(self.destination, data),
-                };
+                cpu.write_register16(self.destination.into(), data);
 
                 Self {
                     destination: self.destination,
@@ -60,19 +57,32 @@ impl Instruction for LoadImmediateToDoubleRegister {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         let register_part = self.destination.numerical_id() << 4;
         let opcode = 0b00000001 | register_part;
         match self.phase {
-            ThreePhases::Second => Vec::from([opcode, self.value.to_le_bytes()[0]]),
-            ThreePhases::Third => Vec::from([
+            ThreePhases::Second => EncodedInstruction::from([opcode, self.value.to_le_bytes()[0]]),
+            ThreePhases::Third => EncodedInstruction::from([
                 opcode,
                 self.value.to_le_bytes()[0],
                 self.value.to_le_bytes()[1],
             ]),
-            _ => Vec::from([opcode]),
+            _ => EncodedInstruction::from([opcode]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        let register_part = self.destination.numerical_id() << 4;
+        let opcode = 0b00000001 | register_part;
+        EncodedInstruction::from([
+            opcode,
+            self.value.to_le_bytes()[0],
+            self.value.to_le_bytes()[1],
+        ])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        let registers = RegisterSet::new().with_double_or_stack_pointer(self.destination);
+        (RegisterSet::new(), registers)
+    }
 }
 
 #[cfg(test)]
@@ -80,7 +90,7 @@ mod tests {
     use super::LoadImmediateToDoubleRegister;
     use crate::cpu::instruction::phases::ThreePhases;
     use crate::cpu::instruction::{Instruction, InstructionEnum};
-    use crate::cpu::{Cpu, CpuState, DoubleRegister};
+    use crate::cpu::{Cpu, CpuState, DoubleRegister, DoubleRegisterOrStackPointer};
     use crate::memory::MemoryController;
 
     #[test]
@@ -90,7 +100,7 @@ mod tests {
         let mut memory = MemoryController::new_with_init(&[0x34, 0x12]);
 
         let instruction = LoadImmediateToDoubleRegister {
-            destination: DoubleRegister::BC,
+            destination: DoubleRegisterOrStackPointer::BC,
             value: 0,
             phase: ThreePhases::First,
         };
@@ -101,7 +111,7 @@ mod tests {
             instruction,
             InstructionEnum::LoadImmediateToDoubleRegister(LoadImmediateToDoubleRegister {
                 phase: ThreePhases::Second,
-                destination: DoubleRegister::BC,
+                destination: DoubleRegisterOrStackPointer::BC,
                 value: 0x0034
             })
         ));
@@ -112,7 +122,7 @@ mod tests {
             instruction,
             InstructionEnum::LoadImmediateToDoubleRegister(LoadImmediateToDoubleRegister {
                 phase: ThreePhases::Third,
-                destination: DoubleRegister::BC,
+                destination: DoubleRegisterOrStackPointer::BC,
                 value: 0x1234
             })
         ));
@@ -127,7 +137,7 @@ mod tests {
         let mut memory = MemoryController::new_with_init(&[0x34, 0x12]);
 
         let instruction = LoadImmediateToDoubleRegister {
-            destination: DoubleRegister::DE,
+            destination: DoubleRegisterOrStackPointer::DE,
             value: 0,
             phase: ThreePhases::First,
         };
@@ -141,4 +151,25 @@ mod tests {
         assert_eq!(encoded[1], 0x34);
         assert_eq!(encoded[2], 0x12);
     }
+
+    #[test]
+    fn encode_full_includes_unread_immediate() {
+        let instruction = LoadImmediateToDoubleRegister {
+            destination: DoubleRegisterOrStackPointer::DE,
+            value: 0,
+            phase: ThreePhases::First,
+        };
+        assert_eq!(instruction.encode(), Vec::from([0b00010001]));
+        assert_eq!(instruction.encode_full(), Vec::from([0b00010001, 0, 0]));
+
+        let mut cpu = CpuState::new();
+        let mut memory = MemoryController::new_with_init(&[0x34, 0x12]);
+        let instruction = instruction.execute(&mut cpu, &mut memory);
+        assert_eq!(instruction.encode_full(), Vec::from([0b00010001, 0x34, 0]));
+        let instruction = instruction.execute(&mut cpu, &mut memory);
+        assert_eq!(
+            instruction.encode_full(),
+            Vec::from([0b00010001, 0x34, 0x12])
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::{phases::ThreePhases, Instruction};
 use crate::{
     cpu::{Cpu, DoubleRegister},
@@ -7,7 +9,7 @@ use crate::{
 /// Stores the byte following the opcode to the address specified in [HL](DoubleRegister::HL).
 #[doc(alias = "LD")]
 #[doc(alias = "LD (HL),n")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadImmediateToHl {
     /// The immediate value. Only valid after the first phase.
     pub value: u8,
@@ -45,12 +47,21 @@ impl Instruction for LoadImmediateToHl {
             ThreePhases::Third => cpu.load_instruction(memory),
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         match self.phase {
-            ThreePhases::First => Vec::from([0b00110110]),
-            _ => Vec::from([0b00110110, self.value]),
+            ThreePhases::First => EncodedInstruction::from([0b00110110]),
+            _ => EncodedInstruction::from([0b00110110, self.value]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b00110110, self.value])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with_double(DoubleRegister::HL),
+            RegisterSet::new(),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,6 @@
 use super::phases::TwoPhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{
     cpu::{Cpu, Register},
@@ -8,7 +10,7 @@ use crate::{
 /// Loads the byte following the opcode of the instruction to a register
 #[doc(alias = "LD")]
 #[doc(alias = "LD R,n")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadImmediateToRegister {
     /// The destination register.
     pub destination: Register,
@@ -41,7 +43,7 @@ impl Instruction for LoadImmediateToRegister {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         if matches!(self.destination, Register::F) {
             panic!("Cannot encode load immediate to register for destination register Register::F")
         }
@@ -49,10 +51,20 @@ impl Instruction for LoadImmediateToRegister {
         let destination_code = (self.destination.id() << 3) & 0b00111000u8;
         let opcode = base_code | destination_code;
         match self.phase {
-            TwoPhases::First => Vec::from([opcode]),
-            TwoPhases::Second => Vec::from([opcode, self.value]),
+            TwoPhases::First => EncodedInstruction::from([opcode]),
+            TwoPhases::Second => EncodedInstruction::from([opcode, self.value]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        let opcode = self.encode()[0];
+        EncodedInstruction::from([opcode, self.value])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new(),
+            RegisterSet::new().with(self.destination),
+        )
+    }
 }
 
 #[cfg(test)]
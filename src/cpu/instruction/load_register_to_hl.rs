@@ -1,3 +1,5 @@
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::{phases::TwoPhases, Instruction};
 use crate::{
     cpu::{Cpu, DoubleRegister, Register},
@@ -7,7 +9,7 @@ use crate::{
 /// Stores the data from a register to the address specified in [HL](DoubleRegister::HL).
 #[doc(alias = "LD")]
 #[doc(alias = "LD (HL),R")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadRegisterToHl {
     /// The source register.
     pub source: Register,
@@ -23,6 +25,10 @@ impl Instruction for LoadRegisterToHl {
     ) -> super::InstructionEnum {
         match self.phase {
             TwoPhases::First => {
+                // The register encoding of F (0b110) means (HL), so this can never be decoded
+                if matches!(self.source, Register::F) {
+                    panic!("Cannot load from Register::F to hl")
+                }
                 let address = cpu.read_double_register(DoubleRegister::HL);
                 let data = cpu.read_register(self.source);
                 memory.write(address, data);
@@ -36,14 +42,22 @@ impl Instruction for LoadRegisterToHl {
             TwoPhases::Second => cpu.load_instruction(memory),
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         if matches!(self.source, Register::F) {
             panic!("Cannot encode load register to hl for destination source Register::F")
         }
         let base_code = 0b01110000 & 0b11111000u8;
         let destination_code = self.source.id() & 0b00000111u8;
         let opcode = base_code | destination_code;
-        Vec::from([opcode])
+        EncodedInstruction::from([opcode])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new()
+                .with(self.source)
+                .with_double(DoubleRegister::HL),
+            RegisterSet::new(),
+        )
     }
 }
 
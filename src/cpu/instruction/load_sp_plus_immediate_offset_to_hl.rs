@@ -1,5 +1,9 @@
+use super::flag_effects::{FlagEffect, FlagEffects};
 use super::phases::ThreePhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
+use crate::cpu::Register;
 use crate::{
     cpu::{Cpu, DoubleRegister, Flag},
     memory::MemoryDevice,
@@ -16,7 +20,7 @@ use crate::{
 #[doc(alias = "LD HL,SP+n")]
 #[doc(alias = "LDHL")]
 #[doc(alias = "LDHL SP,n")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadSpPlusImmediateOffsetToHl {
     /// The immediate offset. Will only valid after the first phase.
     pub offset: i8,
@@ -73,12 +77,31 @@ impl Instruction for LoadSpPlusImmediateOffsetToHl {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         match self.phase {
-            ThreePhases::First => Vec::from([0b11111000]),
-            _ => Vec::from([0b11111000, self.offset.to_ne_bytes()[0]]),
+            ThreePhases::First => EncodedInstruction::from([0b11111000]),
+            _ => EncodedInstruction::from([0b11111000, self.offset.to_ne_bytes()[0]]),
         }
     }
+    fn affects_flags(&self) -> FlagEffects {
+        FlagEffects::new(
+            FlagEffect::Reset,
+            FlagEffect::Reset,
+            FlagEffect::Modified,
+            FlagEffect::Modified,
+        )
+    }
+    fn encode_full(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b11111000, self.offset.to_ne_bytes()[0]])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new(),
+            RegisterSet::new()
+                .with_double(DoubleRegister::HL)
+                .with(Register::F),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
 use super::phases::FivePhases;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
@@ -8,7 +9,7 @@ use crate::{cpu::Cpu, memory::MemoryDevice};
 /// The msb of the stackpointer is stored at the specified address + 1.
 #[doc(alias = "LD")]
 #[doc(alias = "LD (nn),SP")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LoadSpToImmediateAddress {
     /// The target address. Will only valid after the second phase.
     pub address: u16,
@@ -68,17 +69,26 @@ impl Instruction for LoadSpToImmediateAddress {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         match self.phase {
-            FivePhases::First => Vec::from([0b00001000]),
-            FivePhases::Second => Vec::from([0b00001000, self.address.to_le_bytes()[0]]),
-            _ => Vec::from([
+            FivePhases::First => EncodedInstruction::from([0b00001000]),
+            FivePhases::Second => {
+                EncodedInstruction::from([0b00001000, self.address.to_le_bytes()[0]])
+            }
+            _ => EncodedInstruction::from([
                 0b00001000,
                 self.address.to_le_bytes()[0],
                 self.address.to_le_bytes()[1],
             ]),
         }
     }
+    fn encode_full(&self) -> EncodedInstruction {
+        EncodedInstruction::from([
+            0b00001000,
+            self.address.to_le_bytes()[0],
+            self.address.to_le_bytes()[1],
+        ])
+    }
 }
 
 #[cfg(test)]
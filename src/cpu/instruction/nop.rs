@@ -1,9 +1,10 @@
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::memory::MemoryDevice;
 
 /// Do nothing and load the next instruction.
 #[doc(alias = "NOP")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Nop {}
 impl Instruction for Nop {
     fn execute<T: MemoryDevice>(
@@ -13,8 +14,8 @@ impl Instruction for Nop {
     ) -> super::InstructionEnum {
         cpu.load_instruction(memory)
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b00000000])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b00000000])
     }
 }
 
@@ -1,5 +1,5 @@
 /// The phases of an instruction with two phases
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum TwoPhases {
     /// First phase
     First,
@@ -7,8 +7,18 @@ pub enum TwoPhases {
     Second,
 }
 
+impl TwoPhases {
+    /// Get the index of the phase, starting at 0 for the first phase
+    pub fn index(&self) -> usize {
+        match self {
+            TwoPhases::First => 0,
+            TwoPhases::Second => 1,
+        }
+    }
+}
+
 /// The phases of an instruction with three phases
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ThreePhases {
     /// First phase
     First,
@@ -18,8 +28,19 @@ pub enum ThreePhases {
     Third,
 }
 
+impl ThreePhases {
+    /// Get the index of the phase, starting at 0 for the first phase
+    pub fn index(&self) -> usize {
+        match self {
+            ThreePhases::First => 0,
+            ThreePhases::Second => 1,
+            ThreePhases::Third => 2,
+        }
+    }
+}
+
 /// The phases of an instruction with four phases
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum FourPhases {
     /// First phase
     First,
@@ -31,8 +52,20 @@ pub enum FourPhases {
     Fourth,
 }
 
+impl FourPhases {
+    /// Get the index of the phase, starting at 0 for the first phase
+    pub fn index(&self) -> usize {
+        match self {
+            FourPhases::First => 0,
+            FourPhases::Second => 1,
+            FourPhases::Third => 2,
+            FourPhases::Fourth => 3,
+        }
+    }
+}
+
 /// The phases of an instruction with five phases
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum FivePhases {
     /// First phase
     First,
@@ -46,8 +79,21 @@ pub enum FivePhases {
     Fifth,
 }
 
+impl FivePhases {
+    /// Get the index of the phase, starting at 0 for the first phase
+    pub fn index(&self) -> usize {
+        match self {
+            FivePhases::First => 0,
+            FivePhases::Second => 1,
+            FivePhases::Third => 2,
+            FivePhases::Fourth => 3,
+            FivePhases::Fifth => 4,
+        }
+    }
+}
+
 /// The phases of an instruction with six phases
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SixPhases {
     /// First phase
     First,
@@ -62,3 +108,17 @@ pub enum SixPhases {
     /// Sixth phase
     Sixth,
 }
+
+impl SixPhases {
+    /// Get the index of the phase, starting at 0 for the first phase
+    pub fn index(&self) -> usize {
+        match self {
+            SixPhases::First => 0,
+            SixPhases::Second => 1,
+            SixPhases::Third => 2,
+            SixPhases::Fourth => 3,
+            SixPhases::Fifth => 4,
+            SixPhases::Sixth => 5,
+        }
+    }
+}
@@ -1,4 +1,7 @@
+use super::flag_effects::FlagEffects;
 use super::phases::ThreePhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::cpu::DoubleRegister;
 use crate::{cpu::Cpu, memory::MemoryDevice};
@@ -13,7 +16,7 @@ use crate::{cpu::Cpu, memory::MemoryDevice};
 #[doc(alias = "POP DE")]
 #[doc(alias = "POP HL")]
 #[doc(alias = "POP AF")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PopDoubleRegister {
     /// The destination double register.
     pub destination: DoubleRegister,
@@ -55,10 +58,22 @@ impl Instruction for PopDoubleRegister {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         let register_part = self.destination.numerical_id() << 4;
         let opcode = 0b11000001 | register_part;
-        Vec::from([opcode])
+        EncodedInstruction::from([opcode])
+    }
+    fn affects_flags(&self) -> FlagEffects {
+        match self.destination {
+            DoubleRegister::AF => FlagEffects::ALL,
+            _ => FlagEffects::NONE,
+        }
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new(),
+            RegisterSet::new().with_double(self.destination),
+        )
     }
 }
 
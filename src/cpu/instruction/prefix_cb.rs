@@ -1,3 +1,4 @@
+use super::EncodedInstruction;
 use super::{decode_cb, Instruction};
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
@@ -5,7 +6,7 @@ use crate::{cpu::Cpu, memory::MemoryDevice};
 ///
 /// Most bit operations have opcodes prefixed by `0xCB`.
 #[doc(alias = "CB")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PrefixCb {}
 impl Instruction for PrefixCb {
     fn execute<T: MemoryDevice>(
@@ -18,16 +19,16 @@ impl Instruction for PrefixCb {
         let instruction = decode_cb(opcode);
         return instruction;
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0xCB])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0xCB])
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::PrefixCb;
-    use crate::cpu::instruction::Instruction;
-    use crate::cpu::CpuState;
+    use crate::cpu::instruction::{Instruction, InstructionEnum};
+    use crate::cpu::{Cpu, CpuState, Register};
     use crate::memory::MemoryController;
 
     #[test]
@@ -39,4 +40,24 @@ mod tests {
 
         instruction.execute(&mut cpu, &mut memory);
     }
+
+    #[test]
+    fn prefixed_opcodes_are_executed_after_a_normal_fetch() {
+        let mut cpu = CpuState::new();
+        // SWAP A
+        let mut memory = MemoryController::new_with_init(&[0xCB, 0x37]);
+        cpu.write_register(Register::A, 0xAB);
+
+        let instruction = cpu.load_instruction(&mut memory);
+        assert!(matches!(instruction, InstructionEnum::PrefixCb(_)));
+        let instruction = instruction.execute(&mut cpu, &mut memory);
+        assert!(matches!(
+            instruction,
+            InstructionEnum::SwapNibblesRegister(_)
+        ));
+        instruction.execute(&mut cpu, &mut memory);
+
+        assert_eq!(cpu.read_register(Register::A), 0xBA);
+        assert_eq!(cpu.read_program_counter(), 0x0003);
+    }
 }
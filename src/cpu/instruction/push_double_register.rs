@@ -1,4 +1,6 @@
 use super::phases::FourPhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::cpu::DoubleRegister;
 use crate::{cpu::Cpu, memory::MemoryDevice};
@@ -13,7 +15,7 @@ use crate::{cpu::Cpu, memory::MemoryDevice};
 #[doc(alias = "PUSH DE")]
 #[doc(alias = "PUSH HL")]
 #[doc(alias = "PUSH AF")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PushDoubleRegister {
     /// The source double register.
     pub source: DoubleRegister,
@@ -63,10 +65,16 @@ impl Instruction for PushDoubleRegister {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         let register_part = self.source.numerical_id() << 4;
         let opcode = 0b11000101 | register_part;
-        Vec::from([opcode])
+        EncodedInstruction::from([opcode])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with_double(self.source),
+            RegisterSet::new(),
+        )
     }
 }
 
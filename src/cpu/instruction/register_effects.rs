@@ -0,0 +1,79 @@
+use crate::cpu::{DoubleRegister, DoubleRegisterOrStackPointer, Register};
+
+/// A set of 8 bit registers.
+///
+/// [Register::F] stands for the flags. The stack pointer and the program counter are not part of the set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RegisterSet {
+    /// One bit per register, indexed by the id of the register
+    registers: u8,
+}
+
+const ALL_REGISTERS: [Register; 8] = [
+    Register::B,
+    Register::C,
+    Register::D,
+    Register::E,
+    Register::H,
+    Register::L,
+    Register::F,
+    Register::A,
+];
+
+impl RegisterSet {
+    /// The set without any registers
+    pub const EMPTY: RegisterSet = RegisterSet { registers: 0 };
+
+    /// Create a new empty set
+    pub const fn new() -> RegisterSet {
+        RegisterSet::EMPTY
+    }
+
+    /// Get a copy of this set that also contains `register`
+    pub const fn with(self, register: Register) -> RegisterSet {
+        RegisterSet {
+            registers: self.registers | (1 << register as u8),
+        }
+    }
+
+    /// Get a copy of this set that also contains both halves of `double_register`
+    pub fn with_double(self, double_register: DoubleRegister) -> RegisterSet {
+        let combination = double_register.id();
+        self.with(combination.msb).with(combination.lsb)
+    }
+
+    /// Get a copy of this set that also contains both halves of `register`. The stack pointer does not add any registers.
+    pub fn with_double_or_stack_pointer(
+        self,
+        register: DoubleRegisterOrStackPointer,
+    ) -> RegisterSet {
+        match register.double_register() {
+            Some(double_register) => self.with_double(double_register),
+            None => self,
+        }
+    }
+
+    /// Get a set that contains all registers of both sets
+    pub const fn union(self, other: RegisterSet) -> RegisterSet {
+        RegisterSet {
+            registers: self.registers | other.registers,
+        }
+    }
+
+    /// Check if `register` is in the set
+    pub fn contains(&self, register: Register) -> bool {
+        self.registers & (1 << register as u8) != 0
+    }
+
+    /// Check if the set contains no registers
+    pub fn is_empty(&self) -> bool {
+        self.registers == 0
+    }
+
+    /// Iterate over the registers in the set
+    pub fn iter(&self) -> impl Iterator<Item = Register> + '_ {
+        ALL_REGISTERS
+            .into_iter()
+            .filter(|register| self.contains(*register))
+    }
+}
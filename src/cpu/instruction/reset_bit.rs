@@ -21,6 +21,9 @@ generate_instruction!(
     ),
     cb,
     0b10000000,
+    flags: [Unchanged, Unchanged, Unchanged, Unchanged],
+    reads_carry: false,
+    writes_result: true,
     cpu,
     memory,
     operand,
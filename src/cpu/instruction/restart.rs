@@ -1,4 +1,5 @@
 use super::phases::FourPhases;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{
     cpu::{Cpu, RestartAddress},
@@ -11,7 +12,7 @@ use crate::{
 ///
 /// The value pushed to the stack points to the next instruction directly after this one.
 #[doc(alias = "RST")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Restart {
     /// The immediate address. Will only valid after the second phase.
     pub address: RestartAddress,
@@ -63,11 +64,11 @@ impl Instruction for Restart {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         let base_code = 0b11000111;
         let address_code: u8 = Into::<u8>::into(self.address) << 3;
         let opcode = base_code | address_code;
-        Vec::from([opcode])
+        EncodedInstruction::from([opcode])
     }
 }
 
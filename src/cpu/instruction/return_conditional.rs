@@ -1,5 +1,8 @@
 use super::phases::FivePhases;
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
+use crate::cpu::Register;
 use crate::{
     cpu::{ConditionCode, Cpu},
     memory::MemoryDevice,
@@ -17,7 +20,7 @@ use crate::{
 #[doc(alias = "RET Z")]
 #[doc(alias = "RET NC")]
 #[doc(alias = "RET C")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ReturnConditional {
     /// The jump is only made if the condition is fullfilled in the third phase.
     pub condition: ConditionCode,
@@ -84,10 +87,13 @@ impl Instruction for ReturnConditional {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
+    fn encode(&self) -> EncodedInstruction {
         let condition_code_part = ((self.condition as u8) << 3) & 0b00011000;
         let opcode = 0b11000000 | condition_code_part;
-        Vec::from([opcode])
+        EncodedInstruction::from([opcode])
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (RegisterSet::new().with(Register::F), RegisterSet::new())
     }
 }
 
@@ -1,4 +1,5 @@
 use super::phases::FourPhases;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
@@ -6,9 +7,10 @@ use crate::{cpu::Cpu, memory::MemoryDevice};
 ///
 /// Sets IME to true.
 ///
-/// Basically this has the same effect as calling [EnableInterrupts](super::EnableInterrupts) and then [Return](super::Return)
+/// Basically this has the same effect as calling [EnableInterrupts](super::EnableInterrupts) and then [Return](super::Return).
+/// Unlike [EnableInterrupts](super::EnableInterrupts) the IME is enabled without a delay, so an interrupt that is already pending is serviced instead of the next instruction.
 #[doc(alias = "RETI")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ReturnFromInterrupt {
     /// The current phase of the instruction.
     pub phase: FourPhases,
@@ -58,8 +60,8 @@ impl Instruction for ReturnFromInterrupt {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b11011001])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b11011001])
     }
 }
 
@@ -67,8 +69,9 @@ impl Instruction for ReturnFromInterrupt {
 mod tests {
     use super::ReturnFromInterrupt;
     use crate::cpu::instruction::phases::FourPhases;
-    use crate::cpu::instruction::{Instruction, InstructionEnum};
-    use crate::cpu::{Cpu, CpuState};
+    use crate::cpu::instruction::{Instruction, InstructionEnum, InterruptServiceRoutine};
+    use crate::cpu::interrupt_controller::InterruptController;
+    use crate::cpu::{Cpu, CpuState, Interrupt};
     use crate::memory::MemoryController;
     use crate::memory::MemoryDevice;
 
@@ -102,4 +105,34 @@ mod tests {
         assert_eq!(memory.read(0x1234 - 2), 0x34);
         assert_eq!(memory.read(0x1234 - 1), 0x12);
     }
+
+    #[test]
+    fn pending_interrupt_is_serviced_right_after_reti() {
+        let mut cpu = CpuState::new();
+        let mut memory = MemoryController::new_for_tests();
+        cpu.write_stack_pointer(0x1234 - 2);
+        memory.write(0x1234 - 2, 0x00);
+        memory.write(0x1234 - 1, 0x20);
+        memory.write_interrupt_enable(Interrupt::VBlank, true);
+        memory.write_interrupt_flag(Interrupt::VBlank, true);
+
+        let mut instruction: InstructionEnum = ReturnFromInterrupt {
+            phase: FourPhases::First,
+        }
+        .into();
+        for _ in 0..4 {
+            instruction = instruction.execute(&mut cpu, &mut memory);
+        }
+
+        assert!(matches!(
+            instruction,
+            InstructionEnum::InterruptServiceRoutine(InterruptServiceRoutine {
+                address: 0x0040,
+                ..
+            })
+        ));
+        assert!(!memory.read_interrupt_flag(Interrupt::VBlank));
+        // The interrupt returns to the address RETI returned to
+        assert_eq!(cpu.read_program_counter(), 0x2000);
+    }
 }
@@ -1,4 +1,5 @@
 use super::phases::FourPhases;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
@@ -8,7 +9,7 @@ use crate::{cpu::Cpu, memory::MemoryDevice};
 ///
 /// Basically just [pops](super::PopDoubleRegister) a address from the stack and sets the program counter to it.
 #[doc(alias = "RET")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Return {
     /// The current phase of the instruction.
     pub phase: FourPhases,
@@ -54,8 +55,8 @@ impl Instruction for Return {
             }
         }
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b11001001])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b11001001])
     }
 }
 
@@ -1,3 +1,6 @@
+use super::flag_effects::{FlagEffect, FlagEffects};
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{
     cpu::{Cpu, Flag, Register},
@@ -20,7 +23,7 @@ use crate::{
 /// |--------------------|----------------------------|------------------------------|-------------------------------|
 /// | false              | false                      | false                        | set to the value of old bit 0 |
 #[doc(alias = "RRA")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RotateAccumulatorRightThroughCarry {}
 
 impl Instruction for RotateAccumulatorRightThroughCarry {
@@ -46,7 +49,21 @@ impl Instruction for RotateAccumulatorRightThroughCarry {
         cpu.write_register(Register::A, result);
         return cpu.load_instruction(memory);
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b00011111])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b00011111])
+    }
+    fn affects_flags(&self) -> FlagEffects {
+        FlagEffects::new(
+            FlagEffect::Reset,
+            FlagEffect::Reset,
+            FlagEffect::Reset,
+            FlagEffect::Modified,
+        )
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with(Register::A).with(Register::F),
+            RegisterSet::new().with(Register::A).with(Register::F),
+        )
     }
 }
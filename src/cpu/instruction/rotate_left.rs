@@ -25,6 +25,9 @@ generate_instruction!(
     ),
     cb,
     0b00000000,
+    flags: [Modified, Reset, Reset, Modified],
+    reads_carry: false,
+    writes_result: true,
     cpu,
     memory,
     operand,
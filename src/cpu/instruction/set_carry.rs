@@ -1,4 +1,8 @@
+use super::flag_effects::{FlagEffect, FlagEffects};
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
+use crate::cpu::Register;
 use crate::{
     cpu::{Cpu, Flag},
     memory::MemoryDevice,
@@ -27,7 +31,7 @@ use crate::{
 /// |---------------------|----------------------------|------------------------------|----------------------------|
 /// | unchanged           | false                      | false                        | true                       |
 #[doc(alias = "SCF")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SetCarry {}
 
 impl Instruction for SetCarry {
@@ -42,8 +46,19 @@ impl Instruction for SetCarry {
 
         return cpu.load_instruction(memory);
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b00110111])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b00110111])
+    }
+    fn affects_flags(&self) -> FlagEffects {
+        FlagEffects::new(
+            FlagEffect::Unchanged,
+            FlagEffect::Reset,
+            FlagEffect::Reset,
+            FlagEffect::Set,
+        )
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (RegisterSet::new(), RegisterSet::new().with(Register::F))
     }
 }
 
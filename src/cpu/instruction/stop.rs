@@ -1,12 +1,20 @@
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{cpu::Cpu, memory::MemoryDevice};
 
 /// Powers down the CPU and screen until a button is pressed.
 ///
-/// Our current implementation is basically identical to [Halt][super::Halt] but it uses [Cpu::get_pending_stop_wakeup()] instead of [Cpu::get_pending_interrupt()]
+/// STOP is two bytes long, the second byte is skipped. Entering the low-power state resets the timer divider.
+/// Similar to [Halt][super::Halt] it keeps returning itself, but it only wakes up when a [Interrupt::Joypad](crate::cpu::Interrupt::Joypad) is requested. See [Cpu::is_stop_wakeup_pending].
+/// After waking up the next instruction is loaded, which may be the interrupt service routine.
+///
+/// If a speed switch was prepared, STOP switches the speed of the CPU instead and execution continues with the next instruction. See [SpeedSwitch](crate::memory::speed_switch::SpeedSwitch).
 #[doc(alias = "STOP")]
-#[derive(Debug)]
-pub struct Stop {}
+#[derive(Debug, Clone)]
+pub struct Stop {
+    /// Whether the CPU is already stopped. This is false for the first cycle of the instruction.
+    pub stopped: bool,
+}
 
 impl Instruction for Stop {
     fn execute<T: MemoryDevice>(
@@ -14,14 +22,21 @@ impl Instruction for Stop {
         cpu: &mut crate::cpu::CpuState,
         memory: &mut T,
     ) -> super::InstructionEnum {
-        let interrupt = cpu.get_pending_stop_wakeup(memory);
-        match interrupt {
-            Some(instruction) => instruction,
-            None => (Self {}).into(),
+        if !self.stopped {
+            // Skip the second byte
+            cpu.advance_program_counter();
+            memory.reset_divider();
+            if memory.switch_speed() {
+                return cpu.load_instruction(memory);
+            }
+        }
+        if cpu.is_stop_wakeup_pending(memory) {
+            return cpu.load_instruction(memory);
         }
+        (Self { stopped: true }).into()
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b00010000])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b00010000, 0x00])
     }
 }
 
@@ -30,15 +45,17 @@ mod tests {
     use super::Stop;
     use crate::cpu::instruction::{Instruction, InstructionEnum};
     use crate::cpu::interrupt_controller::InterruptController;
-    use crate::cpu::{CpuState, Interrupt};
-    use crate::memory::MemoryController;
+    use crate::cpu::{Cpu, CpuState, Interrupt};
+    use crate::memory::memory_addresses::TIMER_DIVIDER_ADDRESS;
+    use crate::memory::{MemoryController, MemoryDevice};
 
     #[test]
     fn stop_works() {
         let mut cpu = CpuState::new();
         let mut memory = MemoryController::new_for_tests();
+        cpu.write_interrupt_master_enable(true);
 
-        let instruction = Stop {};
+        let instruction = Stop { stopped: false };
 
         let instruction = instruction.execute(&mut cpu, &mut memory);
         let instruction = instruction.execute(&mut cpu, &mut memory);
@@ -47,7 +64,10 @@ mod tests {
         let instruction = instruction.execute(&mut cpu, &mut memory);
         let instruction = instruction.execute(&mut cpu, &mut memory);
 
-        assert!(matches!(instruction, InstructionEnum::Stop(Stop {})));
+        assert!(matches!(
+            instruction,
+            InstructionEnum::Stop(Stop { stopped: true })
+        ));
 
         memory.write_interrupt_enable(Interrupt::Joypad, true);
         memory.write_interrupt_flag(Interrupt::Joypad, true);
@@ -59,4 +79,41 @@ mod tests {
             InstructionEnum::InterruptServiceRoutine(_)
         ));
     }
+
+    #[test]
+    fn stop_skips_the_second_byte_and_resets_the_divider() {
+        let mut cpu = CpuState::new_at(0x0101);
+        let mut memory = MemoryController::new_for_tests();
+        for _ in 0..64 * 3 {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(TIMER_DIVIDER_ADDRESS as u16), 3);
+
+        memory.enable_io_log();
+        Stop { stopped: false }.execute(&mut cpu, &mut memory);
+        assert_eq!(cpu.read_program_counter(), 0x0102);
+        assert_eq!(memory.read(TIMER_DIVIDER_ADDRESS as u16), 0);
+        // The program did not write to the divider
+        assert!(memory.io_log().is_empty());
+    }
+
+    #[test]
+    fn only_the_joypad_interrupt_ends_stop() {
+        let mut cpu = CpuState::new();
+        let mut memory = MemoryController::new_for_tests();
+        memory.write_interrupt_enable(Interrupt::Timer, true);
+        memory.write_interrupt_flag(Interrupt::Timer, true);
+
+        let mut instruction: InstructionEnum = Stop { stopped: false }.into();
+        for _ in 0..10 {
+            instruction = instruction.execute(&mut cpu, &mut memory);
+            assert!(matches!(instruction, InstructionEnum::Stop(_)));
+        }
+
+        // The joypad interrupt wakes the CPU even if it is not enabled
+        memory.write_interrupt_flag(Interrupt::Joypad, true);
+        let instruction = instruction.execute(&mut cpu, &mut memory);
+        assert!(matches!(instruction, InstructionEnum::Nop(_)));
+        assert_eq!(cpu.read_program_counter(), 0x0002);
+    }
 }
@@ -16,6 +16,9 @@ generate_instruction!(
         SubtractImmediate
     ),
     0b10010000,
+    flags: [Modified, Set, Modified, Modified],
+    reads_carry: false,
+    writes_result: true,
     cpu,
     memory,
     operand,
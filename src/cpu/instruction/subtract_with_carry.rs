@@ -18,6 +18,9 @@ generate_instruction!(
         SubtractWithCarryImmediate
     ),
     0b10011000,
+    flags: [Modified, Set, Modified, Modified],
+    reads_carry: true,
+    writes_result: true,
     cpu,
     memory,
     operand,
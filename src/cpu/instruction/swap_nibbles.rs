@@ -25,6 +25,9 @@ generate_instruction!(
     ),
     cb,
     0b00110000,
+    flags: [Modified, Reset, Reset, Reset],
+    reads_carry: false,
+    writes_result: true,
     cpu,
     memory,
     operand,
@@ -1,3 +1,6 @@
+use super::flag_effects::{FlagEffect, FlagEffects};
+use super::register_effects::RegisterSet;
+use super::EncodedInstruction;
 use super::Instruction;
 use crate::{
     cpu::{Cpu, Flag, Register},
@@ -14,7 +17,7 @@ use crate::{
 /// |---------------------|----------------------------|------------------------------|----------------------------|
 /// | true if result is 0 | unchanged                  | false                        | true if a carry occurred   |
 #[doc(alias = "DAA")]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ToBinaryCodedDecimal {}
 
 impl Instruction for ToBinaryCodedDecimal {
@@ -51,8 +54,22 @@ impl Instruction for ToBinaryCodedDecimal {
 
         return cpu.load_instruction(memory);
     }
-    fn encode(&self) -> Vec<u8> {
-        Vec::from([0b00100111])
+    fn encode(&self) -> EncodedInstruction {
+        EncodedInstruction::from([0b00100111])
+    }
+    fn affects_flags(&self) -> FlagEffects {
+        FlagEffects::new(
+            FlagEffect::Modified,
+            FlagEffect::Unchanged,
+            FlagEffect::Reset,
+            FlagEffect::Modified,
+        )
+    }
+    fn register_effects(&self) -> (RegisterSet, RegisterSet) {
+        (
+            RegisterSet::new().with(Register::A).with(Register::F),
+            RegisterSet::new().with(Register::A).with(Register::F),
+        )
     }
 }
 
@@ -0,0 +1,568 @@
+use self::{machine_state::MachineState, rewind_buffer::RewindBuffer};
+use crate::{
+    cpu::{
+        instruction::{Instruction, InstructionEnum, Nop},
+        Cpu, CpuState, Register,
+    },
+    memory::{
+        cartridge::{cgb_flag::CgbFlag, Cartridge},
+        io_log::IoWrite,
+        joypad::Button,
+        memory_addresses::{
+            BACKGROUND_PALETTE_ADDRESS, LCD_CONTROL_ADDRESS, TIMER_DIVIDER_ADDRESS,
+        },
+        serial::serial_connection::SerialConnection,
+        video::display_connection::DisplayConnection,
+        MemoryController, MemoryDevice,
+    },
+};
+
+/// Contains snapshots of the whole emulator state
+pub mod machine_state;
+
+/// Contains a ring buffer of snapshots for rewinding
+pub mod rewind_buffer;
+
+/// The number of machine cycles it takes the PPU to draw one frame. 154 lines with 114 cycles each.
+pub const CYCLES_PER_FRAME: u64 = 154 * 114;
+
+/// Errors that can occur when importing a save with [Emulator::import_save]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImportSaveError {
+    /// The cartridge has no battery backed RAM, so there is nothing to restore
+    NoBattery,
+    /// The save does not have the same size as the RAM of the cartridge
+    WrongSize {
+        /// The size of the cartridge RAM
+        expected: usize,
+        /// The size of the save
+        actual: usize,
+    },
+}
+
+/// Ties a [CpuState] and a [MemoryController] together and drives them in lockstep.
+///
+/// Each call to [Instruction::execute] takes one machine cycle, after which all peripherals are advanced by one cycle as well.
+pub struct Emulator<T: SerialConnection, D: DisplayConnection> {
+    /// The state of the processor
+    pub cpu: CpuState,
+    /// The memory and all peripherals
+    pub memory: MemoryController<T, D>,
+    /// The instruction that will be executed in the next cycle
+    instruction: InstructionEnum,
+    /// The number of machine cycles executed since the emulator was created
+    cycles: u64,
+    /// Snapshots of the last frames. `None` if rewinding is not enabled.
+    rewind_buffer: Option<RewindBuffer>,
+}
+
+impl<T: SerialConnection, D: DisplayConnection> Emulator<T, D> {
+    /// Create a new emulator. The first instruction is fetched from the current program counter of `cpu`.
+    pub fn new(mut cpu: CpuState, mut memory: MemoryController<T, D>) -> Self {
+        let instruction = cpu.load_instruction(&mut memory);
+        Self {
+            cpu,
+            memory,
+            instruction,
+            cycles: 0,
+            rewind_buffer: None,
+        }
+    }
+
+    /// Create an emulator that runs `cartridge`.
+    ///
+    /// The boot ROM is skipped, so the CPU starts at `0x0100` with the registers set up like the boot ROM would leave them. See [CpuState::new_post_boot].
+    /// The LCD is enabled and the background palette is set like the boot ROM would leave them as well.
+    ///
+    /// Cartridges that only work on the CGB run in CGB mode, see [MemoryController::set_cgb_mode].
+    /// Games that work on both are run as on the original gameboy, because most of the CGB features are not emulated yet.
+    pub fn from_cartridge(
+        cartridge: Cartridge,
+        serial_connection: Option<T>,
+        display_connection: D,
+    ) -> Self {
+        let mut memory =
+            MemoryController::new_with_video_connections(serial_connection, display_connection);
+        let mut cpu = CpuState::new_post_boot();
+        if cartridge.cgb_flag == CgbFlag::CgbOnly {
+            memory.set_cgb_mode(true);
+            // Games check for 0x11 in A to detect the CGB
+            cpu.write_register(Register::A, 0x11);
+        }
+        cartridge.place_into_memory(&mut memory.memory);
+        memory.cartridge = cartridge;
+        // The boot ROM leaves the LCD enabled with the background palette set
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0x91);
+        memory.write(BACKGROUND_PALETTE_ADDRESS as u16, 0xFC);
+        Self::new(cpu, memory)
+    }
+
+    /// Run one complete instruction. Returns the number of machine cycles it took.
+    ///
+    /// [Halt](crate::cpu::instruction::Halt), [Stop](crate::cpu::instruction::Stop) and [HaltAndCatchFire](crate::cpu::instruction::HaltAndCatchFire) return after a single cycle, because they keep running until an interrupt happens.
+    pub fn step(&mut self) -> u64 {
+        let start_cycles = self.cycles;
+        let loaded_instructions = self.cpu.loaded_instructions();
+        let waiting = matches!(
+            self.instruction,
+            InstructionEnum::Halt(_)
+                | InstructionEnum::Stop(_)
+                | InstructionEnum::HaltAndCatchFire(_)
+        );
+        loop {
+            self.step_cycle();
+            if waiting || self.cpu.loaded_instructions() != loaded_instructions {
+                break;
+            }
+        }
+        self.cycles - start_cycles
+    }
+
+    /// Run exactly one machine cycle
+    pub fn step_cycle(&mut self) {
+        let instruction = std::mem::replace(&mut self.instruction, InstructionEnum::Nop(Nop {}));
+        self.instruction = instruction.execute(&mut self.cpu, &mut self.memory);
+        self.cpu.count_machine_cycle();
+        self.memory.process_cycle();
+        self.cycles += 1;
+        if self.cycles % CYCLES_PER_FRAME == 0 {
+            self.record_rewind_snapshot();
+        }
+    }
+
+    /// Run exactly `cycles` machine cycles.
+    ///
+    /// Instructions that take multiple cycles are paused between two of their phases, so this can stop in the middle of an instruction.
+    /// The next call continues where this one stopped. This is useful for audio callbacks that need to advance the emulator by the duration of a fixed number of samples.
+    pub fn run_cycles(&mut self, cycles: u64) {
+        for _ in 0..cycles {
+            self.step_cycle();
+        }
+    }
+
+    /// Run until the PPU finished the current frame. Returns the number of machine cycles that were executed.
+    ///
+    /// A frame is finished when the PPU enters VBlank, so after this the complete frame was passed to the display connection.
+    /// If the LCD is off or the PPU is paused, no frame is finished, so this stops after [CYCLES_PER_FRAME] cycles instead.
+    pub fn run_frame(&mut self) -> u64 {
+        let start_cycles = self.cycles;
+        let frame = self.memory.graphics.finished_frames();
+        while self.memory.graphics.finished_frames() == frame {
+            self.step_cycle();
+            let graphics = &self.memory.graphics;
+            let ppu_running = graphics.current_lcd_control.lcd_ppu_enable && !graphics.is_paused();
+            if !ppu_running && self.cycles - start_cycles >= CYCLES_PER_FRAME {
+                break;
+            }
+        }
+        self.cycles - start_cycles
+    }
+
+    /// Get the number of machine cycles executed since the emulator was created
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Capture the complete state of the emulator. See [MachineState].
+    pub fn save_state(&self) -> MachineState {
+        MachineState::capture(self)
+    }
+
+    /// Restore a state captured with [Emulator::save_state]
+    pub fn load_state(&mut self, state: &MachineState) {
+        state.restore(self);
+    }
+
+    /// Start keeping a snapshot of each of the last `frames` frames, so the emulator can be rewound with [Emulator::rewind].
+    ///
+    /// A snapshot is taken right away and then every [CYCLES_PER_FRAME] cycles. Any previously recorded snapshots are discarded.
+    pub fn enable_rewind(&mut self, frames: usize) {
+        self.rewind_buffer = Some(RewindBuffer::new(frames));
+        self.record_rewind_snapshot();
+    }
+
+    /// Stop recording snapshots and drop all recorded ones
+    pub fn disable_rewind(&mut self) {
+        self.rewind_buffer = None;
+    }
+
+    /// Push the current state into the rewind buffer, if rewinding is enabled
+    fn record_rewind_snapshot(&mut self) {
+        if let Some(mut rewind_buffer) = self.rewind_buffer.take() {
+            rewind_buffer.push(self.save_state());
+            self.rewind_buffer = Some(rewind_buffer);
+        }
+    }
+
+    /// Go back `frames` frames.
+    ///
+    /// Restores the snapshot taken `frames` snapshots before the newest one. With `0` this returns to the start of the current frame.
+    /// Returns `false` and leaves the emulator unchanged if rewinding is not enabled or not enough frames were recorded.
+    pub fn rewind(&mut self, frames: u32) -> bool {
+        let Some(mut rewind_buffer) = self.rewind_buffer.take() else {
+            return false;
+        };
+        let restored = match rewind_buffer.rewind(frames as usize) {
+            Some(state) => {
+                state.restore(self);
+                true
+            }
+            None => false,
+        };
+        self.rewind_buffer = Some(rewind_buffer);
+        restored
+    }
+
+    /// Start recording all writes to IO registers. See [Emulator::io_log].
+    pub fn enable_io_log(&mut self) {
+        self.memory.enable_io_log();
+    }
+
+    /// Get all writes to IO registers since [Emulator::enable_io_log] was called.
+    ///
+    /// Each write is stamped with the cycle it happened in. This shows exactly how a game configured the hardware.
+    pub fn io_log(&self) -> &[IoWrite] {
+        self.memory.io_log()
+    }
+
+    /// Press or release a button on the joypad
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        self.memory
+            .joypad
+            .set_button(&mut self.memory.memory, button, pressed);
+    }
+
+    /// Get the current value of the timer divider.
+    ///
+    /// This is the same value a ROM would read from `0xFF04`. Many games use it as a source of randomness, so this shows which seed a game would pick right now.
+    pub fn div(&self) -> u8 {
+        self.memory.read(TIMER_DIVIDER_ADDRESS as u16)
+    }
+
+    /// The number of bytes of battery backed RAM that are part of a save. `0` if the cartridge has no battery.
+    fn save_size(&self) -> usize {
+        let cartridge = &self.memory.cartridge;
+        if !cartridge.cartridge_type.has_battery() {
+            return 0;
+        }
+        cartridge.saved_ram_size()
+    }
+
+    /// Get the battery backed RAM of the cartridge, as it would be stored in a save file.
+    ///
+    /// Returns `None` if the cartridge has no battery backed RAM.
+    /// Unlike a full savestate this only contains the data the game itself would find after turning the gameboy off and on again.
+    pub fn export_save(&self) -> Option<Vec<u8>> {
+        if self.save_size() == 0 {
+            return None;
+        }
+        Some(self.memory.cartridge.dump_ram())
+    }
+
+    /// Restore the battery backed RAM of the cartridge from a save created by [Emulator::export_save].
+    pub fn import_save(&mut self, save: &[u8]) -> Result<(), ImportSaveError> {
+        let size = self.save_size();
+        if size == 0 {
+            return Err(ImportSaveError::NoBattery);
+        }
+        self.memory
+            .cartridge
+            .load_ram(save)
+            .map_err(|_| ImportSaveError::WrongSize {
+                expected: size,
+                actual: save.len(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Emulator, ImportSaveError, CYCLES_PER_FRAME};
+    use crate::cpu::{Cpu, CpuState, DoubleRegister, Reg16, Register};
+    use crate::memory::cartridge::Cartridge;
+    use crate::memory::io_log::IoWrite;
+    use crate::memory::memory_addresses::{
+        CARTRIDGE_TYPE_ADDRESS, LCD_CONTROL_ADDRESS, RAM_SIZE_ADDRESS, SPEED_SWITCH_ADDRESS,
+    };
+    use crate::memory::serial::serial_connection::LoggerSerialConnection;
+    use crate::memory::video::display_connection::DummyDisplayConnection;
+    use crate::memory::{MemoryController, MemoryDevice};
+
+    #[test]
+    fn run_cycles_advances_the_cycle_counter_exactly() {
+        // LD BC,d16 takes three cycles, so 100 cycles will end in the middle of an instruction
+        let program = [0x01, 0x00, 0x00].repeat(50);
+        let mut emulator =
+            Emulator::new(CpuState::new(), MemoryController::new_with_init(&program));
+
+        emulator.run_cycles(100);
+        assert_eq!(emulator.cycles(), 100);
+
+        emulator.run_cycles(44);
+        assert_eq!(emulator.cycles(), 144);
+        assert_eq!(emulator.cpu.read_t_cycles(), 144 * 4);
+        // 144 cycles are 48 instructions, the opcode of the 49th instruction is already fetched
+        assert_eq!(emulator.cpu.read_program_counter(), 48 * 3 + 1);
+    }
+
+    #[test]
+    fn bundled_rom_runs_for_a_few_frames() {
+        let mut emulator = Emulator::from_cartridge(
+            Cartridge::new(),
+            None::<LoggerSerialConnection>,
+            DummyDisplayConnection {},
+        );
+        assert_eq!(emulator.cpu.read_program_counter(), 0x0101);
+
+        for _ in 0..3 {
+            emulator.run_frame();
+        }
+        assert!(emulator.memory.graphics.finished_frames() >= 2);
+    }
+
+    #[test]
+    fn cgb_only_cartridges_run_in_cgb_mode() {
+        let emulator = Emulator::from_cartridge(
+            Cartridge::new(),
+            None::<LoggerSerialConnection>,
+            DummyDisplayConnection {},
+        );
+        assert!(!emulator.memory.is_cgb_mode());
+        assert_eq!(emulator.cpu.read_register(Register::A), 0x01);
+
+        let cartridge =
+            Cartridge::load("test_roms/blargg/interrupt_time/interrupt_time.gb").unwrap();
+        let mut emulator = Emulator::from_cartridge(
+            cartridge,
+            None::<LoggerSerialConnection>,
+            DummyDisplayConnection {},
+        );
+        assert!(emulator.memory.is_cgb_mode());
+        assert!(emulator.memory.graphics.is_cgb_mode());
+        assert_eq!(emulator.cpu.read_register(Register::A), 0x11);
+        assert_eq!(emulator.memory.read(SPEED_SWITCH_ADDRESS as u16), 0x7E);
+
+        let state = emulator.save_state();
+        emulator.memory.set_cgb_mode(false);
+        emulator.load_state(&state);
+        assert!(emulator.memory.is_cgb_mode());
+    }
+
+    #[test]
+    fn step_executes_one_instruction() {
+        // NOP; LD BC,0x1234; NOP
+        let program = [0x00, 0x01, 0x34, 0x12, 0x00];
+        let mut emulator =
+            Emulator::new(CpuState::new(), MemoryController::new_with_init(&program));
+
+        assert_eq!(emulator.step(), 1);
+        assert_eq!(emulator.step(), 3);
+        assert_eq!(emulator.cycles(), 4);
+        assert_eq!(
+            emulator.cpu.read_double_register(DoubleRegister::BC),
+            0x1234
+        );
+    }
+
+    #[test]
+    fn run_frame_stops_when_the_frame_is_finished() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+        let mut emulator = Emulator::new(CpuState::new(), memory);
+
+        let cycles = emulator.run_frame();
+        assert!(cycles <= CYCLES_PER_FRAME);
+        assert_eq!(emulator.memory.graphics.finished_frames(), 1);
+        // The PPU just entered VBlank
+        assert_eq!(emulator.memory.graphics.current_line, 144);
+        assert_eq!(emulator.memory.graphics.cycles_on_current_line, 1);
+
+        // A whole frame passes between two VBlanks
+        assert_eq!(emulator.run_frame(), CYCLES_PER_FRAME);
+        assert_eq!(emulator.memory.graphics.current_line, 144);
+        assert_eq!(emulator.memory.graphics.cycles_on_current_line, 1);
+    }
+
+    #[test]
+    fn run_frame_stops_after_a_frame_worth_of_cycles_if_the_lcd_is_off() {
+        let mut emulator = Emulator::new(CpuState::new(), MemoryController::new_for_tests());
+        assert_eq!(emulator.run_frame(), CYCLES_PER_FRAME);
+        assert_eq!(emulator.memory.graphics.finished_frames(), 0);
+    }
+
+    #[test]
+    fn div_matches_the_divider_register() {
+        let mut emulator = Emulator::new(CpuState::new(), MemoryController::new_for_tests());
+        assert_eq!(emulator.div(), 0);
+
+        emulator.run_cycles(64 * 5);
+        assert_eq!(emulator.div(), 5);
+        assert_eq!(emulator.div(), emulator.memory.read(0xFF04));
+
+        emulator.run_cycles(64 * 300);
+        assert_eq!(emulator.div(), 49);
+        assert_eq!(emulator.div(), emulator.memory.read(0xFF04));
+    }
+
+    #[test]
+    fn exported_save_can_be_restored() {
+        let mut rom = std::fs::read("test_roms/blargg/cpu_instrs/individual/06-ld r,r.gb").unwrap();
+        rom[CARTRIDGE_TYPE_ADDRESS] = 0x03;
+        rom[RAM_SIZE_ADDRESS] = 0x02;
+        let mut memory = MemoryController::new();
+        memory.cartridge = Cartridge::from_bytes(rom).unwrap();
+        assert!(memory.cartridge.cartridge_type.has_battery());
+        let mut emulator = Emulator::new(CpuState::new(), memory);
+
+        // Enable RAM and write some data
+        emulator.memory.write(0x0000, 0x0A);
+        emulator.memory.write(0xA000, 0x12);
+        emulator.memory.write(0xBFFF, 0x34);
+
+        let save = emulator
+            .export_save()
+            .expect("Cartridge has battery backed RAM");
+        assert_eq!(save.len(), 0x2000);
+
+        emulator.memory.write(0xA000, 0x56);
+        emulator.memory.write(0xBFFF, 0x78);
+        assert_eq!(emulator.memory.read(0xA000), 0x56);
+
+        emulator.import_save(&save).unwrap();
+        assert_eq!(emulator.memory.read(0xA000), 0x12);
+        assert_eq!(emulator.memory.read(0xBFFF), 0x34);
+        assert_eq!(
+            emulator.import_save(&save[1..]),
+            Err(ImportSaveError::WrongSize {
+                expected: 0x2000,
+                actual: 0x1FFF
+            })
+        );
+    }
+
+    #[test]
+    fn cartridge_without_battery_has_no_save() {
+        let mut emulator = Emulator::new(CpuState::new(), MemoryController::new());
+        assert_eq!(emulator.export_save(), None);
+        assert_eq!(
+            emulator.import_save(&[0; 0x2000]),
+            Err(ImportSaveError::NoBattery)
+        );
+    }
+
+    #[test]
+    fn rewind_restores_an_earlier_frame() {
+        // LD HL,0xC000; loop: INC A; LD (HL+),A; JR loop
+        let program = [0x21, 0x00, 0xC0, 0x3C, 0x22, 0x18, 0xFC];
+        let mut emulator =
+            Emulator::new(CpuState::new(), MemoryController::new_with_init(&program));
+        emulator.enable_rewind(10);
+
+        emulator.run_cycles(CYCLES_PER_FRAME * 2);
+        let snapshot = emulator.save_state();
+        emulator.run_cycles(CYCLES_PER_FRAME * 2);
+        assert_ne!(
+            emulator.cpu.read_register16(Reg16::HL),
+            snapshot.cpu().read_register16(Reg16::HL)
+        );
+
+        assert!(emulator.rewind(2));
+        assert_eq!(emulator.cycles(), snapshot.cycles());
+        for register in [
+            Reg16::BC,
+            Reg16::DE,
+            Reg16::HL,
+            Reg16::AF,
+            Reg16::SP,
+            Reg16::PC,
+        ] {
+            assert_eq!(
+                emulator.cpu.read_register16(register),
+                snapshot.cpu().read_register16(register)
+            );
+        }
+        assert!(emulator.memory.memory.data == snapshot.memory().data);
+
+        // Continuing from the restored state behaves exactly like the first time
+        emulator.run_cycles(CYCLES_PER_FRAME);
+        let mut replay = Emulator::new(CpuState::new(), MemoryController::new_with_init(&program));
+        replay.run_cycles(CYCLES_PER_FRAME * 3);
+        assert_eq!(
+            emulator.cpu.read_register16(Reg16::HL),
+            replay.cpu.read_register16(Reg16::HL)
+        );
+    }
+
+    #[test]
+    fn restored_state_continues_like_the_original() {
+        // LD A,0x91; LDH (0x40),A; loop: INC A; LDH (0x47),A; JR loop
+        let program = [0x3E, 0x91, 0xE0, 0x40, 0x3C, 0xE0, 0x47, 0x18, 0xFB];
+        let mut emulator =
+            Emulator::new(CpuState::new(), MemoryController::new_with_init(&program));
+        emulator.run_cycles(CYCLES_PER_FRAME * 2 + 100);
+        let state = emulator.save_state();
+        emulator.enable_io_log();
+        emulator.run_cycles(CYCLES_PER_FRAME);
+
+        let mut restored =
+            Emulator::new(CpuState::new(), MemoryController::new_with_init(&program));
+        restored.load_state(&state);
+        restored.enable_io_log();
+        restored.run_cycles(CYCLES_PER_FRAME);
+
+        // The log is stamped with the cycles of the memory, so they have to be restored as well
+        assert_eq!(restored.io_log(), emulator.io_log());
+        assert_eq!(
+            restored.memory.graphics.finished_frames(),
+            emulator.memory.graphics.finished_frames()
+        );
+        assert_eq!(restored.cycles(), emulator.cycles());
+        assert!(restored.memory.memory.data == emulator.memory.memory.data);
+    }
+
+    #[test]
+    fn rewind_fails_without_enough_frames() {
+        let mut emulator = Emulator::new(CpuState::new(), MemoryController::new_for_tests());
+        assert!(!emulator.rewind(0));
+
+        emulator.enable_rewind(3);
+        emulator.run_cycles(CYCLES_PER_FRAME * 5);
+        assert!(!emulator.rewind(3));
+        assert_eq!(emulator.cycles(), CYCLES_PER_FRAME * 5);
+        assert!(emulator.rewind(2));
+        assert_eq!(emulator.cycles(), CYCLES_PER_FRAME * 3);
+    }
+
+    #[test]
+    fn io_log_records_register_writes() {
+        // LD A,0x91; LDH (0x40),A; LD A,0xE4; LDH (0x47),A; LD (0xC000),A
+        let program = [
+            0x3E, 0x91, 0xE0, 0x40, 0x3E, 0xE4, 0xE0, 0x47, 0xEA, 0x00, 0xC0,
+        ];
+        let mut emulator =
+            Emulator::new(CpuState::new(), MemoryController::new_with_init(&program));
+        emulator.run_cycles(20);
+        assert!(emulator.io_log().is_empty());
+
+        let mut emulator =
+            Emulator::new(CpuState::new(), MemoryController::new_with_init(&program));
+        emulator.enable_io_log();
+        emulator.run_cycles(20);
+
+        assert_eq!(
+            emulator.io_log(),
+            [
+                IoWrite {
+                    cycle: 3,
+                    address: 0xFF40,
+                    value: 0x91
+                },
+                IoWrite {
+                    cycle: 8,
+                    address: 0xFF47,
+                    value: 0xE4
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{
+    cpu::{instruction::InstructionEnum, CpuState},
+    memory::{
+        cartridge::CartridgeState, joypad::Joypad, serial::serial_connection::SerialConnection,
+        serial::SerialState, speed_switch::SpeedSwitch, timer::Timer,
+        video::display_connection::DisplayConnection, video::VideoState, Memory,
+    },
+};
+
+use super::Emulator;
+
+/// A snapshot of everything that is needed to continue emulation from a certain point.
+///
+/// The connections and the cartridge ROM are not part of the snapshot, so a state can only be restored into an emulator running the same game.
+#[derive(Clone)]
+pub struct MachineState {
+    cpu: CpuState,
+    instruction: InstructionEnum,
+    memory: Box<Memory>,
+    timer: Timer,
+    joypad: Joypad,
+    speed_switch: SpeedSwitch,
+    serial: SerialState,
+    cartridge: CartridgeState,
+    video: VideoState,
+    cgb_mode: bool,
+    memory_cycles: u64,
+    cycles: u64,
+}
+
+impl MachineState {
+    /// Capture the current state of `emulator`
+    pub fn capture<T: SerialConnection, D: DisplayConnection>(emulator: &Emulator<T, D>) -> Self {
+        Self {
+            cpu: emulator.cpu.clone(),
+            instruction: emulator.instruction.clone(),
+            memory: Box::new(emulator.memory.memory.clone()),
+            timer: emulator.memory.timer.clone(),
+            joypad: emulator.memory.joypad.clone(),
+            speed_switch: emulator.memory.speed_switch.clone(),
+            serial: emulator.memory.serial.save_state(),
+            cartridge: emulator.memory.cartridge.save_state(),
+            video: emulator.memory.graphics.save_state(),
+            cgb_mode: emulator.memory.is_cgb_mode(),
+            memory_cycles: emulator.memory.cycles,
+            cycles: emulator.cycles,
+        }
+    }
+
+    /// Restore this state into `emulator`
+    pub fn restore<T: SerialConnection, D: DisplayConnection>(
+        &self,
+        emulator: &mut Emulator<T, D>,
+    ) {
+        emulator.cpu = self.cpu.clone();
+        emulator.instruction = self.instruction.clone();
+        emulator.memory.memory = (*self.memory).clone();
+        emulator.memory.timer = self.timer.clone();
+        emulator.memory.joypad = self.joypad.clone();
+        emulator.memory.speed_switch = self.speed_switch.clone();
+        emulator.memory.serial.load_state(&self.serial);
+        emulator.memory.cartridge.load_state(&self.cartridge);
+        emulator.memory.graphics.load_state(&self.video);
+        emulator.memory.set_cgb_mode(self.cgb_mode);
+        emulator.memory.cycles = self.memory_cycles;
+        emulator.cycles = self.cycles;
+    }
+
+    /// The state of the processor when the snapshot was taken
+    pub fn cpu(&self) -> &CpuState {
+        &self.cpu
+    }
+
+    /// The memory when the snapshot was taken
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    /// The number of machine cycles the emulator had executed when the snapshot was taken
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+}
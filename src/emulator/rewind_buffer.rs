@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+
+use super::machine_state::MachineState;
+
+/// A ring buffer of [MachineState]s, one per frame.
+///
+/// When the buffer is full, the oldest snapshot is dropped to make room for the new one.
+pub struct RewindBuffer {
+    snapshots: VecDeque<MachineState>,
+    capacity: usize,
+}
+
+impl RewindBuffer {
+    /// Create an empty buffer that keeps at most `capacity` snapshots
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// The maximum number of snapshots in the buffer
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of snapshots currently in the buffer
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Check if the buffer contains no snapshots
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Add a snapshot as the newest entry
+    pub fn push(&mut self, state: MachineState) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(state);
+    }
+
+    /// Go back `frames` snapshots from the newest one.
+    ///
+    /// All snapshots newer than the returned one are dropped. Returns `None` and keeps the buffer unchanged if there are not enough snapshots.
+    pub fn rewind(&mut self, frames: usize) -> Option<&MachineState> {
+        if frames >= self.snapshots.len() {
+            return None;
+        }
+        let remaining = self.snapshots.len() - frames;
+        self.snapshots.truncate(remaining);
+        self.snapshots.back()
+    }
+}
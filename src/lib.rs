@@ -8,4 +8,7 @@ pub mod cpu;
 /// Contains the [memory::MemoryDevice] trait.
 pub mod memory;
 
+/// Contains [emulator::Emulator], which runs the [cpu] and the [memory] together.
+pub mod emulator;
+
 mod test_roms;
@@ -1,3 +1,5 @@
+use std::{collections::HashSet, ops::RangeInclusive};
+
 use arr_macro::arr;
 
 /// Contains named memory addresses as constants
@@ -12,6 +14,15 @@ pub mod cartridge;
 /// Contains the serial connection
 pub mod serial;
 
+/// Contains the joypad
+pub mod joypad;
+
+/// Contains the CGB speed switch
+pub mod speed_switch;
+
+/// Contains a log of writes to IO registers
+pub mod io_log;
+
 /// Contains the GPU and video memory
 pub mod video;
 
@@ -19,18 +30,27 @@ use timer::Timer;
 
 use self::{
     cartridge::Cartridge,
-    memory_addresses::ALWAYS_RETURNS_FF_ADDRESS,
+    io_log::{is_io_register, IoWrite},
+    joypad::Joypad,
+    memory_addresses::{
+        ECHO_RAM_AREA, IO_REGISTER_AREA, JOYPAD_ADDRESS, MAPPED_IO_GAP_ADDRESSES,
+        OBJECT_ATTRIBUTE_MEMORY_AREA, SPEED_SWITCH_ADDRESS, TILE_DATA_AREA, TIMER_DIVIDER_ADDRESS,
+        UNMAPPED_IO_AREA, VIDEO_RAM_AREA, VIDEO_RAM_BANK_ADDRESS, WORK_RAM_AREA,
+    },
     serial::{
         serial_connection::{LoggerSerialConnection, SerialConnection},
         Serial,
     },
+    speed_switch::SpeedSwitch,
     video::{
         display_connection::{DisplayConnection, DummyDisplayConnection},
-        Video,
+        lcd_status::PpuMode,
+        ObjectAttributeMemoryTransfer, Video,
     },
 };
 
 /// The backing memory that will be used for all reads
+#[derive(Clone)]
 pub struct Memory {
     /// The raw memory array
     pub data: [u8; 65536],
@@ -43,6 +63,21 @@ impl Memory {
             data: arr![0; 65536],
         }
     }
+
+    /// Copy the raw bytes in `range`. Unlike [MemoryDevice::dump] this does not go through any peripherals.
+    pub fn dump(&self, range: RangeInclusive<u16>) -> Vec<u8> {
+        self.data[*range.start() as usize..=*range.end() as usize].to_vec()
+    }
+
+    /// Copy `bytes` into the raw memory, starting at `start`. Bytes that would be placed after `0xFFFF` are dropped.
+    ///
+    /// Like [Memory::dump] this does not go through any peripherals, so it can also place data in ROM. Useful for loading a program to `0x0100` in tests.
+    /// Use [MemoryController::write_slice] to write tile data, so the tiles cached by the PPU are decoded again.
+    pub fn write_slice(&mut self, start: u16, bytes: &[u8]) {
+        for (destination, source) in self.data[start as usize..].iter_mut().zip(bytes) {
+            *destination = *source;
+        }
+    }
 }
 
 /// Debug memory does simple reads and writes to 64kb of memory. It also prints every read or write
@@ -53,12 +88,26 @@ pub struct MemoryController<T: SerialConnection, D: DisplayConnection> {
     pub test_mode: bool,
     /// The timer is stored here because it is probably the best place for it.
     pub timer: Timer,
+    /// Contains the state of the buttons
+    pub joypad: Joypad,
+    /// Contains the current CPU speed
+    pub speed_switch: SpeedSwitch,
     /// Contains data related to the serial connection
     pub serial: Serial<T>,
     /// Contains a cartridge
     pub cartridge: Cartridge,
     /// Contains the video stuff
     pub graphics: Video<D>,
+    /// Whether the CGB registers and features are available. See [MemoryController::set_cgb_mode].
+    cgb_mode: bool,
+    /// The number of cycles processed since the memory was created
+    pub(crate) cycles: u64,
+    /// All writes to IO registers. `None` if the log is not enabled.
+    io_log: Option<Vec<IoWrite>>,
+    /// Writes to these addresses are recorded in `watched_writes`
+    watchpoints: HashSet<u16>,
+    /// Address and value of writes to watched addresses that were not taken yet
+    watched_writes: Vec<(u16, u8)>,
 }
 
 impl<T: SerialConnection, D: DisplayConnection> MemoryController<T, D> {
@@ -71,81 +120,243 @@ impl<T: SerialConnection, D: DisplayConnection> MemoryController<T, D> {
             memory: Memory::new(),
             test_mode: false,
             timer: Timer::new(),
+            joypad: Joypad::new(),
+            speed_switch: SpeedSwitch::new(),
             serial: Serial::new(connection),
             cartridge: Cartridge::new(),
             graphics: Video::new(display_connection),
+            cgb_mode: false,
+            cycles: 0,
+            io_log: None,
+            watchpoints: HashSet::new(),
+            watched_writes: Vec::new(),
+        }
+    }
+
+    /// Copy the next byte of a running OAM DMA transfer.
+    ///
+    /// The source is read the same way the CPU would read it, so the currently selected ROM bank and the cartridge RAM are honored.
+    /// The transfer itself is not affected by the restriction of CPU reads during the transfer, see [MemoryController::dma_active].
+    fn process_object_attribute_memory_transfer(&mut self) {
+        let Some(transfer) = &self.graphics.current_transfer else {
+            return;
+        };
+        let source_address = transfer.current_source_address;
+        let target_address = transfer.current_target_address;
+        self.memory.data[target_address] = self.read_unrestricted(source_address as u16);
+
+        if target_address >= *OBJECT_ATTRIBUTE_MEMORY_AREA.end() {
+            self.graphics.current_transfer = None;
+            return;
+        }
+        self.graphics.current_transfer = Some(ObjectAttributeMemoryTransfer {
+            current_source_address: source_address + 1,
+            current_target_address: target_address + 1,
+        });
+    }
+
+    /// Check if an OAM DMA transfer is running.
+    ///
+    /// While a transfer is running, reads below the [IO_REGISTER_AREA] return `0xFF`. The IO registers, the [HIGH_RAM_AREA](memory_addresses::HIGH_RAM_AREA) and IE can still be read.
+    pub fn dma_active(&self) -> bool {
+        self.graphics.current_transfer.is_some()
+    }
+
+    /// Check if the external RAM of the cartridge can be accessed. Reads return `0xFF` and writes are ignored while it is disabled.
+    pub fn external_ram_enabled(&self) -> bool {
+        self.cartridge.is_external_ram_enabled()
+    }
+
+    /// Get the external RAM bank that is currently mapped to the [EXTERNAL_RAM_BANK](memory_addresses::EXTERNAL_RAM_BANK) area
+    pub fn current_ram_bank(&self) -> usize {
+        self.cartridge.current_ram_bank
+    }
+
+    /// Check if the PPU currently blocks the CPU from accessing `address`.
+    ///
+    /// The [VIDEO_RAM_AREA] can not be accessed while the PPU is transferring data to the LCD. The [OBJECT_ATTRIBUTE_MEMORY_AREA] can not be accessed during the OAM scan either.
+    /// Blocked reads return `0xFF` and blocked writes are ignored. In test mode nothing is blocked.
+    fn blocked_by_ppu(&self, address: u16) -> bool {
+        if self.test_mode {
+            return false;
+        }
+        let address = address as usize;
+        match self.graphics.current_lcd_status.ppu_mode {
+            PpuMode::TransferringData => {
+                VIDEO_RAM_AREA.contains(&address) || OBJECT_ATTRIBUTE_MEMORY_AREA.contains(&address)
+            }
+            PpuMode::Oam => OBJECT_ATTRIBUTE_MEMORY_AREA.contains(&address),
+            PpuMode::HBlank | PpuMode::VBlank => false,
+        }
+    }
+
+    /// Copy `bytes` into the memory, starting at `start`. See [Memory::write_slice].
+    ///
+    /// If the bytes overlap the [TILE_DATA_AREA], the cached tiles are decoded again before they are rendered.
+    pub fn write_slice(&mut self, start: u16, bytes: &[u8]) {
+        self.memory.write_slice(start, bytes);
+        let end = start as usize + bytes.len();
+        if (start as usize) <= *TILE_DATA_AREA.end() && end > *TILE_DATA_AREA.start() {
+            self.graphics.invalidate_tile_cache();
+        }
+    }
+
+    /// Get the mode the PPU is currently in. See [Video::ppu_mode].
+    pub fn ppu_mode(&self) -> PpuMode {
+        self.graphics.ppu_mode()
+    }
+
+    /// Get the line the PPU is currently on. See [Video::current_line].
+    pub fn current_line(&self) -> u8 {
+        self.graphics.current_line()
+    }
+
+    /// Render the whole active background tilemap into an RGBA buffer. See [Video::render_full_background].
+    pub fn render_full_background(&self) -> Vec<u8> {
+        self.graphics.render_full_background(&self.memory)
+    }
+
+    /// Render all tiles in VRAM into an RGBA buffer. See [Video::render_tile_atlas].
+    pub fn render_tile_atlas(&self) -> Vec<u8> {
+        self.graphics.render_tile_atlas(&self.memory)
+    }
+
+    /// Run as a Game Boy Color instead of the original gameboy.
+    ///
+    /// This maps the [SPEED_SWITCH_ADDRESS] and [VIDEO_RAM_BANK_ADDRESS] registers, allows the fast serial clock and renders with the CGB palettes, see [Video::set_cgb_mode].
+    pub fn set_cgb_mode(&mut self, cgb_mode: bool) {
+        self.cgb_mode = cgb_mode;
+        self.graphics.set_cgb_mode(cgb_mode);
+    }
+
+    /// Check if the memory runs as a Game Boy Color. See [MemoryController::set_cgb_mode].
+    pub fn is_cgb_mode(&self) -> bool {
+        self.cgb_mode
+    }
+
+    /// Check if an address is an IO register that is not used. See [UNMAPPED_IO_AREA] for details.
+    ///
+    /// The speed switch and VRAM bank registers only exist in CGB mode.
+    fn is_unmapped_io(&self, address: usize) -> bool {
+        if address == SPEED_SWITCH_ADDRESS || address == VIDEO_RAM_BANK_ADDRESS {
+            return !self.cgb_mode;
+        }
+        UNMAPPED_IO_AREA.contains(&address) && !MAPPED_IO_GAP_ADDRESSES.contains(&address)
+    }
+
+    /// Read a byte without the restrictions of a running OAM DMA transfer
+    fn read_unrestricted(&self, address: u16) -> u8 {
+        let address = resolve_echo_ram(address);
+        match address as usize {
+            address if self.is_unmapped_io(address) => 0xFF,
+            JOYPAD_ADDRESS => self.joypad.read(),
+            SPEED_SWITCH_ADDRESS => self.speed_switch.read(),
+            VIDEO_RAM_BANK_ADDRESS => self.graphics.read_video_ram_bank(),
+            address
+                if self.graphics.second_video_ram_bank_active()
+                    && VIDEO_RAM_AREA.contains(&address) =>
+            {
+                self.graphics.read_second_video_ram_bank(address)
+            }
+            _ => {
+                if !self.test_mode {
+                    if let Some(value) = self.cartridge.read(address) {
+                        return value;
+                    }
+                }
+                self.memory.data[address as usize]
+            }
         }
     }
 
     /// Should be called on every cycle
+    ///
+    /// In double speed mode the PPU is only advanced on every second cycle.
     pub fn process_cycle(&mut self) {
+        self.process_object_attribute_memory_transfer();
         self.timer.cycle(&mut self.memory);
         self.serial.cycle(&mut self.memory);
-        self.graphics.cycle(&mut self.memory);
+        if !self.speed_switch.is_double_speed() || self.cycles % 2 == 0 {
+            self.graphics.cycle(&mut self.memory);
+        }
+        self.cycles += 1;
+    }
+
+    /// Start recording all writes to IO registers. See [MemoryController::io_log].
+    ///
+    /// Calling this again clears the log.
+    pub fn enable_io_log(&mut self) {
+        self.io_log = Some(Vec::new());
+    }
+
+    /// Stop recording writes to IO registers and drop the log
+    pub fn disable_io_log(&mut self) {
+        self.io_log = None;
+    }
+
+    /// Get all writes to IO registers (`0xFF00..=0xFF7F` and `0xFFFF`) since [MemoryController::enable_io_log] was called.
+    ///
+    /// Writes to unused registers are recorded as well. Empty if the log is not enabled.
+    pub fn io_log(&self) -> &[IoWrite] {
+        self.io_log.as_deref().unwrap_or_default()
+    }
+
+    /// Start recording writes to `address`. See [MemoryController::take_watched_writes].
+    ///
+    /// Writes to the echo RAM are recorded at the address they are mirrored to.
+    pub fn add_watchpoint(&mut self, address: u16) {
+        self.watchpoints.insert(address);
+    }
+
+    /// Stop recording writes to `address`. Writes that were already recorded are kept.
+    pub fn remove_watchpoint(&mut self, address: u16) {
+        self.watchpoints.remove(&address);
+    }
+
+    /// Get the address and value of all writes to watched addresses since the last call and clear them.
+    ///
+    /// Writes are recorded even if they are ignored, like writes to ROM.
+    pub fn take_watched_writes(&mut self) -> Vec<(u16, u8)> {
+        std::mem::take(&mut self.watched_writes)
     }
 }
 
 impl<T: SerialConnection> MemoryController<T, DummyDisplayConnection> {
     /// Create a new Memory filled with `0`.
     pub fn new_with_connections(connection: Option<T>) -> Self {
-        Self {
-            memory: Memory::new(),
-            test_mode: false,
-            timer: Timer::new(),
-            serial: Serial::new(connection),
-            cartridge: Cartridge::new(),
-            graphics: Video::new(DummyDisplayConnection {}),
-        }
+        Self::new_with_video_connections(connection, DummyDisplayConnection {})
     }
 }
 
 impl MemoryController<LoggerSerialConnection, DummyDisplayConnection> {
     /// Create a new Memory filled with `0`.
     pub fn new() -> Self {
-        MemoryController {
-            memory: Memory::new(),
-            test_mode: false,
-            timer: Timer::new(),
-            serial: Serial::new(Some(LoggerSerialConnection::new())),
-            cartridge: Cartridge::new(),
-            graphics: Video::new(DummyDisplayConnection {}),
-        }
+        Self::new_with_connections(Some(LoggerSerialConnection::new()))
     }
     /// Create a new Memory filled with `0`.
     pub fn new_for_tests() -> Self {
-        MemoryController {
-            memory: Memory::new(),
-            test_mode: true,
-            timer: Timer::new(),
-            serial: Serial::new(Some(LoggerSerialConnection::new())),
-            cartridge: Cartridge::new(),
-            graphics: Video::new(DummyDisplayConnection {}),
-        }
+        let mut memory = Self::new();
+        memory.test_mode = true;
+        memory
     }
 
     /// Create a new Memory. `init` will be placed at memory address 0. The remaining memory will be filled with `0`.
     pub fn new_with_init(init: &[u8]) -> Self {
-        let mut memory = MemoryController {
-            memory: Memory::new(),
-            test_mode: true,
-            timer: Timer::new(),
-            serial: Serial::new(Some(LoggerSerialConnection::new())),
-            cartridge: Cartridge::new(),
-            graphics: Video::new(DummyDisplayConnection {}),
-        };
-        for (dst, src) in memory.memory.data.iter_mut().zip(init) {
-            *dst = *src;
-        }
+        let mut memory = Self::new_for_tests();
+        memory.write_slice(0, init);
         return memory;
     }
 }
 
 impl<T: SerialConnection, D: DisplayConnection> MemoryDevice for MemoryController<T, D> {
     fn read(&self, address: u16) -> u8 {
-        match address as usize {
-            ALWAYS_RETURNS_FF_ADDRESS => 0xFF,
-            _ => self.memory.data[address as usize],
+        if self.dma_active() && (address as usize) < *IO_REGISTER_AREA.start() {
+            return 0xFF;
+        }
+        if self.blocked_by_ppu(address) {
+            return 0xFF;
         }
+        self.read_unrestricted(address)
         // if (address == 0xff01) || (address == 0xff02) {
         //     println!("Read value {}({:#04x}) from {:#06x}", value, value, address);
         // }
@@ -156,6 +367,25 @@ impl<T: SerialConnection, D: DisplayConnection> MemoryDevice for MemoryControlle
         //     "Write value {}({:#04x}) from {:#06x}",
         //     value, value, address
         // );
+        if let Some(io_log) = &mut self.io_log {
+            if is_io_register(address as usize) {
+                io_log.push(IoWrite {
+                    cycle: self.cycles,
+                    address,
+                    value,
+                });
+            }
+        }
+        if self.is_unmapped_io(address as usize) {
+            return;
+        }
+        let address = resolve_echo_ram(address);
+        if !self.watchpoints.is_empty() && self.watchpoints.contains(&address) {
+            self.watched_writes.push((address, value));
+        }
+        if self.blocked_by_ppu(address) {
+            return;
+        }
         if self.test_mode {
             self.memory.data[address as usize] = value;
         }
@@ -163,7 +393,17 @@ impl<T: SerialConnection, D: DisplayConnection> MemoryDevice for MemoryControlle
         if write_timer_result.is_some() {
             return;
         }
-        let write_serial_result = self.serial.write(&mut self.memory, address, value);
+        let write_joypad_result = self.joypad.write(address, value);
+        if write_joypad_result.is_some() {
+            return;
+        }
+        let write_speed_switch_result = self.speed_switch.write(address, value);
+        if write_speed_switch_result.is_some() {
+            return;
+        }
+        let write_serial_result =
+            self.serial
+                .write(&mut self.memory, address, value, self.cgb_mode);
         if write_serial_result.is_some() {
             return;
         }
@@ -178,6 +418,21 @@ impl<T: SerialConnection, D: DisplayConnection> MemoryDevice for MemoryControlle
 
         self.memory.data[address as usize] = value;
     }
+    fn switch_speed(&mut self) -> bool {
+        self.speed_switch.switch()
+    }
+    fn reset_divider(&mut self) {
+        self.timer.reset_divider(&mut self.memory);
+    }
+}
+
+/// Redirect addresses in the [ECHO_RAM_AREA] to the [WORK_RAM_AREA] they mirror
+fn resolve_echo_ram(address: u16) -> u16 {
+    if ECHO_RAM_AREA.contains(&(address as usize)) {
+        let offset = address as usize - ECHO_RAM_AREA.start();
+        return (WORK_RAM_AREA.start() + offset) as u16;
+    }
+    address
 }
 
 /// The trait for things that can be accessed via memory
@@ -195,10 +450,39 @@ pub trait MemoryDevice {
     fn write_signed(&mut self, address: u16, value: i8) -> () {
         self.write(address, value.to_ne_bytes()[0]);
     }
+    /// Read a little-endian 16 bit value. The low byte is read from `address`, the high byte from the address after it.
+    fn read_u16(&self, address: u16) -> u16 {
+        u16::from_le_bytes([self.read(address), self.read(address.wrapping_add(1))])
+    }
+    /// Write a little-endian 16 bit value. The low byte is written to `address`, the high byte to the address after it.
+    fn write_u16(&mut self, address: u16, value: u16) -> () {
+        let [low, high] = value.to_le_bytes();
+        self.write(address, low);
+        self.write(address.wrapping_add(1), high);
+    }
+    /// Read all bytes in `range`. Useful for comparing whole memory regions in tests.
+    fn dump(&self, range: RangeInclusive<u16>) -> Vec<u8> {
+        range.map(|address| self.read(address)).collect()
+    }
+    /// Toggle between normal and double speed mode, if a switch was prepared. Returns `true` if the speed was switched.
+    ///
+    /// Called by the STOP instruction. Devices without a speed switch never switch.
+    fn switch_speed(&mut self) -> bool {
+        false
+    }
+    /// Reset the timer divider without a write to [TIMER_DIVIDER_ADDRESS].
+    ///
+    /// Called by the STOP instruction, so the reset does not show up in the IO log or the watchpoints. Devices without a timer write `0` to the divider.
+    fn reset_divider(&mut self) {
+        self.write(TIMER_DIVIDER_ADDRESS as u16, 0);
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::cpu::interrupt_controller::InterruptController;
+    use crate::cpu::Interrupt;
+    use crate::memory::memory_addresses::{LCD_CONTROL_ADDRESS, VIDEO_RAM_BANK_ADDRESS};
     use crate::{memory::MemoryController, memory::MemoryDevice};
 
     #[test]
@@ -209,6 +493,53 @@ mod tests {
         assert_eq!(read_value, 99);
     }
 
+    #[test]
+    fn u16_values_are_little_endian() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write_u16(0xC000, 0x1234);
+        assert_eq!(memory.read(0xC000), 0x34);
+        assert_eq!(memory.read(0xC001), 0x12);
+
+        memory.write(0xC010, 0xCD);
+        memory.write(0xC011, 0xAB);
+        assert_eq!(memory.read_u16(0xC010), 0xABCD);
+    }
+
+    #[test]
+    fn echo_ram_mirrors_work_ram() {
+        let mut memory = MemoryController::new();
+        memory.write(0xC000, 0x12);
+        assert_eq!(memory.read(0xE000), 0x12);
+
+        memory.write(0xFDFF, 0x34);
+        assert_eq!(memory.read(0xDDFF), 0x34);
+        assert_eq!(memory.memory.data[0xFDFF], 0);
+
+        // The last 0x200 bytes of work RAM are not mirrored
+        memory.write(0xDE00, 0x56);
+        assert_eq!(memory.read(0xFE00), 0);
+    }
+
+    #[test]
+    fn writes_to_watched_addresses_are_recorded() {
+        let mut memory = MemoryController::new();
+        memory.add_watchpoint(0xC000);
+
+        memory.write(0xC001, 0x12);
+        memory.write(0xC000, 0x34);
+        // Echo RAM writes are recorded at the mirrored address
+        memory.write(0xE000, 0x56);
+        assert_eq!(
+            memory.take_watched_writes(),
+            [(0xC000, 0x34), (0xC000, 0x56)]
+        );
+        assert!(memory.take_watched_writes().is_empty());
+
+        memory.remove_watchpoint(0xC000);
+        memory.write(0xC000, 0x78);
+        assert!(memory.take_watched_writes().is_empty());
+    }
+
     #[test]
     fn reads_zero_in_unused_memory() {
         let debug_memory = MemoryController::new_for_tests();
@@ -219,6 +550,48 @@ mod tests {
         assert_eq!(debug_memory.read(30000), 0);
     }
 
+    #[test]
+    fn unmapped_io_reads_ff_and_ignores_writes() {
+        let mut memory = MemoryController::new_for_tests();
+        assert_eq!(memory.read(0xFF4D), 0xFF);
+        assert_eq!(memory.read(0xFF7F), 0xFF);
+
+        memory.write(0xFF4D, 0x12);
+        memory.write(0xFF60, 0x34);
+        assert_eq!(memory.read(0xFF4D), 0xFF);
+        assert_eq!(memory.read(0xFF60), 0xFF);
+        assert_eq!(memory.memory.data[0xFF60], 0);
+    }
+
+    #[test]
+    fn second_video_ram_bank_can_only_be_selected_in_cgb_mode() {
+        let mut memory = MemoryController::new();
+        memory.write(0x8000, 0x12);
+        memory.write(VIDEO_RAM_BANK_ADDRESS as u16, 1);
+        assert_eq!(memory.read(VIDEO_RAM_BANK_ADDRESS as u16), 0xFF);
+        assert_eq!(memory.read(0x8000), 0x12);
+
+        memory.set_cgb_mode(true);
+        assert_eq!(memory.read(VIDEO_RAM_BANK_ADDRESS as u16), 0xFE);
+        memory.write(VIDEO_RAM_BANK_ADDRESS as u16, 1);
+        assert_eq!(memory.read(VIDEO_RAM_BANK_ADDRESS as u16), 0xFF);
+        assert_eq!(memory.read(0x8000), 0x00);
+        memory.write(0x8000, 0x34);
+        assert_eq!(memory.read(0x8000), 0x34);
+
+        memory.write(VIDEO_RAM_BANK_ADDRESS as u16, 0);
+        assert_eq!(memory.read(0x8000), 0x12);
+    }
+
+    #[test]
+    fn io_around_the_gap_is_still_mapped() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(0xFF4C, 0x12);
+        memory.write(0xFF80, 0x34);
+        assert_eq!(memory.read(0xFF4C), 0x12);
+        assert_eq!(memory.read(0xFF80), 0x34);
+    }
+
     #[test]
     fn initializing_memory_works() {
         let debug_memory = MemoryController::new_with_init(&[7, 5, 0, 255]);
@@ -228,4 +601,176 @@ mod tests {
         assert_eq!(debug_memory.read(3), 255);
         assert_eq!(debug_memory.read(4), 0);
     }
+
+    #[test]
+    fn dma_copies_from_the_switchable_rom_bank() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.place_into_memory(&mut memory.memory);
+        let expected: Vec<u8> = (0x4000..0x40A0)
+            .map(|address| memory.read(address))
+            .collect();
+        assert!(expected.iter().any(|value| *value != 0));
+
+        memory.write(0xFF46, 0x40);
+        for _ in 0..160 {
+            memory.process_cycle();
+        }
+
+        assert!(memory.graphics.current_transfer.is_none());
+        assert_eq!(&memory.memory.data[0xFE00..0xFEA0], expected.as_slice());
+        assert_eq!(memory.read(0xFF46), 0x40);
+    }
+
+    #[test]
+    fn dma_reads_disabled_external_ram_as_ff() {
+        let mut memory = MemoryController::new();
+        assert_eq!(memory.cartridge.ram_size, 0);
+
+        memory.write(0xFF46, 0xA0);
+        for _ in 0..160 {
+            memory.process_cycle();
+        }
+
+        assert!(memory.memory.data[0xFE00..0xFEA0]
+            .iter()
+            .all(|value| *value == 0xFF));
+    }
+
+    #[test]
+    fn dma_takes_160_cycles_with_the_lcd_off() {
+        let mut memory = MemoryController::new_for_tests();
+        assert!(!memory.graphics.current_lcd_control.lcd_ppu_enable);
+        for (index, value) in memory.memory.data[0xC000..0xC0A0].iter_mut().enumerate() {
+            *value = index as u8 + 1;
+        }
+
+        memory.write(0xFF46, 0xC0);
+        for _ in 0..159 {
+            memory.process_cycle();
+        }
+        assert!(memory.graphics.current_transfer.is_some());
+        assert_eq!(memory.memory.data[0xFE9F], 0);
+
+        memory.process_cycle();
+        assert!(memory.graphics.current_transfer.is_none());
+        assert_eq!(
+            memory.memory.data[0xFE00..0xFEA0],
+            memory.memory.data[0xC000..0xC0A0]
+        );
+    }
+
+    #[test]
+    fn dump_returns_the_object_attribute_memory_after_dma() {
+        let mut memory = MemoryController::new_for_tests();
+        let expected: Vec<u8> = (0..0xA0).map(|value| value as u8 ^ 0x5A).collect();
+        for (offset, value) in expected.iter().enumerate() {
+            memory.write(0xC100 + offset as u16, *value);
+        }
+
+        memory.write(0xFF46, 0xC1);
+        for _ in 0..160 {
+            memory.process_cycle();
+        }
+
+        assert_eq!(memory.dump(0xFE00..=0xFE9F), expected);
+        assert_eq!(memory.memory.dump(0xFE00..=0xFE9F), expected);
+    }
+
+    #[test]
+    fn slices_can_be_placed_at_any_address() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.memory.write_slice(0x0100, &[0x00, 0xC3, 0x50, 0x01]);
+        assert_eq!(
+            memory.dump(0x00FF..=0x0104),
+            [0x00, 0x00, 0xC3, 0x50, 0x01, 0x00]
+        );
+
+        // Bytes past the end of the memory are dropped
+        memory.memory.write_slice(0xFFFE, &[0x12, 0x34, 0x56]);
+        assert_eq!(memory.read(0xFFFE), 0x12);
+        assert_eq!(memory.read(0xFFFF), 0x34);
+        assert_eq!(memory.read(0x0000), 0x00);
+    }
+
+    #[test]
+    fn writing_a_slice_of_tile_data_updates_the_cached_tiles() {
+        let mut memory = MemoryController::new_for_tests();
+        // Decode the tiles while they are all white
+        let tiles = memory.graphics.get_tile_data(&memory.memory);
+        assert_eq!(tiles[0].get_line(0), [0; 8]);
+
+        // Make tile 0 black
+        memory.write_slice(0x8000, &[0xFF; 16]);
+        let tiles = memory.graphics.get_tile_data(&memory.memory);
+        assert_eq!(tiles[0].get_line(0), [3; 8]);
+    }
+
+    #[test]
+    fn video_memory_can_not_be_accessed_while_the_ppu_uses_it() {
+        let mut memory = MemoryController::new();
+        memory.write(0x8000, 0x12);
+        memory.write(0xFE00, 0x34);
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+
+        // OAM scan
+        memory.process_cycle();
+        assert_eq!(memory.read(0x8000), 0x12);
+        assert_eq!(memory.read(0xFE00), 0xFF);
+        memory.write(0xFE00, 0x56);
+
+        // Transferring data
+        for _ in 0..20 {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(0x8000), 0xFF);
+        assert_eq!(memory.read(0xFE00), 0xFF);
+        memory.write(0x8000, 0x78);
+
+        // HBlank
+        for _ in 0..50 {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(0x8000), 0x12);
+        assert_eq!(memory.read(0xFE00), 0x34);
+        memory.write(0x8000, 0x78);
+        assert_eq!(memory.read(0x8000), 0x78);
+    }
+
+    #[test]
+    fn interrupts_can_be_requested_during_dma() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write_interrupt_enable_register(0b00000101);
+
+        memory.write(0xFF46, 0xC0);
+        memory.process_cycle();
+        assert!(memory.dma_active());
+        memory.write_interrupt_flag(Interrupt::Timer, true);
+
+        assert_eq!(
+            memory.read_interrupt_flag_register(),
+            Interrupt::Timer as u8
+        );
+        assert_eq!(memory.read_interrupt_enable_register(), 0b00000101);
+    }
+
+    #[test]
+    fn only_high_ram_and_io_registers_can_be_read_during_dma() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(0xC000, 0x42);
+        memory.write(0xFF80, 0x43);
+
+        memory.write(0xFF46, 0xC0);
+        memory.process_cycle();
+        assert!(memory.dma_active());
+        assert_eq!(memory.read(0xC000), 0xFF);
+        assert_eq!(memory.read(0xFF80), 0x43);
+        assert_eq!(memory.read(0xFF46), 0xC0);
+
+        for _ in 0..159 {
+            memory.process_cycle();
+        }
+        assert!(!memory.dma_active());
+        assert_eq!(memory.read(0xC000), 0x42);
+        assert_eq!(memory.memory.data[0xFE00], 0x42);
+    }
 }
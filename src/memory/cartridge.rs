@@ -1,18 +1,25 @@
-use std::{cmp::max, fs, mem::take};
+use std::{
+    cmp::{max, min},
+    fs, io,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::memory::memory_addresses::{
     CARTRIDGE_CHECKSUM_LSB_ADDRESS, CARTRIDGE_CHECKSUM_MSB_ADDRESS, CARTRIDGE_HEADER_RANGE,
-    CARTRIDGE_TYPE_ADDRESS, DESTINATION_COUNTRY_ADDRESS, FIRST_ROM_BANK, HEADER_CHECKSUM_ADDRESS,
-    RAM_SIZE_ADDRESS, ROM_BANK_SIZE, ROM_SIZE_ADDRESS, ROM_VERSION_ADDRESS, SECOND_ROM_BANK,
-    TITLE_RANGE,
+    CARTRIDGE_TYPE_ADDRESS, CGB_FLAG_ADDRESS, DESTINATION_COUNTRY_ADDRESS, EXTERNAL_RAM_BANK,
+    FIRST_ROM_BANK, HEADER_CHECKSUM_ADDRESS, NEW_LICENSEE_CODE_RANGE, OLD_LICENSEE_CODE_ADDRESS,
+    RAM_BANK_SIZE, RAM_SIZE_ADDRESS, ROM_BANK_SIZE, ROM_SIZE_ADDRESS, ROM_VERSION_ADDRESS,
+    SECOND_ROM_BANK, SGB_FLAG_ADDRESS, TITLE_RANGE,
 };
 
-use self::{cartridge_type::CartridgeType, destination::Destination};
+use self::{cartridge_type::CartridgeType, cgb_flag::CgbFlag, destination::Destination};
 
 use super::Memory;
 
 /// Contains information about cartridge types
 pub mod cartridge_type;
+/// Contains information about Game Boy Color support
+pub mod cgb_flag;
 /// Contains information about destination regions
 pub mod destination;
 
@@ -21,6 +28,10 @@ pub struct Cartridge {
     rom: Vec<u8>,
     /// The title of the ROm
     pub title: String,
+    /// Indicates whether the game supports or requires the Game Boy Color
+    pub cgb_flag: CgbFlag,
+    /// Indicates whether the game supports Super Game Boy functions
+    pub sgb_support: bool,
     /// Indicates what kind of hardware is present on the cartridge
     pub cartridge_type: CartridgeType,
     /// Rom size in bytes
@@ -29,6 +40,10 @@ pub struct Cartridge {
     pub ram_size: usize,
     /// Destination code
     pub destination: Destination,
+    /// The licensee code of the publisher.
+    ///
+    /// This is the two character new licensee code, or the old licensee code as two hex digits for older games.
+    pub licensee: String,
     /// Version number of the game
     pub mask_rom_version_number: u8,
     /// An 8-bit checksum computed from the cartridge header bytes 0x0134 0x014C
@@ -38,64 +53,144 @@ pub struct Cartridge {
 
     /// The current ram bank
     pub current_ram_bank: usize,
-    /// The current second rom bank
-    pub current_second_rom_bank: u8,
+    /// The current second rom bank. MBC5 uses all 9 bits.
+    pub current_second_rom_bank: u16,
     /// If advanced banking is enabled
     pub advanced_banking_enabled: bool,
     /// Whether external RAM is enabled
     /// Enable writes between `0xA000` and `0xBFFF`
     pub external_ram_enabled: bool,
+    /// The contents of the external RAM banks, or of the RAM that is built into the MBC2
+    ram: Vec<u8>,
+    /// The MBC3 clock register that is mapped instead of RAM, if one is selected
+    pub selected_rtc_register: Option<usize>,
+    /// The MBC3 clock registers: seconds, minutes, hours, lower 8 bits of the day counter and the upper bit of the day counter with the halt and carry flags.
+    ///
+    /// They can be read and written, but the clock does not run yet.
+    pub rtc_registers: [u8; 5],
+}
+
+/// The banking state of a [Cartridge]. The ROM itself is not part of it. Used for savestates.
+#[derive(Clone)]
+pub struct CartridgeState {
+    current_ram_bank: usize,
+    current_second_rom_bank: u16,
+    advanced_banking_enabled: bool,
+    external_ram_enabled: bool,
+    ram: Vec<u8>,
+    selected_rtc_register: Option<usize>,
+    rtc_registers: [u8; 5],
 }
 
+/// Errors that can occur when loading a [Cartridge]
+#[derive(Debug, PartialEq, Eq)]
+pub enum CartridgeError {
+    /// The ROM file could not be read
+    ReadFailed(io::ErrorKind),
+    /// The ROM is too short to contain a cartridge header
+    Truncated {
+        /// The length of the ROM
+        length: usize,
+    },
+    /// The title contains bytes that are not valid UTF-8
+    InvalidTitle,
+    /// The new licensee code contains bytes that are not valid UTF-8
+    InvalidLicensee,
+    /// The cartridge type byte does not describe a known [CartridgeType]
+    UnknownCartridgeType(u8),
+    /// The ROM size byte is not a valid size
+    InvalidRomSize(u8),
+    /// The RAM size byte is not a valid size
+    InvalidRamSize(u8),
+    /// The destination byte is neither Japan nor overseas
+    UnknownDestination(u8),
+}
+
+/// The number of half-bytes in the RAM that is built into the MBC2 chip
+const MBC2_RAM_SIZE: usize = 512;
+
+/// The number of bytes that are appended to the RAM in saves of cartridges with a real time clock.
+///
+/// This is the format most emulators use: the 5 clock registers and the 5 latched clock registers as 32 bit little endian values, followed by a 64 bit little endian UNIX timestamp.
+const RTC_SAVE_SIZE: usize = 48;
+
 /// Decode the RAM size byte from the cartridge header into the number of RAM bytes.
-pub fn decode_ram_size(byte: u8) -> usize {
+pub fn decode_ram_size(byte: u8) -> Result<usize, CartridgeError> {
     match byte {
-        0 => 0,
-        1 => 0,
-        2 => 1 << 13,
-        3 => 1 << 15,
-        4 => 1 << 17,
-        5 => 1 << 16,
-        _ => panic!("Invalid value for the cartridge RAM size"),
+        0 => Ok(0),
+        1 => Ok(0),
+        2 => Ok(1 << 13),
+        3 => Ok(1 << 15),
+        4 => Ok(1 << 17),
+        5 => Ok(1 << 16),
+        _ => Err(CartridgeError::InvalidRamSize(byte)),
     }
 }
 
 /// Decode the ROM size byte from the cartridge header into the number of ROM bytes.
-pub fn decode_rom_size(byte: u8) -> usize {
-    (1 << 15) * (1 << byte)
+pub fn decode_rom_size(byte: u8) -> Result<usize, CartridgeError> {
+    match byte {
+        0..=8 => Ok((1 << 15) * (1 << byte)),
+        _ => Err(CartridgeError::InvalidRomSize(byte)),
+    }
 }
 
 impl Cartridge {
     /// Loads a new test cartridge with a test ROM
     pub fn new() -> Cartridge {
         Self::load("test_roms/blargg/cpu_instrs/individual/06-ld r,r.gb")
+            .expect("The test ROM should be valid")
     }
     /// Loads a new test cartridge with a ROM from a file
-    pub fn load(path_to_rom: &str) -> Cartridge {
-        let mut content = fs::read(path_to_rom).expect("Should exists");
-        let memory = take(&mut content);
-
-        let title_memory: &[u8] = &memory[TITLE_RANGE];
-        let title_result = String::from_utf8(title_memory.into());
-        let title = title_result.expect("The title should not contain invalid characters");
-        let cartridge_type: CartridgeType = memory[CARTRIDGE_TYPE_ADDRESS].into();
-        let rom_size = decode_rom_size(memory[ROM_SIZE_ADDRESS]);
-        let ram_size = decode_ram_size(memory[RAM_SIZE_ADDRESS]);
-        let destination: Destination = memory[DESTINATION_COUNTRY_ADDRESS].into();
-        let mask_rom_version_number = memory[ROM_VERSION_ADDRESS];
-        let header_checksum = memory[HEADER_CHECKSUM_ADDRESS];
+    pub fn load(path_to_rom: &str) -> Result<Cartridge, CartridgeError> {
+        let content =
+            fs::read(path_to_rom).map_err(|error| CartridgeError::ReadFailed(error.kind()))?;
+        Self::from_bytes(content)
+    }
+    /// Create a cartridge from the bytes of a ROM.
+    ///
+    /// Useful if the ROM is not stored in a file, for example because it is embedded or downloaded.
+    /// Fails if the ROM does not contain a valid cartridge header.
+    pub fn from_bytes(rom: Vec<u8>) -> Result<Cartridge, CartridgeError> {
+        if rom.len() <= CARTRIDGE_CHECKSUM_LSB_ADDRESS {
+            return Err(CartridgeError::Truncated { length: rom.len() });
+        }
+        let title_memory: &[u8] = &rom[TITLE_RANGE];
+        let title =
+            String::from_utf8(title_memory.into()).map_err(|_| CartridgeError::InvalidTitle)?;
+        let cgb_flag = CgbFlag::from(rom[CGB_FLAG_ADDRESS]);
+        let sgb_support = rom[SGB_FLAG_ADDRESS] == 0x03;
+        let cartridge_type = CartridgeType::try_from(rom[CARTRIDGE_TYPE_ADDRESS])?;
+        let rom_size = decode_rom_size(rom[ROM_SIZE_ADDRESS])?;
+        let ram_size = decode_ram_size(rom[RAM_SIZE_ADDRESS])?;
+        let destination = Destination::try_from(rom[DESTINATION_COUNTRY_ADDRESS])?;
+        let licensee = match rom[OLD_LICENSEE_CODE_ADDRESS] {
+            0x33 => String::from_utf8(rom[NEW_LICENSEE_CODE_RANGE].to_vec())
+                .map_err(|_| CartridgeError::InvalidLicensee)?,
+            old_licensee_code => format!("{:02X}", old_licensee_code),
+        };
+        let mask_rom_version_number = rom[ROM_VERSION_ADDRESS];
+        let header_checksum = rom[HEADER_CHECKSUM_ADDRESS];
         let cartridge_checksum = u16::from_be_bytes([
-            memory[CARTRIDGE_CHECKSUM_MSB_ADDRESS],
-            memory[CARTRIDGE_CHECKSUM_LSB_ADDRESS],
+            rom[CARTRIDGE_CHECKSUM_MSB_ADDRESS],
+            rom[CARTRIDGE_CHECKSUM_LSB_ADDRESS],
         ]);
 
-        Cartridge {
-            rom: memory,
+        let ram = match cartridge_type {
+            CartridgeType::Mbc2 | CartridgeType::Mbc2Battery => vec![0; MBC2_RAM_SIZE],
+            _ => vec![0; ram_size],
+        };
+
+        Ok(Cartridge {
+            rom,
             title,
+            cgb_flag,
+            sgb_support,
             cartridge_type,
             rom_size,
             ram_size,
             destination,
+            licensee,
             mask_rom_version_number,
             header_checksum,
             cartridge_checksum,
@@ -103,53 +198,255 @@ impl Cartridge {
             current_second_rom_bank: 1,
             advanced_banking_enabled: false,
             external_ram_enabled: false,
+            ram,
+            selected_rtc_register: None,
+            rtc_registers: [0; 5],
+        })
+    }
+    /// Capture the current banking state
+    pub fn save_state(&self) -> CartridgeState {
+        CartridgeState {
+            current_ram_bank: self.current_ram_bank,
+            current_second_rom_bank: self.current_second_rom_bank,
+            advanced_banking_enabled: self.advanced_banking_enabled,
+            external_ram_enabled: self.external_ram_enabled,
+            ram: self.ram.clone(),
+            selected_rtc_register: self.selected_rtc_register,
+            rtc_registers: self.rtc_registers,
         }
     }
+    /// Restore a banking state captured with [Cartridge::save_state]
+    pub fn load_state(&mut self, state: &CartridgeState) {
+        self.current_ram_bank = state.current_ram_bank;
+        self.current_second_rom_bank = state.current_second_rom_bank;
+        self.advanced_banking_enabled = state.advanced_banking_enabled;
+        self.external_ram_enabled = state.external_ram_enabled;
+        self.ram.clone_from(&state.ram);
+        self.selected_rtc_register = state.selected_rtc_register;
+        self.rtc_registers = state.rtc_registers;
+    }
+    /// Compute the header checksum of `rom`, which is stored at [HEADER_CHECKSUM_ADDRESS].
+    ///
+    /// Only the bytes in [CARTRIDGE_HEADER_RANGE] are used, so this can be used to create a valid header for ROMs that are built in code.
+    /// Returns `None` if `rom` is too short to contain the header.
+    pub fn compute_header_checksum(rom: &[u8]) -> Option<u8> {
+        let header = rom.get(CARTRIDGE_HEADER_RANGE)?;
+        Some(header.iter().fold(0u8, |accumulator, byte| {
+            accumulator.wrapping_sub(*byte).wrapping_sub(1)
+        }))
+    }
     /// Check if the cartridge header is valid
     pub fn check_header_checksum(&self) -> Result<(), ()> {
-        let checksum_bytes = &self.rom[CARTRIDGE_HEADER_RANGE];
-        let checksum = checksum_bytes.iter().fold(0u8, |accumulator, byte| {
-            accumulator.wrapping_sub(*byte).wrapping_sub(1)
-        });
-        if checksum != self.header_checksum {
+        if Self::compute_header_checksum(&self.rom) != Some(self.header_checksum) {
             return Err(());
         }
         Ok(())
     }
+    /// Compute the global checksum of the ROM.
+    ///
+    /// This is the sum of all bytes in the ROM, except for the two checksum bytes themselves.
+    pub fn compute_cartridge_checksum(&self) -> u16 {
+        Self::compute_rom_checksum(&self.rom)
+    }
+    /// Compute the global checksum of `rom`, which is stored big-endian at [CARTRIDGE_CHECKSUM_MSB_ADDRESS] and [CARTRIDGE_CHECKSUM_LSB_ADDRESS].
+    ///
+    /// Like [Cartridge::compute_cartridge_checksum], but for ROMs that are built in code.
+    pub fn compute_rom_checksum(rom: &[u8]) -> u16 {
+        rom.iter()
+            .enumerate()
+            .filter(|(address, _)| {
+                *address != CARTRIDGE_CHECKSUM_MSB_ADDRESS
+                    && *address != CARTRIDGE_CHECKSUM_LSB_ADDRESS
+            })
+            .fold(0u16, |accumulator, (_, byte)| {
+                accumulator.wrapping_add(*byte as u16)
+            })
+    }
     /// Check if the cartridge ROM is valid
+    ///
+    /// The computed checksum is compared to the big-endian value stored at [CARTRIDGE_CHECKSUM_MSB_ADDRESS] and [CARTRIDGE_CHECKSUM_LSB_ADDRESS], independent of the byte order of the host.
     pub fn check_cartridge_checksum(&self) -> Result<(), ()> {
-        let checksum_with_checksum_bytes = self.rom.iter().fold(0u16, |accumulator, byte| {
-            accumulator.wrapping_add(*byte as u16)
-        });
-        let checksum = checksum_with_checksum_bytes
-            .wrapping_sub(self.cartridge_checksum.to_ne_bytes()[0] as u16)
-            .wrapping_sub(self.cartridge_checksum.to_ne_bytes()[1] as u16);
-        if checksum != self.cartridge_checksum {
+        let stored_checksum = u16::from_be_bytes([
+            self.rom[CARTRIDGE_CHECKSUM_MSB_ADDRESS],
+            self.rom[CARTRIDGE_CHECKSUM_LSB_ADDRESS],
+        ]);
+        if self.compute_cartridge_checksum() != stored_checksum {
             return Err(());
         }
         Ok(())
     }
     /// Put the cartridge ROM into memory
+    ///
+    /// ROMs that are smaller than the two banks that are mapped at the start are padded with `0xFF`.
     pub fn place_into_memory(&self, memory: &mut Memory) {
-        memory.data[FIRST_ROM_BANK].copy_from_slice(&self.rom[FIRST_ROM_BANK]);
-        memory.data[SECOND_ROM_BANK].copy_from_slice(&self.rom[SECOND_ROM_BANK]);
+        self.copy_rom(*FIRST_ROM_BANK.start(), &mut memory.data[FIRST_ROM_BANK]);
+        self.copy_rom(*SECOND_ROM_BANK.start(), &mut memory.data[SECOND_ROM_BANK]);
+    }
+    /// Fill `destination` with the ROM starting at `start`. Bytes past the end of the ROM read as `0xFF`.
+    fn copy_rom(&self, start: usize, destination: &mut [u8]) {
+        let available = self.rom.get(start..).unwrap_or_default();
+        let length = min(available.len(), destination.len());
+        destination[..length].copy_from_slice(&available[..length]);
+        destination[length..].fill(0xFF);
     }
     fn load_second_rom_bank(&self, memory: &mut Memory) {
-        let selected_rom_bank = if self.advanced_banking_enabled {
-            self.current_second_rom_bank
+        let selected_rom_bank = match self.cartridge_type {
+            CartridgeType::Mbc1 | CartridgeType::Mbc1Ram | CartridgeType::Mbc1RamBattery
+                if !self.advanced_banking_enabled =>
+            {
+                self.current_second_rom_bank & 0b1111
+            }
+            _ => self.current_second_rom_bank,
+        };
+        self.copy_rom(
+            selected_rom_bank as usize * ROM_BANK_SIZE,
+            &mut memory.data[SECOND_ROM_BANK],
+        );
+    }
+    /// Check if the external RAM can be accessed.
+    ///
+    /// Cartridges with a MBC need to enable their RAM first, cartridges without one can always access it.
+    pub fn is_external_ram_enabled(&self) -> bool {
+        match self.cartridge_type {
+            CartridgeType::RomOnly | CartridgeType::RomRam | CartridgeType::RomRamBattery => true,
+            _ => self.external_ram_enabled,
+        }
+    }
+    /// Check if the cartridge uses the RAM that is built into the MBC2 instead of external RAM
+    fn has_mbc2_ram(&self) -> bool {
+        matches!(
+            self.cartridge_type,
+            CartridgeType::Mbc2 | CartridgeType::Mbc2Battery
+        )
+    }
+    /// The number of bytes of RAM in a save, without the clock. See [Cartridge::saved_ram_size].
+    fn saved_ram_without_rtc_size(&self) -> usize {
+        if self.has_mbc2_ram() {
+            return MBC2_RAM_SIZE;
+        }
+        self.ram_size
+    }
+    /// The number of bytes returned by [Cartridge::dump_ram].
+    ///
+    /// This is [Cartridge::ram_size], except for MBC2 cartridges which have 512 half-bytes of RAM built into the MBC.
+    /// Cartridges with a real time clock store the clock registers in 48 additional bytes.
+    pub fn saved_ram_size(&self) -> usize {
+        if self.cartridge_type.has_timer() {
+            return self.saved_ram_without_rtc_size() + RTC_SAVE_SIZE;
+        }
+        self.saved_ram_without_rtc_size()
+    }
+    /// Get the contents of all RAM banks, for example to store them in a `.sav` file.
+    ///
+    /// For cartridges with a real time clock the clock registers are appended, in the format most emulators use.
+    /// The clock does not run yet, so the current time is stored as the time of the save and the latched registers are the same as the current ones.
+    pub fn dump_ram(&self) -> Vec<u8> {
+        let mut ram = self.ram.clone();
+        ram.resize(self.saved_ram_without_rtc_size(), 0);
+        if self.cartridge_type.has_timer() {
+            for register in self.rtc_registers.iter().chain(self.rtc_registers.iter()) {
+                ram.extend_from_slice(&(*register as u32).to_le_bytes());
+            }
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            ram.extend_from_slice(&timestamp.to_le_bytes());
+        }
+        ram
+    }
+    /// Restore the contents of all RAM banks and the clock registers from data created by [Cartridge::dump_ram].
+    ///
+    /// Fails if `data` does not have the size returned by [Cartridge::saved_ram_size].
+    pub fn load_ram(&mut self, data: &[u8]) -> Result<(), ()> {
+        if data.len() != self.saved_ram_size() {
+            return Err(());
+        }
+        let (ram, rtc) = data.split_at(self.saved_ram_without_rtc_size());
+        self.ram = ram.to_vec();
+        if self.cartridge_type.has_timer() {
+            for (register, value) in self.rtc_registers.iter_mut().zip(rtc.chunks_exact(4)) {
+                *register = value[0];
+            }
+        }
+        Ok(())
+    }
+    /// Map `bank` to the external RAM area.
+    ///
+    /// Banks that do not exist wrap around to the existing ones.
+    fn switch_ram_bank(&mut self, bank: usize) {
+        let bank_count = self.ram.len() / RAM_BANK_SIZE;
+        if bank_count == 0 {
+            return;
+        }
+        self.current_ram_bank = bank % bank_count;
+    }
+    /// Get the position of `address` in the external RAM area in [Cartridge::ram], using the current RAM bank
+    fn ram_position(&self, address: u16) -> usize {
+        if self.has_mbc2_ram() {
+            return address as usize % MBC2_RAM_SIZE;
+        }
+        self.current_ram_bank * RAM_BANK_SIZE + (address as usize - EXTERNAL_RAM_BANK.start())
+    }
+
+    /// Process reads from the memory
+    ///
+    /// Returns `None` if the value should be read from the memory.
+    ///
+    /// Cartridges without RAM and cartridges whose RAM is not enabled read `0xFF` in the external RAM area.
+    /// The MBC2 RAM only stores the lower 4 bits and is repeated in the whole external RAM area.
+    pub fn read(&self, address: u16) -> Option<u8> {
+        if EXTERNAL_RAM_BANK.contains(&(address as usize)) && !self.is_external_ram_enabled() {
+            return Some(0xFF);
+        }
+        if !EXTERNAL_RAM_BANK.contains(&(address as usize)) {
+            return None;
+        }
+        if self.has_mbc2_ram() {
+            return Some(self.ram[self.ram_position(address)] & 0x0F);
+        }
+        if let Some(register) = self.selected_rtc_register {
+            return Some(self.rtc_registers[register]);
+        }
+        Some(
+            self.ram
+                .get(self.ram_position(address))
+                .copied()
+                .unwrap_or(0xFF),
+        )
+    }
+
+    /// Process writes to the external RAM area. Writes are ignored while the RAM is disabled.
+    fn write_external_ram(&mut self, address: u16, value: u8) {
+        if let Some(register) = self.selected_rtc_register {
+            if self.external_ram_enabled {
+                self.rtc_registers[register] = value;
+            }
+            return;
+        }
+        if !self.is_external_ram_enabled() {
+            return;
+        }
+        let value = if self.has_mbc2_ram() {
+            value & 0x0F
         } else {
-            self.current_second_rom_bank & 0b1111
+            value
         };
-        let rom_bank_chunk = self
-            .rom
-            .chunks_exact(ROM_BANK_SIZE)
-            .nth(selected_rom_bank as usize)
-            .expect("Tried to load a nonexisting ROM bank");
-        memory.data[SECOND_ROM_BANK].copy_from_slice(rom_bank_chunk)
+        let position = self.ram_position(address);
+        if let Some(byte) = self.ram.get_mut(position) {
+            *byte = value;
+        }
     }
 
     /// Process writes to the memory
+    ///
+    /// The external RAM is stored in the cartridge, so writes to the external RAM area never reach the memory.
+    /// Writes to the external RAM area of cartridges without RAM are ignored, even if RAM was enabled.
     pub fn write(&mut self, memory: &mut Memory, address: u16, value: u8) -> Option<()> {
+        if EXTERNAL_RAM_BANK.contains(&(address as usize)) {
+            self.write_external_ram(address, value);
+            return Some(());
+        }
         match self.cartridge_type {
             CartridgeType::RomRam | CartridgeType::RomRamBattery | CartridgeType::RomOnly => {}
             CartridgeType::Mbc1 | CartridgeType::Mbc1Ram | CartridgeType::Mbc1RamBattery => {
@@ -163,43 +460,106 @@ impl Cartridge {
                         self.external_ram_enabled = enable_external_ram
                     }
                     0x2000..=0x3FFF => {
-                        let new_rom_bank = max(value & 0b11111, 1)
-                            | (self.current_second_rom_bank as u8 & 0b1100000);
+                        let new_rom_bank = max(value as u16 & 0b11111, 1)
+                            | (self.current_second_rom_bank & 0b1100000);
                         self.current_second_rom_bank = new_rom_bank;
                         self.load_second_rom_bank(memory);
                     }
+                    // Cartridges with at least 32KiB of RAM use the register as RAM bank in advanced banking mode
+                    0x4000..=0x5FFF
+                        if self.advanced_banking_enabled && self.ram_size >= 4 * RAM_BANK_SIZE =>
+                    {
+                        self.switch_ram_bank((value & 0b11) as usize);
+                    }
                     0x4000..=0x5FFF => {
                         let new_rom_bank =
-                            (value & 0b01100000) | (self.current_second_rom_bank & 0b1111);
+                            (value as u16 & 0b01100000) | (self.current_second_rom_bank & 0b1111);
                         self.current_second_rom_bank = new_rom_bank;
                         self.load_second_rom_bank(memory);
                     }
                     0x6000..=0x7FFF => {
                         self.advanced_banking_enabled = value % 2 != 0;
-                        self.load_second_rom_bank(memory);
-                    }
-                    0xA000..=0xBFFF => {
-                        if self.external_ram_enabled {
-                            memory.data[address as usize] = value;
+                        if !self.advanced_banking_enabled {
+                            // Only the first RAM bank is accessible in simple banking mode
+                            self.switch_ram_bank(0);
                         }
-                        return Some(());
+                        self.load_second_rom_bank(memory);
                     }
                     _ => {}
                 }
             }
-            CartridgeType::Mbc2 | CartridgeType::Mbc2Battery => {}
+            CartridgeType::Mbc2 | CartridgeType::Mbc2Battery => match address {
+                // Bit 8 of the address decides between RAM enable and ROM bank select
+                0x0000..=0x3FFF if address & 0x0100 == 0 => {
+                    let enable_external_ram = (value & 0b1111) == 0xA;
+                    self.external_ram_enabled = enable_external_ram
+                }
+                0x0000..=0x3FFF => {
+                    self.current_second_rom_bank = max(value as u16 & 0b1111, 1);
+                    self.load_second_rom_bank(memory);
+                }
+                _ => {}
+            },
             CartridgeType::Mmm01 | CartridgeType::Mmm01Ram | CartridgeType::Mmm01RamBattery => {}
             CartridgeType::Mbc3TimerBattery
             | CartridgeType::Mbc3TimerRamBattery
             | CartridgeType::Mbc3
             | CartridgeType::Mbc3Ram
-            | CartridgeType::Mbc3RamBattery => {}
+            | CartridgeType::Mbc3RamBattery => match address {
+                0x0000..=0x1FFF => {
+                    // Also enables the clock registers
+                    let enable_external_ram = (value & 0b1111) == 0xA;
+                    self.external_ram_enabled = enable_external_ram
+                }
+                0x2000..=0x3FFF => {
+                    self.current_second_rom_bank = max(value as u16 & 0b1111111, 1);
+                    self.load_second_rom_bank(memory);
+                }
+                0x4000..=0x5FFF => match value {
+                    0x00..=0x03 => {
+                        self.selected_rtc_register = None;
+                        self.switch_ram_bank(value as usize);
+                    }
+                    0x08..=0x0C => {
+                        self.selected_rtc_register = Some((value - 0x08) as usize);
+                    }
+                    _ => {}
+                },
+                // TODO: Latch the clock registers when writing 0x00 and then 0x01 to 0x6000..=0x7FFF, once the clock is running
+                _ => {}
+            },
             CartridgeType::Mbc5
             | CartridgeType::Mbc5Ram
             | CartridgeType::Mbc5RamBattery
             | CartridgeType::Mbc5Rumble
             | CartridgeType::Mbc5RumbleRam
-            | CartridgeType::Mbc5RumbleRamBattery => {}
+            | CartridgeType::Mbc5RumbleRamBattery => match address {
+                0x0000..=0x1FFF => {
+                    let enable_external_ram = (value & 0b1111) == 0xA;
+                    self.external_ram_enabled = enable_external_ram
+                }
+                0x2000..=0x2FFF => {
+                    // Unlike the other MBCs, bank 0 can be selected
+                    self.current_second_rom_bank =
+                        (self.current_second_rom_bank & 0x100) | value as u16;
+                    self.load_second_rom_bank(memory);
+                }
+                0x3000..=0x3FFF => {
+                    self.current_second_rom_bank =
+                        (self.current_second_rom_bank & 0xFF) | ((value as u16 & 1) << 8);
+                    self.load_second_rom_bank(memory);
+                }
+                0x4000..=0x5FFF => {
+                    // Bit 3 controls the rumble motor on rumble cartridges
+                    let ram_bank_mask = if self.cartridge_type.has_rumble() {
+                        0b0111
+                    } else {
+                        0b1111
+                    };
+                    self.switch_ram_bank((value & ram_bank_mask) as usize);
+                }
+                _ => {}
+            },
             CartridgeType::Mbc6 => {}
             CartridgeType::Mbc7SensorRumbleRamBattery => {}
             CartridgeType::PocketCamera => {}
@@ -216,9 +576,22 @@ impl Cartridge {
 
 #[cfg(test)]
 mod tests {
+    use crate::memory::memory_addresses::{
+        CARTRIDGE_CHECKSUM_LSB_ADDRESS, CARTRIDGE_CHECKSUM_MSB_ADDRESS, CARTRIDGE_TYPE_ADDRESS,
+        CGB_FLAG_ADDRESS, HEADER_CHECKSUM_ADDRESS, ROM_BANK_SIZE, SGB_FLAG_ADDRESS, TITLE_RANGE,
+    };
     use crate::{memory::MemoryController, memory::MemoryDevice};
 
-    use super::Cartridge;
+    use super::{cartridge_type::CartridgeType, cgb_flag::CgbFlag, Cartridge, CartridgeError};
+
+    /// Create a ROM with `banks` banks. The first two bytes of every bank contain its number.
+    fn numbered_rom(banks: usize) -> Vec<u8> {
+        let mut rom = vec![0; banks * ROM_BANK_SIZE];
+        for (number, bank) in rom.chunks_exact_mut(ROM_BANK_SIZE).enumerate() {
+            bank[0..2].copy_from_slice(&(number as u16).to_le_bytes());
+        }
+        rom
+    }
 
     #[test]
     fn loads_correctly() {
@@ -227,6 +600,103 @@ mod tests {
         assert_eq!(cartridge.rom[0x0101], 195);
     }
 
+    #[test]
+    fn cartridge_can_be_created_from_bytes() {
+        let path = "test_roms/blargg/cpu_instrs/individual/06-ld r,r.gb";
+        let rom = std::fs::read(path).unwrap();
+        let cartridge = Cartridge::from_bytes(rom.clone()).unwrap();
+        let loaded_cartridge = Cartridge::load(path).unwrap();
+        assert_eq!(cartridge.title, loaded_cartridge.title);
+        assert_eq!(
+            cartridge.cartridge_checksum,
+            loaded_cartridge.cartridge_checksum
+        );
+        assert_eq!(cartridge.rom, rom);
+    }
+
+    #[test]
+    fn cgb_flag_is_parsed() {
+        let cartridge =
+            Cartridge::load("test_roms/blargg/interrupt_time/interrupt_time.gb").unwrap();
+        assert_eq!(cartridge.cgb_flag, CgbFlag::CgbOnly);
+        assert!(!cartridge.sgb_support);
+
+        let cartridge = Cartridge::new();
+        assert_eq!(cartridge.cgb_flag, CgbFlag::CgbEnhanced);
+
+        let mut rom = std::fs::read("test_roms/blargg/cpu_instrs/individual/06-ld r,r.gb").unwrap();
+        rom[CGB_FLAG_ADDRESS] = 0x00;
+        rom[SGB_FLAG_ADDRESS] = 0x03;
+        let cartridge = Cartridge::from_bytes(rom).unwrap();
+        assert_eq!(cartridge.cgb_flag, CgbFlag::DmgOnly);
+        assert!(cartridge.sgb_support);
+    }
+
+    #[test]
+    fn licensee_is_parsed() {
+        let cartridge = Cartridge::new();
+        assert_eq!(cartridge.licensee, "00");
+
+        let cartridge = Cartridge::load("test_roms/mooneye/emulator-only/mbc1/rom_4Mb.gb").unwrap();
+        assert_eq!(cartridge.licensee, "ZZ");
+    }
+
+    #[test]
+    fn truncated_rom_is_rejected() {
+        let rom = std::fs::read("test_roms/blargg/cpu_instrs/individual/06-ld r,r.gb").unwrap();
+        assert_eq!(
+            Cartridge::from_bytes(rom[..0x0140].to_vec()).err(),
+            Some(CartridgeError::Truncated { length: 0x0140 })
+        );
+        assert_eq!(
+            Cartridge::from_bytes(Vec::new()).err(),
+            Some(CartridgeError::Truncated { length: 0 })
+        );
+    }
+
+    #[test]
+    fn reads_past_the_end_of_small_roms_return_0xff() {
+        let mut rom = std::fs::read("test_roms/blargg/cpu_instrs/individual/06-ld r,r.gb").unwrap();
+        assert_eq!(rom.len(), 2 * ROM_BANK_SIZE);
+        rom[CARTRIDGE_TYPE_ADDRESS] = 0x01;
+        let mut memory = MemoryController::new();
+        memory.cartridge = Cartridge::from_bytes(rom.clone()).unwrap();
+        memory.cartridge.place_into_memory(&mut memory.memory);
+        assert_eq!(memory.read(0x7FFF), rom[0x7FFF]);
+
+        // Selecting a bank that does not exist does not panic
+        memory.write(0x2000, 0x03);
+        assert_eq!(memory.read(0x4000), 0xFF);
+        assert_eq!(memory.read(0x7FFF), 0xFF);
+        memory.write(0x2000, 0x01);
+        assert_eq!(memory.read(0x4000), rom[0x4000]);
+
+        // ROMs that are smaller than two banks are padded
+        let cartridge = Cartridge::from_bytes(rom[..0x0150].to_vec()).unwrap();
+        cartridge.place_into_memory(&mut memory.memory);
+        assert_eq!(memory.read(0x0100), rom[0x0100]);
+        assert_eq!(memory.read(0x0150), 0xFF);
+        assert_eq!(memory.read(0x4000), 0xFF);
+    }
+
+    #[test]
+    fn unknown_cartridge_type_is_rejected() {
+        let mut rom = std::fs::read("test_roms/blargg/cpu_instrs/individual/06-ld r,r.gb").unwrap();
+        rom[CARTRIDGE_TYPE_ADDRESS] = 0x04;
+        assert_eq!(
+            Cartridge::from_bytes(rom).err(),
+            Some(CartridgeError::UnknownCartridgeType(0x04))
+        );
+    }
+
+    #[test]
+    fn missing_rom_file_is_rejected() {
+        assert_eq!(
+            Cartridge::load("test_roms/does_not_exist.gb").err(),
+            Some(CartridgeError::ReadFailed(std::io::ErrorKind::NotFound))
+        );
+    }
+
     #[test]
     fn test_cartridge_has_correct_header() {
         let cartridge = Cartridge::new();
@@ -241,6 +711,94 @@ mod tests {
         assert!(check_result.is_ok());
     }
 
+    #[test]
+    fn cartridge_checksum_ignores_the_checksum_bytes() {
+        let mut cartridge = Cartridge::new();
+        let checksum = cartridge.compute_cartridge_checksum();
+        assert_eq!(checksum, cartridge.cartridge_checksum);
+
+        // Changing the stored checksum must not change the computed one
+        cartridge.rom[CARTRIDGE_CHECKSUM_MSB_ADDRESS] = 0x12;
+        cartridge.rom[CARTRIDGE_CHECKSUM_LSB_ADDRESS] = 0x34;
+        assert_eq!(cartridge.compute_cartridge_checksum(), checksum);
+        assert!(cartridge.check_cartridge_checksum().is_err());
+
+        let [msb, lsb] = checksum.to_be_bytes();
+        cartridge.rom[CARTRIDGE_CHECKSUM_MSB_ADDRESS] = msb;
+        cartridge.rom[CARTRIDGE_CHECKSUM_LSB_ADDRESS] = lsb;
+        assert!(cartridge.check_cartridge_checksum().is_ok());
+    }
+
+    #[test]
+    fn cartridge_checksum_wraps_around_and_uses_the_stored_byte_order() {
+        let mut cartridge = Cartridge::new();
+        // The sum of all bytes overflows 16 bits many times
+        cartridge.rom.fill(0xFF);
+        let other_bytes = cartridge.rom.len() as u32 - 2;
+        let expected = (other_bytes * 0xFF % 0x10000) as u16;
+        assert_eq!(cartridge.compute_cartridge_checksum(), expected);
+
+        // The checksum is stored big-endian, so swapping the bytes makes it invalid
+        let [msb, lsb] = expected.to_be_bytes();
+        assert_ne!(msb, lsb);
+        cartridge.rom[CARTRIDGE_CHECKSUM_MSB_ADDRESS] = lsb;
+        cartridge.rom[CARTRIDGE_CHECKSUM_LSB_ADDRESS] = msb;
+        assert!(cartridge.check_cartridge_checksum().is_err());
+
+        cartridge.rom[CARTRIDGE_CHECKSUM_MSB_ADDRESS] = msb;
+        cartridge.rom[CARTRIDGE_CHECKSUM_LSB_ADDRESS] = lsb;
+        assert!(cartridge.check_cartridge_checksum().is_ok());
+    }
+
+    #[test]
+    fn computed_checksums_make_a_patched_rom_valid() {
+        let mut rom = std::fs::read("test_roms/blargg/cpu_instrs/individual/06-ld r,r.gb").unwrap();
+        rom[TITLE_RANGE][..4].copy_from_slice(b"TEST");
+        rom[0x0200] = rom[0x0200].wrapping_add(1);
+        let cartridge = Cartridge::from_bytes(rom.clone()).unwrap();
+        assert!(cartridge.check_header_checksum().is_err());
+        assert!(cartridge.check_cartridge_checksum().is_err());
+
+        // ROMs without a complete header have no header checksum
+        assert_eq!(Cartridge::compute_header_checksum(&rom[..0x014C]), None);
+
+        // The cartridge checksum covers the header checksum, so it has to be computed last
+        rom[HEADER_CHECKSUM_ADDRESS] = Cartridge::compute_header_checksum(&rom).unwrap();
+        let [msb, lsb] = Cartridge::compute_rom_checksum(&rom).to_be_bytes();
+        rom[CARTRIDGE_CHECKSUM_MSB_ADDRESS] = msb;
+        rom[CARTRIDGE_CHECKSUM_LSB_ADDRESS] = lsb;
+        let cartridge = Cartridge::from_bytes(rom).unwrap();
+        assert!(cartridge.check_header_checksum().is_ok());
+        assert!(cartridge.check_cartridge_checksum().is_ok());
+    }
+
+    #[test]
+    fn cartridge_without_ram_ignores_external_ram() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.cartridge_type = CartridgeType::RomOnly;
+        assert_eq!(memory.cartridge.ram_size, 0);
+
+        memory.write(0x0000, 0x0A);
+        memory.write(0xA000, 0x12);
+        memory.write(0xBFFF, 0x34);
+        assert_eq!(memory.read(0xA000), 0xFF);
+        assert_eq!(memory.read(0xBFFF), 0xFF);
+        assert_eq!(memory.memory.data[0xA000], 0);
+    }
+
+    #[test]
+    fn mbc1_cartridge_without_ram_ignores_external_ram() {
+        let mut memory = MemoryController::new();
+        assert!(matches!(
+            memory.cartridge.cartridge_type,
+            CartridgeType::Mbc1
+        ));
+
+        memory.write(0x0000, 0x0A);
+        memory.write(0xA000, 0x12);
+        assert_eq!(memory.read(0xA000), 0xFF);
+    }
+
     #[test]
     fn test_cartridge_can_be_placed_in_memory() {
         let cartridge = Cartridge::new();
@@ -249,4 +807,238 @@ mod tests {
         assert_eq!(memory.read(0x0100), 0);
         assert_eq!(memory.read(0x0101), 195);
     }
+
+    #[test]
+    fn mbc3_switches_rom_banks() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.cartridge_type = CartridgeType::Mbc3;
+        memory.cartridge.rom = numbered_rom(128);
+
+        memory.write(0x2000, 0x05);
+        assert_eq!(memory.read(0x4000), 0x05);
+
+        // All 7 bits are used
+        memory.write(0x3FFF, 0x7F);
+        assert_eq!(memory.read(0x4000), 0x7F);
+
+        // Bank 0 selects bank 1
+        memory.write(0x2000, 0x00);
+        assert_eq!(memory.read(0x4000), 0x01);
+    }
+
+    #[test]
+    fn mbc3_switches_ram_banks_and_clock_registers() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.cartridge_type = CartridgeType::Mbc3TimerRamBattery;
+        memory.cartridge.ram_size = 1 << 15;
+        memory.cartridge.ram = vec![0; 1 << 15];
+
+        memory.write(0x0000, 0x0A);
+        memory.write(0x4000, 0x01);
+        memory.write(0xA000, 0x11);
+        memory.write(0x4000, 0x02);
+        assert_eq!(memory.read(0xA000), 0x00);
+        memory.write(0xA000, 0x22);
+
+        // Select the minutes register
+        memory.write(0x4000, 0x09);
+        memory.write(0xA000, 42);
+        assert_eq!(memory.read(0xA000), 42);
+        assert_eq!(memory.cartridge.rtc_registers[1], 42);
+
+        memory.write(0x4000, 0x01);
+        assert_eq!(memory.read(0xA000), 0x11);
+        memory.write(0x4000, 0x02);
+        assert_eq!(memory.read(0xA000), 0x22);
+    }
+
+    #[test]
+    fn mbc5_selects_banks_above_0xff() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.cartridge_type = CartridgeType::Mbc5;
+        memory.cartridge.rom = numbered_rom(0x101);
+
+        memory.write(0x2000, 0x00);
+        memory.write(0x3000, 0x01);
+        assert_eq!(memory.read(0x4000), 0x00);
+        assert_eq!(memory.read(0x4001), 0x01);
+        assert_eq!(memory.cartridge.current_second_rom_bank, 0x100);
+
+        memory.write(0x3000, 0x00);
+        memory.write(0x2000, 0x42);
+        assert_eq!(memory.read(0x4000), 0x42);
+        assert_eq!(memory.read(0x4001), 0x00);
+
+        // Bank 0 can be mapped to the second bank area
+        memory.write(0x2000, 0x00);
+        assert_eq!(memory.read(0x4000), 0x00);
+        assert_eq!(memory.read(0x4001), 0x00);
+    }
+
+    #[test]
+    fn mbc2_selects_rom_banks_with_address_bit_8() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.cartridge_type = CartridgeType::Mbc2;
+        memory.cartridge.rom = numbered_rom(16);
+
+        memory.write(0x2100, 0x03);
+        assert_eq!(memory.read(0x4000), 0x03);
+
+        // Bit 8 is not set, so this enables RAM instead of selecting a bank
+        memory.write(0x2000, 0x0A);
+        assert_eq!(memory.read(0x4000), 0x03);
+        assert!(memory.cartridge.external_ram_enabled);
+
+        // Only 4 bits are used
+        memory.write(0x0100, 0x1F);
+        assert_eq!(memory.read(0x4000), 0x0F);
+
+        memory.write(0x0100, 0x00);
+        assert_eq!(memory.read(0x4000), 0x01);
+    }
+
+    #[test]
+    fn mbc2_ram_only_stores_the_lower_nibble() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.cartridge_type = CartridgeType::Mbc2;
+        memory.cartridge.ram = vec![0; 512];
+
+        memory.write(0x0000, 0x0A);
+        memory.write(0xA000, 0xFF);
+        assert_eq!(memory.read(0xA000), 0x0F);
+
+        // The 512 half-bytes are repeated in the whole area
+        assert_eq!(memory.read(0xA200), 0x0F);
+        memory.write(0xBFFF, 0x35);
+        assert_eq!(memory.read(0xA1FF), 0x05);
+
+        memory.write(0x0000, 0x00);
+        assert_eq!(memory.read(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn ram_can_be_dumped_and_loaded() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.cartridge_type = CartridgeType::Mbc3RamBattery;
+        memory.cartridge.ram_size = 1 << 15;
+        memory.cartridge.ram = vec![0; 1 << 15];
+
+        memory.write(0x0000, 0x0A);
+        memory.write(0xA000, 0x11);
+        memory.write(0x4000, 0x03);
+        memory.write(0xBFFF, 0x33);
+
+        let ram = memory.cartridge.dump_ram();
+        assert_eq!(ram.len(), 1 << 15);
+        assert_eq!(ram[0], 0x11);
+        assert_eq!(ram[(1 << 15) - 1], 0x33);
+
+        memory.write(0xBFFF, 0x00);
+        memory.write(0x4000, 0x00);
+        memory.write(0xA000, 0x00);
+
+        memory
+            .cartridge
+            .load_ram(&ram)
+            .expect("RAM has the right size");
+        assert_eq!(memory.read(0xA000), 0x11);
+        memory.write(0x4000, 0x03);
+        assert_eq!(memory.read(0xBFFF), 0x33);
+
+        assert_eq!(memory.cartridge.load_ram(&ram[1..]), Err(()));
+    }
+
+    #[test]
+    fn clock_registers_are_saved_after_the_ram() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.cartridge_type = CartridgeType::Mbc3TimerRamBattery;
+        memory.cartridge.ram_size = 1 << 13;
+        memory.cartridge.ram = vec![0; 1 << 13];
+
+        memory.write(0x0000, 0x0A);
+        memory.write(0xA000, 0x11);
+        // Set the seconds and the upper day counter bit
+        memory.write(0x4000, 0x08);
+        memory.write(0xA000, 42);
+        memory.write(0x4000, 0x0C);
+        memory.write(0xA000, 0x01);
+
+        let ram = memory.cartridge.dump_ram();
+        assert_eq!(ram.len(), (1 << 13) + 48);
+        assert_eq!(ram.len(), memory.cartridge.saved_ram_size());
+        assert_eq!(ram[0], 0x11);
+        // The current and the latched registers are stored as 32 bit values
+        assert_eq!(ram[1 << 13..(1 << 13) + 4], [42, 0, 0, 0]);
+        assert_eq!(ram[(1 << 13) + 16..(1 << 13) + 20], [0x01, 0, 0, 0]);
+        assert_eq!(ram[(1 << 13) + 20..(1 << 13) + 24], [42, 0, 0, 0]);
+
+        memory.cartridge.rtc_registers = [0; 5];
+        memory
+            .cartridge
+            .load_ram(&ram)
+            .expect("RAM has the right size");
+        assert_eq!(memory.cartridge.rtc_registers, [42, 0, 0, 0, 0x01]);
+        assert_eq!(memory.read(0xA000), 0x01);
+        memory.write(0x4000, 0x00);
+        assert_eq!(memory.read(0xA000), 0x11);
+
+        // Saves without the clock are rejected
+        assert_eq!(memory.cartridge.load_ram(&ram[..1 << 13]), Err(()));
+    }
+
+    #[test]
+    fn disabled_external_ram_can_not_be_accessed() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.cartridge_type = CartridgeType::Mbc1RamBattery;
+        memory.cartridge.ram_size = 1 << 13;
+        memory.cartridge.ram = vec![0; 1 << 13];
+        assert!(!memory.external_ram_enabled());
+
+        memory.write(0xA000, 0x12);
+        assert_eq!(memory.read(0xA000), 0xFF);
+
+        memory.write(0x0000, 0x0A);
+        assert!(memory.external_ram_enabled());
+        assert_eq!(memory.read(0xA000), 0x00);
+        memory.write(0xA000, 0x34);
+        assert_eq!(memory.read(0xA000), 0x34);
+
+        // The contents are kept while the RAM is disabled
+        memory.write(0x0000, 0x00);
+        assert_eq!(memory.read(0xA000), 0xFF);
+        memory.write(0xA000, 0x56);
+        memory.write(0x0000, 0x0A);
+        assert_eq!(memory.read(0xA000), 0x34);
+        assert_eq!(memory.current_ram_bank(), 0);
+    }
+
+    #[test]
+    fn mbc1_switches_ram_banks_in_advanced_banking_mode() {
+        let mut memory = MemoryController::new();
+        memory.cartridge.cartridge_type = CartridgeType::Mbc1RamBattery;
+        memory.cartridge.ram_size = 1 << 15;
+        memory.cartridge.ram = vec![0; 1 << 15];
+
+        memory.write(0x0000, 0x0A);
+        memory.write(0x6000, 0x01);
+        let rom_value = memory.read(0x4000);
+        memory.write(0x4000, 0x01);
+        assert_eq!(memory.cartridge.current_ram_bank, 1);
+        memory.write(0xA000, 0x11);
+        memory.write(0x4000, 0x02);
+        assert_eq!(memory.read(0xA000), 0x00);
+        memory.write(0xA000, 0x22);
+
+        memory.write(0x4000, 0x01);
+        assert_eq!(memory.read(0xA000), 0x11);
+        memory.write(0x4000, 0x02);
+        assert_eq!(memory.read(0xA000), 0x22);
+        // The ROM bank is not changed by selecting RAM banks
+        assert_eq!(memory.read(0x4000), rom_value);
+
+        // Simple banking mode always uses the first RAM bank
+        memory.write(0x6000, 0x00);
+        assert_eq!(memory.cartridge.current_ram_bank, 0);
+        assert_eq!(memory.read(0xA000), 0x00);
+    }
 }
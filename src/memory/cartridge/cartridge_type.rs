@@ -1,3 +1,5 @@
+use super::CartridgeError;
+
 /// Indicates what kind of hardware is present on the cartridge
 pub enum CartridgeType {
     /// RomOnly
@@ -58,9 +60,48 @@ pub enum CartridgeType {
     Huc1RamBattery,
 }
 
-impl Into<CartridgeType> for u8 {
-    fn into(self) -> CartridgeType {
-        match self {
+impl CartridgeType {
+    /// Check if the cartridge has a battery that keeps its RAM (and clock) contents when the gameboy is turned off
+    pub fn has_battery(&self) -> bool {
+        matches!(
+            self,
+            CartridgeType::Mbc1RamBattery
+                | CartridgeType::Mbc2Battery
+                | CartridgeType::RomRamBattery
+                | CartridgeType::Mmm01RamBattery
+                | CartridgeType::Mbc3TimerBattery
+                | CartridgeType::Mbc3TimerRamBattery
+                | CartridgeType::Mbc3RamBattery
+                | CartridgeType::Mbc5RamBattery
+                | CartridgeType::Mbc5RumbleRamBattery
+                | CartridgeType::Mbc7SensorRumbleRamBattery
+                | CartridgeType::Huc1RamBattery
+        )
+    }
+    /// Check if the cartridge has a real time clock
+    pub fn has_timer(&self) -> bool {
+        matches!(
+            self,
+            CartridgeType::Mbc3TimerBattery | CartridgeType::Mbc3TimerRamBattery
+        )
+    }
+    /// Check if the cartridge has a rumble motor
+    pub fn has_rumble(&self) -> bool {
+        matches!(
+            self,
+            CartridgeType::Mbc5Rumble
+                | CartridgeType::Mbc5RumbleRam
+                | CartridgeType::Mbc5RumbleRamBattery
+                | CartridgeType::Mbc7SensorRumbleRamBattery
+        )
+    }
+}
+
+impl TryFrom<u8> for CartridgeType {
+    type Error = CartridgeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        let cartridge_type = match value {
             0x00 => CartridgeType::RomOnly,
             0x01 => CartridgeType::Mbc1,
             0x02 => CartridgeType::Mbc1Ram,
@@ -89,7 +130,8 @@ impl Into<CartridgeType> for u8 {
             0xFD => CartridgeType::BandaiTama5,
             0xFE => CartridgeType::Huc3,
             0xFF => CartridgeType::Huc1RamBattery,
-            _ => panic!("Invalid value for the cartridge type"),
-        }
+            _ => return Err(CartridgeError::UnknownCartridgeType(value)),
+        };
+        Ok(cartridge_type)
     }
 }
@@ -0,0 +1,21 @@
+/// Whether a game uses the features of the Game Boy Color
+#[derive(Debug, PartialEq, Clone)]
+pub enum CgbFlag {
+    /// The game was made for the original gameboy. Older games have a part of the title in this byte.
+    DmgOnly,
+    /// The game uses Game Boy Color features, but works on the original gameboy as well
+    CgbEnhanced,
+    /// The game only works on the Game Boy Color
+    CgbOnly,
+}
+
+impl From<u8> for CgbFlag {
+    fn from(value: u8) -> Self {
+        match value {
+            0xC0 => CgbFlag::CgbOnly,
+            // Only bit 7 is checked by the hardware
+            0x80..=0xFF => CgbFlag::CgbEnhanced,
+            _ => CgbFlag::DmgOnly,
+        }
+    }
+}
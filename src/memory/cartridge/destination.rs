@@ -1,3 +1,5 @@
+use super::CartridgeError;
+
 /// Whether a version of the game is intended to be sold in Japan or elsewhere.
 pub enum Destination {
     /// Japan (and possibly overseas)
@@ -6,12 +8,14 @@ pub enum Destination {
     OverseasOnly,
 }
 
-impl Into<Destination> for u8 {
-    fn into(self) -> Destination {
-        match self {
-            0 => Destination::Japan,
-            1 => Destination::OverseasOnly,
-            _ => panic!("Invalid value for the cartridge destination"),
+impl TryFrom<u8> for Destination {
+    type Error = CartridgeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Destination::Japan),
+            1 => Ok(Destination::OverseasOnly),
+            _ => Err(CartridgeError::UnknownDestination(value)),
         }
     }
 }
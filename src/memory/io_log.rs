@@ -0,0 +1,17 @@
+use super::memory_addresses::{INTERRUPT_ENABLE_ADDRESS, IO_REGISTER_AREA};
+
+/// A single write to a hardware IO register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoWrite {
+    /// The number of machine cycles the memory had processed before the write
+    pub cycle: u64,
+    /// The address of the register
+    pub address: u16,
+    /// The written value
+    pub value: u8,
+}
+
+/// Check if writes to `address` are recorded in the IO log
+pub fn is_io_register(address: usize) -> bool {
+    IO_REGISTER_AREA.contains(&address) || address == INTERRUPT_ENABLE_ADDRESS
+}
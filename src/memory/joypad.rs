@@ -0,0 +1,239 @@
+use super::{memory_addresses::JOYPAD_ADDRESS, Memory};
+use crate::cpu::Interrupt;
+
+/// A button on the gameboy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    /// The right button of the direction pad
+    Right,
+    /// The left button of the direction pad
+    Left,
+    /// The up button of the direction pad
+    Up,
+    /// The down button of the direction pad
+    Down,
+    /// The A button
+    A,
+    /// The B button
+    B,
+    /// The select button
+    Select,
+    /// The start button
+    Start,
+}
+
+impl Button {
+    /// Check if the button belongs to the direction group
+    pub fn is_direction(&self) -> bool {
+        matches!(
+            self,
+            Button::Right | Button::Left | Button::Up | Button::Down
+        )
+    }
+    /// The bit of the button in the lower nibble of the joypad register
+    fn mask(&self) -> u8 {
+        match self {
+            Button::Right | Button::A => 0b0001,
+            Button::Left | Button::B => 0b0010,
+            Button::Up | Button::Select => 0b0100,
+            Button::Down | Button::Start => 0b1000,
+        }
+    }
+}
+
+/// Represents the joypad register at [JOYPAD_ADDRESS](super::memory_addresses::JOYPAD_ADDRESS).
+///
+/// The buttons are arranged in two groups of four. The game selects one (or both) groups by writing a `0` to bit 4 (directions) or bit 5 (actions).
+/// The lower nibble then contains the state of the selected buttons, where `0` means pressed.
+/// If no group is selected, the lower nibble reads as `0xF`. The upper two bits are unused and always read as `1`.
+///
+/// See <https://gbdev.io/pandocs/Joypad_Input.html> for more details.
+#[derive(Clone)]
+pub struct Joypad {
+    /// Bits 4 and 5 of the last value written to the register
+    selection: u8,
+    /// The pressed direction buttons. A `1` means the button is pressed.
+    pressed_directions: u8,
+    /// The pressed action buttons. A `1` means the button is pressed.
+    pressed_actions: u8,
+}
+
+const SELECT_DIRECTIONS: u8 = 0b00010000;
+const SELECT_ACTIONS: u8 = 0b00100000;
+const UNUSED_BITS: u8 = 0b11000000;
+
+impl Joypad {
+    /// Create a new joypad with no buttons pressed and no group selected
+    pub fn new() -> Joypad {
+        Joypad {
+            selection: SELECT_DIRECTIONS | SELECT_ACTIONS,
+            pressed_directions: 0,
+            pressed_actions: 0,
+        }
+    }
+
+    /// Press or release a button.
+    ///
+    /// Requests a [Interrupt::Joypad] when one of the lines in the lower nibble of the register goes from high to low.
+    /// This only happens if the group of the pressed button is selected and no other selected button on the same line is already pressed.
+    pub fn set_button(&mut self, memory: &mut Memory, button: Button, pressed: bool) {
+        let previous_lines = self.read();
+        let buttons = if button.is_direction() {
+            &mut self.pressed_directions
+        } else {
+            &mut self.pressed_actions
+        };
+        if pressed {
+            *buttons |= button.mask();
+        } else {
+            *buttons &= !button.mask();
+        }
+        let falling_lines = previous_lines & !self.read() & 0x0F;
+        if falling_lines != 0 {
+            memory.write_interrupt_flag(Interrupt::Joypad, true);
+        }
+    }
+
+    /// Check if a button is currently pressed
+    pub fn is_pressed(&self, button: Button) -> bool {
+        let buttons = if button.is_direction() {
+            self.pressed_directions
+        } else {
+            self.pressed_actions
+        };
+        buttons & button.mask() != 0
+    }
+
+    /// Get the current value of the joypad register
+    pub fn read(&self) -> u8 {
+        let mut pressed = 0;
+        if self.selection & SELECT_DIRECTIONS == 0 {
+            pressed |= self.pressed_directions;
+        }
+        if self.selection & SELECT_ACTIONS == 0 {
+            pressed |= self.pressed_actions;
+        }
+        UNUSED_BITS | self.selection | (!pressed & 0x0F)
+    }
+
+    /// Process writes to the memory
+    pub fn write(&mut self, address: u16, value: u8) -> Option<()> {
+        match address as usize {
+            JOYPAD_ADDRESS => {
+                self.selection = value & (SELECT_DIRECTIONS | SELECT_ACTIONS);
+                Some(())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for Joypad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Button;
+    use crate::cpu::{interrupt_controller::InterruptController, Interrupt};
+    use crate::memory::{memory_addresses::JOYPAD_ADDRESS, MemoryController, MemoryDevice};
+
+    #[test]
+    fn deselecting_both_groups_reads_all_released() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(JOYPAD_ADDRESS as u16, 0x30);
+        let value = memory.read(JOYPAD_ADDRESS as u16);
+        assert_eq!(value & 0x0F, 0x0F);
+        assert_eq!(value & 0xC0, 0xC0);
+    }
+
+    #[test]
+    fn unused_bits_always_read_as_one() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(JOYPAD_ADDRESS as u16, 0x00);
+        assert_eq!(memory.read(JOYPAD_ADDRESS as u16), 0xCF);
+        memory.write(JOYPAD_ADDRESS as u16, 0x10);
+        assert_eq!(memory.read(JOYPAD_ADDRESS as u16), 0xDF);
+        memory.write(JOYPAD_ADDRESS as u16, 0x20);
+        assert_eq!(memory.read(JOYPAD_ADDRESS as u16), 0xEF);
+    }
+
+    #[test]
+    fn pressed_action_button_reads_as_zero() {
+        let mut memory = MemoryController::new_for_tests();
+        memory
+            .joypad
+            .set_button(&mut memory.memory, Button::A, true);
+
+        // Select the action buttons
+        memory.write(JOYPAD_ADDRESS as u16, 0x10);
+        assert_eq!(memory.read(JOYPAD_ADDRESS as u16), 0xDE);
+
+        // A is not visible when only the directions are selected
+        memory.write(JOYPAD_ADDRESS as u16, 0x20);
+        assert_eq!(memory.read(JOYPAD_ADDRESS as u16), 0xEF);
+
+        memory
+            .joypad
+            .set_button(&mut memory.memory, Button::A, false);
+        memory.write(JOYPAD_ADDRESS as u16, 0x10);
+        assert_eq!(memory.read(JOYPAD_ADDRESS as u16), 0xDF);
+    }
+
+    #[test]
+    fn pressing_a_button_requests_an_interrupt() {
+        let mut memory = MemoryController::new_for_tests();
+        // Select the directions
+        memory.write(JOYPAD_ADDRESS as u16, 0x20);
+        memory
+            .joypad
+            .set_button(&mut memory.memory, Button::Down, true);
+        assert!(memory.read_interrupt_flag(Interrupt::Joypad));
+        assert!(memory.joypad.is_pressed(Button::Down));
+
+        // Holding or releasing a button does not request another interrupt
+        memory.write_interrupt_flag(Interrupt::Joypad, false);
+        memory
+            .joypad
+            .set_button(&mut memory.memory, Button::Down, true);
+        memory
+            .joypad
+            .set_button(&mut memory.memory, Button::Down, false);
+        assert!(!memory.read_interrupt_flag(Interrupt::Joypad));
+    }
+
+    #[test]
+    fn pressing_a_button_of_a_deselected_group_does_not_request_an_interrupt() {
+        let mut memory = MemoryController::new_for_tests();
+        // Select the actions
+        memory.write(JOYPAD_ADDRESS as u16, 0x10);
+        memory
+            .joypad
+            .set_button(&mut memory.memory, Button::Down, true);
+        assert!(!memory.read_interrupt_flag(Interrupt::Joypad));
+        assert!(memory.joypad.is_pressed(Button::Down));
+
+        memory
+            .joypad
+            .set_button(&mut memory.memory, Button::Start, true);
+        assert!(memory.read_interrupt_flag(Interrupt::Joypad));
+    }
+
+    #[test]
+    fn no_interrupt_is_requested_if_the_line_is_already_low() {
+        let mut memory = MemoryController::new_for_tests();
+        // Select both groups, Down and Start share a line
+        memory.write(JOYPAD_ADDRESS as u16, 0x00);
+        memory
+            .joypad
+            .set_button(&mut memory.memory, Button::Down, true);
+        memory.write_interrupt_flag(Interrupt::Joypad, false);
+
+        memory
+            .joypad
+            .set_button(&mut memory.memory, Button::Start, true);
+        assert!(!memory.read_interrupt_flag(Interrupt::Joypad));
+    }
+}
@@ -8,6 +8,12 @@ pub const FIRST_ROM_BANK: RangeInclusive<usize> = 0x0000..=0x3FFF;
 pub const SECOND_ROM_BANK: RangeInclusive<usize> = 0x4000..=0x7FFF;
 /// The cartridge RAM is accessible here.
 pub const EXTERNAL_RAM_BANK: RangeInclusive<usize> = 0xA000..=0xBFFF;
+/// The size of a cartridge RAM bank
+pub const RAM_BANK_SIZE: usize = 0x2000;
+/// The work RAM built into the gameboy
+pub const WORK_RAM_AREA: RangeInclusive<usize> = 0xC000..=0xDFFF;
+/// Mirrors the first `0x1E00` bytes of the [WORK_RAM_AREA]
+pub const ECHO_RAM_AREA: RangeInclusive<usize> = 0xE000..=0xFDFF;
 
 /// The first area that can be used as tile data for the window and background layer.
 ///
@@ -16,9 +22,16 @@ pub const FIRST_BG_TILE_DATA_AREA: RangeInclusive<usize> = 0x8000..=0x8FFF;
 /// The second area that can be used as tile data for the window and background layer
 pub const SECOND_BG_TILE_DATA_AREA: RangeInclusive<usize> = 0x8800..=0x97FF;
 
+/// The whole area containing tile data. Covers both [FIRST_BG_TILE_DATA_AREA] and [SECOND_BG_TILE_DATA_AREA].
+pub const TILE_DATA_AREA: RangeInclusive<usize> = 0x8000..=0x97FF;
+
 /// The area containing the tile data for the objects layer
 pub const OBJECT_TILE_DATA_AREA: RangeInclusive<usize> = 0x8000..=0x8FFF;
 
+/// The whole video RAM, containing the tile data and the tile maps. It can not be accessed while the PPU draws a line.
+#[doc(alias = "VRAM")]
+pub const VIDEO_RAM_AREA: RangeInclusive<usize> = 0x8000..=0x9FFF;
+
 /// The area containing the object attribute memory
 #[doc(alias = "OAM")]
 pub const OBJECT_ATTRIBUTE_MEMORY_AREA: RangeInclusive<usize> = 0xFE00..=0xFE9F;
@@ -34,6 +47,12 @@ pub const CARTRIDGE_HEADER_RANGE: RangeInclusive<usize> = 0x0134..=0x014C;
 /// In older games the next byte is also part of the title.
 /// The cartridge title is stored here
 pub const TITLE_RANGE: RangeInclusive<usize> = 0x0134..=0x0142;
+/// This byte indicates whether the game supports or requires the Game Boy Color. See [CgbFlag] for the possible values
+pub const CGB_FLAG_ADDRESS: usize = 0x0143;
+/// Contains a two character ASCII code of the publisher of the game. Only used if the [OLD_LICENSEE_CODE_ADDRESS] is `0x33`.
+pub const NEW_LICENSEE_CODE_RANGE: RangeInclusive<usize> = 0x0144..=0x0145;
+/// This byte is `0x03` if the game supports Super Game Boy functions
+pub const SGB_FLAG_ADDRESS: usize = 0x0146;
 /// This byte indicates what kind of hardware is present on the cartridge. See [CartridgeType] for the possible values
 pub const CARTRIDGE_TYPE_ADDRESS: usize = 0x0147;
 /// This byte indicates how much ROM is present on the cartridge. See [decode_rom_size] for the possible values.
@@ -42,6 +61,8 @@ pub const ROM_SIZE_ADDRESS: usize = 0x0148;
 pub const RAM_SIZE_ADDRESS: usize = 0x0149;
 /// This byte specifies whether this version of the game is intended to be sold in Japan or elsewhere. See [Destination] for the possible values.
 pub const DESTINATION_COUNTRY_ADDRESS: usize = 0x014A;
+/// This byte contains the publisher of older games. `0x33` means that the [NEW_LICENSEE_CODE_RANGE] is used instead.
+pub const OLD_LICENSEE_CODE_ADDRESS: usize = 0x014B;
 /// This byte indicates the version of the ROM. It is usually set to 0.
 pub const ROM_VERSION_ADDRESS: usize = 0x014C;
 /// This byte contains an 8-bit checksum computed from the cartridge header bytes. You can check how the checksum is calculated in the implementation of [Cartridge::check_header_checksum]
@@ -55,6 +76,12 @@ pub const CARTRIDGE_CHECKSUM_MSB_ADDRESS: usize = 0x014E;
 /// The checksum is computed as the sum of all the bytes of the cartridge ROM (except these two checksum bytes). Our implementation of that is at [Cartridge::check_cartridge_checksum]
 pub const CARTRIDGE_CHECKSUM_LSB_ADDRESS: usize = 0x014F;
 
+/// Selects the button group and contains the state of the selected buttons.
+///
+/// See [Joypad](super::joypad::Joypad) for details
+#[doc(alias = "P1")]
+#[doc(alias = "JOYP")]
+pub const JOYPAD_ADDRESS: usize = 0xFF00;
 /// Contains the serial data.
 ///
 /// Before a transfer it contains the data being send, which is replaced with the received data during/after transfer.
@@ -86,6 +113,12 @@ pub const LCD_CONTROL_ADDRESS: usize = 0xFF40;
 /// The current LCD status is stored here
 #[doc(alias = "STAT")]
 pub const LCD_STATUS_ADDRESS: usize = 0xFF41;
+/// The vertical position of the visible area in the 256x256 pixel background map
+#[doc(alias = "SCY")]
+pub const SCROLL_Y_ADDRESS: usize = 0xFF42;
+/// The horizontal position of the visible area in the 256x256 pixel background map
+#[doc(alias = "SCX")]
+pub const SCROLL_X_ADDRESS: usize = 0xFF43;
 /// The currently rendering line is stored here. Read only.
 #[doc(alias = "LY")]
 pub const CURRENT_LINE_ADDRESS: usize = 0xFF44;
@@ -106,13 +139,67 @@ pub const FIRST_OBJECT_PALETTE_ADDRESS: usize = 0xFF48;
 /// Write here to set the second [Palette] for the object layer
 #[doc(alias = "OBP2")]
 pub const SECOND_OBJECT_PALETTE_ADDRESS: usize = 0xFF49;
+/// The first line of the screen on which the window is visible
+#[doc(alias = "WY")]
+pub const WINDOW_Y_ADDRESS: usize = 0xFF4A;
+/// The horizontal position of the window on screen + 7
+#[doc(alias = "WX")]
+pub const WINDOW_X_ADDRESS: usize = 0xFF4B;
 
-/// This address should always read `0xff`.
+/// Prepares a switch between normal and double speed mode on the CGB.
+///
+/// The register is only mapped in CGB mode, see [MemoryController::set_cgb_mode](super::MemoryController::set_cgb_mode).
+/// See [SpeedSwitch](super::speed_switch::SpeedSwitch) for details
+#[doc(alias = "KEY1")]
+pub const SPEED_SWITCH_ADDRESS: usize = 0xFF4D;
+
+/// Selects the byte of the CGB background palette memory that is accessed through [BACKGROUND_PALETTE_DATA_ADDRESS].
+///
+/// See [ColorPaletteMemory](super::video::color_palette_memory::ColorPaletteMemory) for details
+#[doc(alias = "BCPS")]
+pub const BACKGROUND_PALETTE_SPECIFICATION_ADDRESS: usize = 0xFF68;
+/// Accesses the byte of the CGB background palette memory selected by [BACKGROUND_PALETTE_SPECIFICATION_ADDRESS]
+#[doc(alias = "BCPD")]
+pub const BACKGROUND_PALETTE_DATA_ADDRESS: usize = 0xFF69;
+/// Selects the byte of the CGB object palette memory that is accessed through [OBJECT_PALETTE_DATA_ADDRESS].
+///
+/// See [ColorPaletteMemory](super::video::color_palette_memory::ColorPaletteMemory) for details
+#[doc(alias = "OCPS")]
+pub const OBJECT_PALETTE_SPECIFICATION_ADDRESS: usize = 0xFF6A;
+/// Accesses the byte of the CGB object palette memory selected by [OBJECT_PALETTE_SPECIFICATION_ADDRESS]
+#[doc(alias = "OCPD")]
+pub const OBJECT_PALETTE_DATA_ADDRESS: usize = 0xFF6B;
+
+/// Selects the VRAM bank the CPU accesses on the CGB. Only bit 0 is used, the other bits always read as `1`.
+///
+/// The second bank contains the tile attributes. The register is only mapped in CGB mode, see [MemoryController::set_cgb_mode](super::MemoryController::set_cgb_mode).
+#[doc(alias = "VBK")]
+pub const VIDEO_RAM_BANK_ADDRESS: usize = 0xFF4F;
+
+/// The hardware IO registers. [INTERRUPT_ENABLE_ADDRESS] is an IO register as well, but it is not part of this area.
+pub const IO_REGISTER_AREA: RangeInclusive<usize> = 0xFF00..=0xFF7F;
+
+/// The small high RAM. While an OAM DMA transfer is running, this and the IO registers are the only memory the CPU can access.
+#[doc(alias = "HRAM")]
+pub const HIGH_RAM_AREA: RangeInclusive<usize> = 0xFF80..=0xFFFE;
+
+/// Most IO registers in this area are not used on the DMG.
+///
+/// Reads from unused registers return `0xFF` and writes to them are ignored. Registers that are actually implemented are listed in [MAPPED_IO_GAP_ADDRESSES].
 ///
 /// I got that info from https://www.reddit.com/r/EmuDev/comments/ipap0w/comment/g76m04i
 ///
 /// Apparently there are tests in the mooneye test suite that verify the correct values for all IO registers.
-pub const ALWAYS_RETURNS_FF_ADDRESS: usize = 0xFF4D;
+pub const UNMAPPED_IO_AREA: RangeInclusive<usize> = 0xFF4D..=0xFF7F;
+/// The registers in [UNMAPPED_IO_AREA] that are implemented and behave like normal memory or are handled by a peripheral.
+pub const MAPPED_IO_GAP_ADDRESSES: &[usize] = &[
+    SPEED_SWITCH_ADDRESS,
+    VIDEO_RAM_BANK_ADDRESS,
+    BACKGROUND_PALETTE_SPECIFICATION_ADDRESS,
+    BACKGROUND_PALETTE_DATA_ADDRESS,
+    OBJECT_PALETTE_SPECIFICATION_ADDRESS,
+    OBJECT_PALETTE_DATA_ADDRESS,
+];
 
 /// Address for the interrupt flags register.
 pub const INTERRUPT_FLAG_ADDRESS: usize = 0xFF0F;
@@ -12,14 +12,14 @@ use super::{
 /// Contains traits for serial connections and some implementations
 pub mod serial_connection;
 
-#[derive(TryFromPrimitive, Debug, IntoPrimitive, PartialEq)]
+#[derive(TryFromPrimitive, Debug, IntoPrimitive, PartialEq, Clone)]
 #[repr(u8)]
 enum ClockType {
     External = 0,
     Internal = 1,
 }
 
-#[derive(TryFromPrimitive, Debug, IntoPrimitive, PartialEq)]
+#[derive(TryFromPrimitive, Debug, IntoPrimitive, PartialEq, Clone)]
 #[repr(u8)]
 enum TransactionState {
     /// Neither sending nor receiving.
@@ -27,17 +27,50 @@ enum TransactionState {
     InProgress = 1,
 }
 
+#[derive(TryFromPrimitive, Debug, IntoPrimitive, PartialEq, Clone)]
+#[repr(u8)]
+enum ClockSpeed {
+    /// 8192 Hz
+    Normal = 0,
+    /// 262144 Hz. Only available on the CGB.
+    Fast = 1,
+}
+
+impl ClockSpeed {
+    /// The number of cycles it takes to transfer one bit
+    fn cycles_per_bit(&self) -> u32 {
+        match self {
+            ClockSpeed::Normal => CYCLES_PER_BIT,
+            ClockSpeed::Fast => FAST_CYCLES_PER_BIT,
+        }
+    }
+}
+
 /// Represents a serial connection
 pub struct Serial<T: SerialConnection> {
     connection: Option<T>,
     transferred_bits: usize,
     clock_source: ClockType,
+    clock_speed: ClockSpeed,
+    instant_transfers: bool,
+    transaction_state: TransactionState,
+    cycles_until_next_bit: u32,
+}
+
+/// The internal state of a [Serial], without the connection. Used for savestates.
+#[derive(Clone)]
+pub struct SerialState {
+    transferred_bits: usize,
+    clock_source: ClockType,
+    clock_speed: ClockSpeed,
     transaction_state: TransactionState,
     cycles_until_next_bit: u32,
 }
 
 /// The gameboy CPU runs at 1048576 Hz, the transfer speed is 8192 Hz. So 1 bit gets transferred per 128 cycles.
 const CYCLES_PER_BIT: u32 = 128;
+/// With the fast clock the transfer speed is 262144 Hz. So 1 bit gets transferred per 4 cycles.
+const FAST_CYCLES_PER_BIT: u32 = 4;
 
 impl<T: SerialConnection> Serial<T> {
     /// Create a new serial connection that logs the output to the console.
@@ -46,23 +79,73 @@ impl<T: SerialConnection> Serial<T> {
             connection: connection,
             transferred_bits: 0,
             clock_source: ClockType::External,
-            transaction_state: TransactionState::InProgress,
+            clock_speed: ClockSpeed::Normal,
+            instant_transfers: false,
+            transaction_state: TransactionState::Nothing,
             cycles_until_next_bit: CYCLES_PER_BIT,
         }
     }
+    /// Transfer all eight bits in a single cycle if the internal clock is used, instead of using the accurate timing.
+    ///
+    /// Useful for test harnesses that only care about the transferred data.
+    pub fn set_instant_transfers(&mut self, instant_transfers: bool) {
+        self.instant_transfers = instant_transfers;
+    }
+    /// The number of cycles it takes to transfer one bit with the current settings
+    pub fn cycles_per_bit(&self) -> u32 {
+        self.clock_speed.cycles_per_bit()
+    }
+    /// Capture the current transfer state
+    pub fn save_state(&self) -> SerialState {
+        SerialState {
+            transferred_bits: self.transferred_bits,
+            clock_source: self.clock_source.clone(),
+            clock_speed: self.clock_speed.clone(),
+            transaction_state: self.transaction_state.clone(),
+            cycles_until_next_bit: self.cycles_until_next_bit,
+        }
+    }
+    /// Restore a transfer state captured with [Serial::save_state]. The connection is not changed.
+    pub fn load_state(&mut self, state: &SerialState) {
+        self.transferred_bits = state.transferred_bits;
+        self.clock_source = state.clock_source.clone();
+        self.clock_speed = state.clock_speed.clone();
+        self.transaction_state = state.transaction_state.clone();
+        self.cycles_until_next_bit = state.cycles_until_next_bit;
+    }
     /// Process writes to the memory
-    pub fn write(&mut self, memory: &mut Memory, address: u16, value: u8) -> Option<()> {
+    ///
+    /// The fast clock can only be selected with bit 1 of the serial control register in `cgb_mode`.
+    pub fn write(
+        &mut self,
+        memory: &mut Memory,
+        address: u16,
+        value: u8,
+        cgb_mode: bool,
+    ) -> Option<()> {
         match address as usize {
             SERIAL_DATA_ADDRESS => None,
             SERIAL_CONTROL_ADDRESS => {
                 let transfer_in_progress_bit = (value & 0b10000000) >> 7;
+                let clock_speed_bit = if cgb_mode {
+                    (value & 0b00000010) >> 1
+                } else {
+                    0
+                };
                 let clock_source_bit = value & 0b00000001;
                 self.clock_source = clock_source_bit
                     .try_into()
                     .expect("Clock source bit should always be in range");
+                self.clock_speed = clock_speed_bit
+                    .try_into()
+                    .expect("Clock speed bit should always be in range");
                 self.transaction_state = transfer_in_progress_bit
                     .try_into()
                     .expect("Transfer in progress bit should always be in range");
+                if self.transaction_state == TransactionState::InProgress {
+                    self.transferred_bits = 0;
+                    self.cycles_until_next_bit = self.cycles_per_bit();
+                }
                 memory.data[SERIAL_CONTROL_ADDRESS] = value;
                 Some(())
             }
@@ -71,9 +154,27 @@ impl<T: SerialConnection> Serial<T> {
     }
     /// Should be called on every cycle
     pub fn cycle(&mut self, memory: &mut Memory) {
-        if !(self.clock_source == ClockType::Internal
-            && self.transaction_state == TransactionState::InProgress)
-        {
+        if self.transaction_state != TransactionState::InProgress {
+            return;
+        }
+
+        if self.clock_source == ClockType::External {
+            let outgoing = memory.data[SERIAL_DATA_ADDRESS];
+            let received = self
+                .connection
+                .as_mut()
+                .and_then(|connection| connection.exchange(outgoing));
+            if let Some(received) = received {
+                memory.data[SERIAL_DATA_ADDRESS] = received;
+                self.finish_transfer(memory);
+            }
+            return;
+        }
+
+        if self.instant_transfers {
+            while self.transaction_state == TransactionState::InProgress {
+                self.transfer_bit(memory);
+            }
             return;
         }
 
@@ -82,8 +183,12 @@ impl<T: SerialConnection> Serial<T> {
             return;
         }
 
-        self.cycles_until_next_bit = CYCLES_PER_BIT;
+        self.cycles_until_next_bit = self.cycles_per_bit();
+        self.transfer_bit(memory);
+    }
 
+    /// Exchange the next bit with the connection. Finishes the transfer after the eighth bit.
+    fn transfer_bit(&mut self, memory: &mut Memory) {
         let send_bit = (memory.data[SERIAL_DATA_ADDRESS] & 0b10000000) == 0b10000000;
         let received_bit = self
             .connection
@@ -98,9 +203,126 @@ impl<T: SerialConnection> Serial<T> {
             return;
         }
 
+        self.finish_transfer(memory);
+    }
+
+    /// Clear the transfer start bit and request the serial interrupt
+    fn finish_transfer(&mut self, memory: &mut Memory) {
         memory.data[SERIAL_CONTROL_ADDRESS] = memory.data[SERIAL_CONTROL_ADDRESS] & 0b01111111;
         self.transaction_state = TransactionState::Nothing;
         memory.write_interrupt_flag(Interrupt::Serial, true);
         self.transferred_bits = 0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::interrupt_controller::InterruptController;
+    use crate::cpu::Interrupt;
+    use crate::memory::memory_addresses::{SERIAL_CONTROL_ADDRESS, SERIAL_DATA_ADDRESS};
+    use crate::memory::serial::serial_connection::SerialConnection;
+    use crate::memory::video::display_connection::DisplayConnection;
+    use crate::memory::{MemoryController, MemoryDevice};
+
+    /// Start a transfer with the given control value and count the cycles until it is finished
+    fn cycles_until_transfer_completes(
+        memory: &mut MemoryController<impl SerialConnection, impl DisplayConnection>,
+        control: u8,
+    ) -> usize {
+        memory.write(SERIAL_CONTROL_ADDRESS as u16, control);
+        let mut cycles = 0;
+        while memory.read(SERIAL_CONTROL_ADDRESS as u16) & 0b10000000 != 0 {
+            memory.process_cycle();
+            cycles += 1;
+            assert!(cycles <= 8 * 128, "Transfer did not complete in time");
+        }
+        cycles
+    }
+
+    #[test]
+    fn transfer_with_internal_clock_takes_eight_bit_periods() {
+        let mut memory = MemoryController::new();
+        assert_eq!(cycles_until_transfer_completes(&mut memory, 0x81), 8 * 128);
+        assert!(memory.read_interrupt_flag(Interrupt::Serial));
+    }
+
+    #[test]
+    fn interrupt_is_requested_when_the_last_bit_is_transferred() {
+        let mut memory = MemoryController::new();
+        memory.write(SERIAL_CONTROL_ADDRESS as u16, 0x81);
+        for _ in 0..(8 * 128 - 1) {
+            memory.process_cycle();
+        }
+        assert!(!memory.read_interrupt_flag(Interrupt::Serial));
+        assert_eq!(memory.read(SERIAL_CONTROL_ADDRESS as u16), 0x81);
+
+        memory.process_cycle();
+        assert!(memory.read_interrupt_flag(Interrupt::Serial));
+        assert_eq!(memory.read(SERIAL_CONTROL_ADDRESS as u16), 0x01);
+    }
+
+    #[test]
+    fn fast_clock_is_ignored_on_dmg() {
+        let mut memory = MemoryController::new();
+        assert_eq!(cycles_until_transfer_completes(&mut memory, 0x83), 8 * 128);
+    }
+
+    #[test]
+    fn fast_clock_transfers_faster() {
+        let mut memory = MemoryController::new();
+        memory.set_cgb_mode(true);
+        assert_eq!(cycles_until_transfer_completes(&mut memory, 0x83), 8 * 4);
+        assert!(memory.read_interrupt_flag(Interrupt::Serial));
+    }
+
+    #[test]
+    fn instant_transfers_take_a_single_cycle() {
+        let mut memory = MemoryController::new();
+        memory.serial.set_instant_transfers(true);
+        memory.write(SERIAL_DATA_ADDRESS as u16, 0x42);
+        assert_eq!(cycles_until_transfer_completes(&mut memory, 0x81), 1);
+        assert!(memory.read_interrupt_flag(Interrupt::Serial));
+        // The logger does not send anything back, so only ones are received
+        assert_eq!(memory.read(SERIAL_DATA_ADDRESS as u16), 0xFF);
+    }
+
+    /// A peer that provides the clock and always sends the same byte
+    struct FixedPeer {
+        byte: u8,
+    }
+
+    impl SerialConnection for FixedPeer {
+        fn exchange_bit(&mut self, _send: bool) -> bool {
+            true
+        }
+        fn exchange(&mut self, _outgoing: u8) -> Option<u8> {
+            Some(self.byte)
+        }
+    }
+
+    #[test]
+    fn external_clock_receives_the_byte_of_the_peer() {
+        let mut memory = MemoryController::new_with_connections(Some(FixedPeer { byte: 0x42 }));
+        memory.write(SERIAL_DATA_ADDRESS as u16, 0x12);
+        memory.write(SERIAL_CONTROL_ADDRESS as u16, 0x80);
+
+        memory.process_cycle();
+        assert_eq!(memory.read(SERIAL_DATA_ADDRESS as u16), 0x42);
+        assert_eq!(memory.read(SERIAL_CONTROL_ADDRESS as u16), 0x00);
+        assert!(memory.read_interrupt_flag(Interrupt::Serial));
+    }
+
+    #[test]
+    fn external_clock_waits_without_a_peer() {
+        let mut memory = MemoryController::new();
+        memory.write(SERIAL_DATA_ADDRESS as u16, 0x12);
+        memory.write(SERIAL_CONTROL_ADDRESS as u16, 0x80);
+
+        for _ in 0..(8 * 128 * 2) {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(SERIAL_DATA_ADDRESS as u16), 0x12);
+        assert_eq!(memory.read(SERIAL_CONTROL_ADDRESS as u16), 0x80);
+        assert!(!memory.read_interrupt_flag(Interrupt::Serial));
+    }
+}
@@ -4,6 +4,14 @@ pub trait SerialConnection {
     ///
     /// Should return true, if there is no data source available
     fn exchange_bit(&mut self, send: bool) -> bool;
+
+    /// Exchange a whole byte with a peer that provides the clock.
+    ///
+    /// Called on every cycle while a transfer with the external clock is in progress. Return the byte the peer clocked in to finish the transfer, or `None` if the peer is not ready yet.
+    /// The default implementation has no peer and always returns `None`, so the transfer never finishes.
+    fn exchange(&mut self, _outgoing: u8) -> Option<u8> {
+        None
+    }
 }
 
 impl SerialConnection for LoggerSerialConnection {
@@ -0,0 +1,128 @@
+use super::memory_addresses::SPEED_SWITCH_ADDRESS;
+
+/// Represents the CGB speed switch register at [SPEED_SWITCH_ADDRESS].
+///
+/// The game requests a switch by writing a `1` to bit 0 and then executing [STOP](crate::cpu::instruction::Stop), which toggles the speed of the CPU.
+/// In double speed mode the timer and the serial connection run at twice the speed as well, while the PPU keeps its normal speed.
+/// Bit 7 contains the current speed and the unused bits always read as `1`.
+///
+/// See <https://gbdev.io/pandocs/CGB_Registers.html#ff4d--key1-cgb-mode-only-prepare-speed-switch> for more details.
+#[derive(Clone)]
+pub struct SpeedSwitch {
+    /// `true` if the CPU runs at double speed
+    double_speed: bool,
+    /// `true` if the next STOP instruction switches the speed
+    switch_armed: bool,
+}
+
+const CURRENT_SPEED: u8 = 0b10000000;
+const PREPARE_SWITCH: u8 = 0b00000001;
+const UNUSED_BITS: u8 = 0b01111110;
+
+impl SpeedSwitch {
+    /// Create a new speed switch in normal speed mode
+    pub fn new() -> SpeedSwitch {
+        SpeedSwitch {
+            double_speed: false,
+            switch_armed: false,
+        }
+    }
+
+    /// Check if the CPU runs at double speed
+    pub fn is_double_speed(&self) -> bool {
+        self.double_speed
+    }
+
+    /// Toggle the speed if a switch was prepared. Returns `true` if the speed was switched.
+    ///
+    /// Called when a STOP instruction is executed.
+    pub fn switch(&mut self) -> bool {
+        if !self.switch_armed {
+            return false;
+        }
+        self.switch_armed = false;
+        self.double_speed = !self.double_speed;
+        true
+    }
+
+    /// Get the current value of the speed switch register
+    pub fn read(&self) -> u8 {
+        let mut value = UNUSED_BITS;
+        if self.double_speed {
+            value |= CURRENT_SPEED;
+        }
+        if self.switch_armed {
+            value |= PREPARE_SWITCH;
+        }
+        value
+    }
+
+    /// Process writes to the memory
+    pub fn write(&mut self, address: u16, value: u8) -> Option<()> {
+        match address as usize {
+            SPEED_SWITCH_ADDRESS => {
+                self.switch_armed = value & PREPARE_SWITCH != 0;
+                Some(())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for SpeedSwitch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::instruction::{Instruction, InstructionEnum, Stop};
+    use crate::cpu::CpuState;
+    use crate::memory::memory_addresses::{
+        LCD_CONTROL_ADDRESS, SPEED_SWITCH_ADDRESS, TIMER_DIVIDER_ADDRESS,
+    };
+    use crate::memory::{MemoryController, MemoryDevice};
+
+    #[test]
+    fn stop_switches_the_speed_after_it_was_prepared() {
+        let mut cpu = CpuState::new();
+        let mut memory = MemoryController::new_for_tests();
+        memory.set_cgb_mode(true);
+        assert_eq!(memory.read(SPEED_SWITCH_ADDRESS as u16), 0x7E);
+
+        // Without a prepared switch STOP just stops
+        let instruction = Stop { stopped: false }.execute(&mut cpu, &mut memory);
+        assert!(matches!(instruction, InstructionEnum::Stop(_)));
+        assert!(!memory.speed_switch.is_double_speed());
+
+        memory.write(SPEED_SWITCH_ADDRESS as u16, 0x01);
+        assert_eq!(memory.read(SPEED_SWITCH_ADDRESS as u16), 0x7F);
+
+        let instruction = Stop { stopped: false }.execute(&mut cpu, &mut memory);
+        assert!(!matches!(instruction, InstructionEnum::Stop(_)));
+        assert!(memory.speed_switch.is_double_speed());
+        assert_eq!(memory.read(SPEED_SWITCH_ADDRESS as u16), 0xFE);
+
+        // Switching again returns to normal speed
+        memory.write(SPEED_SWITCH_ADDRESS as u16, 0x01);
+        Stop { stopped: false }.execute(&mut cpu, &mut memory);
+        assert!(!memory.speed_switch.is_double_speed());
+        assert_eq!(memory.read(SPEED_SWITCH_ADDRESS as u16), 0x7E);
+    }
+
+    #[test]
+    fn the_ppu_runs_at_half_the_speed_in_double_speed_mode() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.set_cgb_mode(true);
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+        memory.write(SPEED_SWITCH_ADDRESS as u16, 0x01);
+        assert!(memory.switch_speed());
+
+        for _ in 0..128 {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(TIMER_DIVIDER_ADDRESS as u16), 2);
+        assert_eq!(memory.graphics.cycles_on_current_line, 64);
+    }
+}
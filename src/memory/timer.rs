@@ -9,7 +9,7 @@ use super::{
     Memory,
 };
 
-#[derive(TryFromPrimitive, Debug, IntoPrimitive)]
+#[derive(TryFromPrimitive, Debug, IntoPrimitive, Clone)]
 #[repr(u8)]
 enum InputClock {
     Hz4096 = 0b00,
@@ -30,11 +30,14 @@ impl InputClock {
     }
 }
 /// Represents the timer and interrupt controller
+#[derive(Clone)]
 pub struct Timer {
     enabled: bool,
     input_clock: InputClock,
     counter: u64,
     tima: u8,
+    /// Set for one cycle after TIMA overflowed. TIMA reads `0x00` until it is reloaded from TMA in the next cycle.
+    reload_pending: bool,
 }
 
 impl Timer {
@@ -45,6 +48,7 @@ impl Timer {
             input_clock: InputClock::Hz4096,
             counter: 0,
             tima: 0,
+            reload_pending: false,
         }
     }
 
@@ -59,16 +63,24 @@ impl Timer {
         self.enabled = is_enabled;
     }
 
+    /// Reset the divider and the internal counter it is made of
+    pub fn reset_divider(&mut self, memory: &mut Memory) {
+        memory.data[TIMER_DIVIDER_ADDRESS] = 0;
+        self.counter = 0;
+    }
     /// Process writes to the memory
     pub fn write(&mut self, memory: &mut Memory, address: u16, value: u8) -> Option<()> {
         match address as usize {
             TIMER_DIVIDER_ADDRESS => {
-                memory.data[TIMER_DIVIDER_ADDRESS] = 0;
+                // Any write resets the whole internal counter, not only the visible upper bits
+                self.reset_divider(memory);
                 Some(())
             }
             TIMER_COUNTER_ADDRESS => {
                 memory.data[TIMER_COUNTER_ADDRESS] = value;
                 self.tima = value;
+                // Writing TIMA while the reload is pending cancels the reload and the interrupt
+                self.reload_pending = false;
                 Some(())
             }
             TIMER_MODULO_ADDRESS => {
@@ -84,7 +96,16 @@ impl Timer {
         }
     }
     /// Should be called on every cycle
+    ///
+    /// Increments TIMA at the frequency selected in TAC. When TIMA overflows, it reads `0x00` for one cycle.
+    /// In the next cycle it is reloaded from TMA and a [Interrupt::Timer] is requested, so writes to TMA during that cycle are used for the reload.
     pub fn cycle(&mut self, memory: &mut Memory) {
+        if std::mem::take(&mut self.reload_pending) {
+            memory.data[TIMER_COUNTER_ADDRESS] = memory.data[TIMER_MODULO_ADDRESS];
+            memory.write_interrupt_flag(Interrupt::Timer, true);
+            self.tima = memory.data[TIMER_COUNTER_ADDRESS];
+        }
+
         self.counter = self.counter.wrapping_add(1);
         if self.counter % 64 == 0 {
             memory.data[TIMER_DIVIDER_ADDRESS] = memory.data[TIMER_DIVIDER_ADDRESS].wrapping_add(1);
@@ -94,11 +115,95 @@ impl Timer {
             let (new_timer_counter, overflow) =
                 memory.data[TIMER_COUNTER_ADDRESS].overflowing_add(1);
             memory.data[TIMER_COUNTER_ADDRESS] = new_timer_counter;
-            if overflow {
-                memory.data[TIMER_COUNTER_ADDRESS] = memory.data[TIMER_MODULO_ADDRESS];
-                memory.write_interrupt_flag(Interrupt::Timer, true);
-            }
+            self.reload_pending = overflow;
             self.tima = memory.data[TIMER_COUNTER_ADDRESS];
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::interrupt_controller::InterruptController;
+    use crate::cpu::Interrupt;
+    use crate::memory::memory_addresses::{
+        TIMER_CONTROL_ADDRESS, TIMER_COUNTER_ADDRESS, TIMER_DIVIDER_ADDRESS, TIMER_MODULO_ADDRESS,
+    };
+    use crate::memory::{MemoryController, MemoryDevice};
+
+    #[test]
+    fn overflowing_the_counter_reloads_it_and_requests_an_interrupt() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(TIMER_MODULO_ADDRESS as u16, 0xAB);
+        memory.write(TIMER_COUNTER_ADDRESS as u16, 0xFE);
+        // Enabled, increment every 4 cycles
+        memory.write(TIMER_CONTROL_ADDRESS as u16, 0b101);
+
+        for _ in 0..4 {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(TIMER_COUNTER_ADDRESS as u16), 0xFF);
+        assert!(!memory.read_interrupt_flag(Interrupt::Timer));
+
+        for _ in 0..5 {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(TIMER_COUNTER_ADDRESS as u16), 0xAB);
+        assert!(memory.read_interrupt_flag(Interrupt::Timer));
+    }
+
+    #[test]
+    fn counter_reads_zero_for_one_cycle_before_the_reload() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(TIMER_MODULO_ADDRESS as u16, 0xAB);
+        memory.write(TIMER_COUNTER_ADDRESS as u16, 0xFF);
+        memory.write(TIMER_CONTROL_ADDRESS as u16, 0b101);
+
+        for _ in 0..4 {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(TIMER_COUNTER_ADDRESS as u16), 0x00);
+        assert!(!memory.read_interrupt_flag(Interrupt::Timer));
+
+        // Writes to TMA during the delay are used for the reload
+        memory.write(TIMER_MODULO_ADDRESS as u16, 0xCD);
+        memory.process_cycle();
+        assert_eq!(memory.read(TIMER_COUNTER_ADDRESS as u16), 0xCD);
+        assert!(memory.read_interrupt_flag(Interrupt::Timer));
+    }
+
+    #[test]
+    fn writing_the_counter_during_the_delay_cancels_the_reload() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(TIMER_MODULO_ADDRESS as u16, 0xAB);
+        memory.write(TIMER_COUNTER_ADDRESS as u16, 0xFF);
+        memory.write(TIMER_CONTROL_ADDRESS as u16, 0b101);
+
+        for _ in 0..4 {
+            memory.process_cycle();
+        }
+        memory.write(TIMER_COUNTER_ADDRESS as u16, 0x12);
+        memory.process_cycle();
+        assert_eq!(memory.read(TIMER_COUNTER_ADDRESS as u16), 0x12);
+        assert!(!memory.read_interrupt_flag(Interrupt::Timer));
+    }
+
+    #[test]
+    fn writing_the_divider_resets_it() {
+        let mut memory = MemoryController::new_for_tests();
+        for _ in 0..(64 * 3 + 10) {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(TIMER_DIVIDER_ADDRESS as u16), 3);
+
+        memory.write(TIMER_DIVIDER_ADDRESS as u16, 0x42);
+        assert_eq!(memory.read(TIMER_DIVIDER_ADDRESS as u16), 0x00);
+
+        // The internal counter is reset as well, so the next increment takes a full period
+        for _ in 0..63 {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(TIMER_DIVIDER_ADDRESS as u16), 0x00);
+        memory.process_cycle();
+        assert_eq!(memory.read(TIMER_DIVIDER_ADDRESS as u16), 0x01);
+    }
+}
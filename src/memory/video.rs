@@ -1,18 +1,28 @@
+use std::borrow::Cow;
+
 use crate::cpu::Interrupt;
 
 use self::{
-    display_connection::DisplayConnection,
-    lcd_control::LcdControl,
-    lcd_status::{LcdStatus, PpuMode},
-    object_attributes::ObjectAttributes,
-    palette::Palette,
+    color_palette_memory::ColorPaletteMemory,
+    display_connection::{DisplayConnection, SCREEN_WIDTH},
+    lcd_control::{BackgroundTilemapArea, LcdControl, ObjectSize, TileDataArea},
+    lcd_status::{LcdStatus, PpuMode, StatInterruptSource},
+    object_attributes::{ObjectAttributes, ObjectPalette},
+    palette::{Palette, DEFAULT_DMG_COLORS},
+    tile::TileData,
+    tile_map::TileMap,
 };
 
 use super::{
     memory_addresses::{
-        BACKGROUND_PALETTE_ADDRESS, CURRENT_LINE_ADDRESS, FIRST_OBJECT_PALETTE_ADDRESS,
-        INITIATE_OBJECT_ATTRIBUTE_MEMORY_TRANSFER_ADDRESS, INTERRUPT_LINE_ADDRESS,
-        LCD_CONTROL_ADDRESS, LCD_STATUS_ADDRESS, SECOND_OBJECT_PALETTE_ADDRESS,
+        BACKGROUND_PALETTE_ADDRESS, BACKGROUND_PALETTE_DATA_ADDRESS,
+        BACKGROUND_PALETTE_SPECIFICATION_ADDRESS, CURRENT_LINE_ADDRESS,
+        FIRST_OBJECT_PALETTE_ADDRESS, INITIATE_OBJECT_ATTRIBUTE_MEMORY_TRANSFER_ADDRESS,
+        INTERRUPT_LINE_ADDRESS, LCD_CONTROL_ADDRESS, LCD_STATUS_ADDRESS,
+        OBJECT_ATTRIBUTE_MEMORY_AREA, OBJECT_PALETTE_DATA_ADDRESS,
+        OBJECT_PALETTE_SPECIFICATION_ADDRESS, SCROLL_X_ADDRESS, SCROLL_Y_ADDRESS,
+        SECOND_OBJECT_PALETTE_ADDRESS, TILE_DATA_AREA, VIDEO_RAM_AREA, VIDEO_RAM_BANK_ADDRESS,
+        WINDOW_X_ADDRESS, WINDOW_Y_ADDRESS,
     },
     Memory,
 };
@@ -32,6 +42,9 @@ pub mod display_connection;
 /// Contains a struct for color palettes.
 pub mod palette;
 
+/// Contains the CGB color palette memory
+pub mod color_palette_memory;
+
 /// Contains logic for decoding the lcd control register.
 pub mod lcd_control;
 
@@ -41,6 +54,10 @@ pub mod lcd_status;
 // struct TileMap {}
 
 /// A running object attribute memory transfer
+///
+/// One byte is copied per machine cycle by [MemoryController::process_cycle](super::MemoryController::process_cycle), so a transfer takes 160 cycles.
+/// It runs on the CPU clock and continues while the LCD is off or the PPU is paused.
+#[derive(Clone)]
 pub struct ObjectAttributeMemoryTransfer {
     /// The current source address
     pub current_source_address: usize,
@@ -48,6 +65,44 @@ pub struct ObjectAttributeMemoryTransfer {
     pub current_target_address: usize,
 }
 
+/// The internal state of a [Video], without the display connection. Used for savestates.
+#[derive(Clone)]
+pub struct VideoState {
+    background_palette: Palette,
+    first_object_palette: Palette,
+    second_object_palette: Palette,
+    background_color_palettes: ColorPaletteMemory,
+    object_color_palettes: ColorPaletteMemory,
+    current_lcd_control: LcdControl,
+    current_lcd_status: LcdStatus,
+    current_transfer: Option<ObjectAttributeMemoryTransfer>,
+    cycles_on_current_line: usize,
+    current_line: u8,
+    window_line: u8,
+    current_objects: Vec<ObjectAttributes>,
+    last_stat_source: Option<StatInterruptSource>,
+    finished_frames: u64,
+    cgb_mode: bool,
+    second_video_ram_bank: Vec<u8>,
+    second_video_ram_bank_selected: bool,
+}
+
+/// The number of objects the hardware can draw on a single line
+pub const DEFAULT_MAX_SPRITES_PER_LINE: usize = 10;
+
+/// The bits of the CGB tile attributes that select the background palette
+const ATTRIBUTES_PALETTE_MASK: u8 = 0b00000111;
+
+/// The width and height of a whole background tilemap in pixels
+pub const BACKGROUND_SIZE: usize = 256;
+
+/// The number of tiles in each row of the tile atlas
+const TILE_ATLAS_COLUMNS: usize = 16;
+/// The width of the tile atlas in pixels
+pub const TILE_ATLAS_WIDTH: usize = TILE_ATLAS_COLUMNS * 8;
+/// The height of the tile atlas in pixels
+pub const TILE_ATLAS_HEIGHT: usize = 384 / TILE_ATLAS_COLUMNS * 8;
+
 /// Represents the gpu
 pub struct Video<T: DisplayConnection> {
     /// Pixels get drawn onto this display
@@ -58,6 +113,10 @@ pub struct Video<T: DisplayConnection> {
     pub first_object_palette: Palette,
     /// The current second object color palette
     pub second_object_palette: Palette,
+    /// The CGB color palettes for the background and window layer
+    pub background_color_palettes: ColorPaletteMemory,
+    /// The CGB color palettes for the object layer
+    pub object_color_palettes: ColorPaletteMemory,
     /// The current state of the LCD control register
     pub current_lcd_control: LcdControl,
     /// The current state of the LCD status register
@@ -69,8 +128,28 @@ pub struct Video<T: DisplayConnection> {
     pub cycles_on_current_line: usize,
     /// The line that is currently rendered
     pub current_line: u8,
+    /// The line of the window that is rendered next. Only advances on lines where the window was visible.
+    pub window_line: u8,
     /// The objects that are relevant for the current line
     pub current_objects: Vec<ObjectAttributes>,
+    /// If the PPU is paused for debugging
+    paused: bool,
+    /// The maximum number of objects that are selected during the OAM scan of a line
+    max_sprites_per_line: usize,
+    /// The RGBA values of the four DMG colors
+    dmg_colors: [[u8; 4]; 4],
+    /// The condition that caused the last [Interrupt::LcdStat]
+    last_stat_source: Option<StatInterruptSource>,
+    /// The decoded tiles of the [TILE_DATA_AREA]. `None` if the tile data was written since they were decoded.
+    tile_cache: Option<Vec<TileData>>,
+    /// Render with the CGB color palettes instead of the DMG palettes
+    cgb_mode: bool,
+    /// The second VRAM bank of the CGB, which contains the tile attributes. The first bank is stored in [Memory].
+    second_video_ram_bank: Vec<u8>,
+    /// The CPU accesses the second VRAM bank. Selected with [VIDEO_RAM_BANK_ADDRESS].
+    second_video_ram_bank_selected: bool,
+    /// The number of frames that were finished since the PPU was created
+    finished_frames: u64,
 }
 
 impl<T: DisplayConnection> Video<T> {
@@ -81,13 +160,289 @@ impl<T: DisplayConnection> Video<T> {
             background_palette: Palette::from_background_register(0),
             first_object_palette: Palette::from_object_register(0),
             second_object_palette: Palette::from_object_register(0),
+            background_color_palettes: ColorPaletteMemory::new(),
+            object_color_palettes: ColorPaletteMemory::new(),
             current_lcd_control: 0.into(),
             current_lcd_status: 0.into(),
             current_transfer: None,
             cycles_on_current_line: 0,
             current_line: 0,
+            window_line: 0,
             current_objects: Vec::new(),
+            paused: false,
+            max_sprites_per_line: DEFAULT_MAX_SPRITES_PER_LINE,
+            dmg_colors: DEFAULT_DMG_COLORS,
+            last_stat_source: None,
+            tile_cache: None,
+            cgb_mode: false,
+            second_video_ram_bank: vec![0; VIDEO_RAM_AREA.count()],
+            second_video_ram_bank_selected: false,
+            finished_frames: 0,
+        }
+    }
+
+    /// Capture the current state of the PPU.
+    ///
+    /// Whether the PPU is paused is a debugging setting and not part of the state.
+    pub fn save_state(&self) -> VideoState {
+        VideoState {
+            background_palette: self.background_palette.clone(),
+            first_object_palette: self.first_object_palette.clone(),
+            second_object_palette: self.second_object_palette.clone(),
+            background_color_palettes: self.background_color_palettes.clone(),
+            object_color_palettes: self.object_color_palettes.clone(),
+            current_lcd_control: self.current_lcd_control.clone(),
+            current_lcd_status: self.current_lcd_status.clone(),
+            current_transfer: self.current_transfer.clone(),
+            cycles_on_current_line: self.cycles_on_current_line,
+            current_line: self.current_line,
+            window_line: self.window_line,
+            current_objects: self.current_objects.clone(),
+            last_stat_source: self.last_stat_source,
+            finished_frames: self.finished_frames,
+            cgb_mode: self.cgb_mode,
+            second_video_ram_bank: self.second_video_ram_bank.clone(),
+            second_video_ram_bank_selected: self.second_video_ram_bank_selected,
+        }
+    }
+
+    /// Restore a state captured with [Video::save_state]. The display connection is not changed.
+    pub fn load_state(&mut self, state: &VideoState) {
+        let state = state.clone();
+        self.background_palette = state.background_palette;
+        self.first_object_palette = state.first_object_palette;
+        self.second_object_palette = state.second_object_palette;
+        self.background_color_palettes = state.background_color_palettes;
+        self.object_color_palettes = state.object_color_palettes;
+        self.current_lcd_control = state.current_lcd_control;
+        self.current_lcd_status = state.current_lcd_status;
+        self.current_transfer = state.current_transfer;
+        self.cycles_on_current_line = state.cycles_on_current_line;
+        self.current_line = state.current_line;
+        self.window_line = state.window_line;
+        self.current_objects = state.current_objects;
+        self.last_stat_source = state.last_stat_source;
+        self.finished_frames = state.finished_frames;
+        self.cgb_mode = state.cgb_mode;
+        self.second_video_ram_bank = state.second_video_ram_bank;
+        self.second_video_ram_bank_selected = state.second_video_ram_bank_selected;
+        self.invalidate_tile_cache();
+    }
+
+    /// Get all tiles of the [TILE_DATA_AREA], ordered by their address.
+    ///
+    /// The tiles are only decoded again after the tile data was written through [Video::write].
+    /// Call [Video::invalidate_tile_cache] after modifying the tile data in `memory` directly.
+    pub fn get_tile_data(&mut self, memory: &Memory) -> &[TileData] {
+        self.tile_cache
+            .get_or_insert_with(|| memory.get_all_tile_data())
+    }
+
+    /// Get the cached tiles, or decode them if they are not cached. See [Video::get_tile_data].
+    fn get_cached_tile_data<'a>(&'a self, memory: &Memory) -> Cow<'a, [TileData]> {
+        match &self.tile_cache {
+            Some(tiles) => Cow::Borrowed(tiles),
+            None => Cow::Owned(memory.get_all_tile_data()),
+        }
+    }
+
+    /// Decode the tiles again the next time they are used. See [Video::get_tile_data].
+    pub fn invalidate_tile_cache(&mut self) {
+        self.tile_cache = None;
+    }
+
+    /// Get the condition that caused the last [Interrupt::LcdStat]. Useful for debugging raster effects.
+    ///
+    /// Returns `None` if no STAT interrupt was requested yet.
+    pub fn last_stat_source(&self) -> Option<StatInterruptSource> {
+        self.last_stat_source
+    }
+
+    /// Request a [Interrupt::LcdStat] if `source` is enabled in the LCD status register
+    fn request_stat_interrupt(&mut self, memory: &mut Memory, source: StatInterruptSource) {
+        let status = &self.current_lcd_status;
+        let enabled = match source {
+            StatInterruptSource::LineYCompare => status.line_y_stat_interrupt_enable,
+            StatInterruptSource::HBlank => status.hblank_stat_interrupt_enable,
+            StatInterruptSource::VBlank => status.vblank_stat_interrupt_enable,
+            StatInterruptSource::Oam => status.oam_stat_interrupt_enable,
+        };
+        if !enabled {
+            return;
+        }
+        self.last_stat_source = Some(source);
+        memory.write_interrupt_flag(Interrupt::LcdStat, true);
+    }
+
+    /// Compare the current line with LYC and update the [line_y_equal_flag](LcdStatus::line_y_equal_flag).
+    ///
+    /// Requests a STAT interrupt when the flag becomes set. The status is not written to memory.
+    fn compare_line_y(&mut self, memory: &mut Memory) {
+        let line_y_equal = self.current_line == memory.data[INTERRUPT_LINE_ADDRESS];
+        let was_equal = self.current_lcd_status.line_y_equal_flag;
+        self.current_lcd_status.line_y_equal_flag = line_y_equal;
+        if line_y_equal && !was_equal {
+            self.request_stat_interrupt(memory, StatInterruptSource::LineYCompare);
+        }
+    }
+
+    /// Update the LCD status after the current line changed.
+    ///
+    /// Compares the current line with LYC and requests the STAT interrupts for the new mode and line.
+    fn finish_line_change(&mut self, memory: &mut Memory) {
+        self.compare_line_y(memory);
+        match self.current_lcd_status.ppu_mode {
+            PpuMode::Oam => self.request_stat_interrupt(memory, StatInterruptSource::Oam),
+            PpuMode::VBlank => self.request_stat_interrupt(memory, StatInterruptSource::VBlank),
+            _ => {}
         }
+        memory.data[LCD_STATUS_ADDRESS] = (&self.current_lcd_status).into();
+        memory.data[CURRENT_LINE_ADDRESS] = self.current_line;
+    }
+
+    /// Pause or resume the PPU.
+    ///
+    /// While paused, [Video::cycle] does not advance the mode or the current line and does not render anything.
+    /// The CPU keeps running, so this can be used to inspect a stable frame while stepping through code.
+    /// After resuming, the PPU continues exactly where it was paused.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Set how many objects are selected per line during the OAM scan.
+    ///
+    /// The hardware only draws the first [DEFAULT_MAX_SPRITES_PER_LINE] objects on a line, which causes games to flicker when there are more.
+    /// Raising the limit (to 40 at most, the size of OAM) removes the flicker, at the cost of no longer being accurate.
+    pub fn set_max_sprites_per_line(&mut self, max_sprites_per_line: usize) {
+        self.max_sprites_per_line = max_sprites_per_line;
+    }
+
+    /// Get the maximum number of objects per line. See [Video::set_max_sprites_per_line].
+    pub fn max_sprites_per_line(&self) -> usize {
+        self.max_sprites_per_line
+    }
+
+    /// Set the RGBA values that are displayed for the four DMG colors, from white to black.
+    ///
+    /// Use this to get the classic green LCD look or any other four colors. Defaults to the grayscale [DEFAULT_DMG_COLORS].
+    pub fn set_dmg_colors(&mut self, dmg_colors: [[u8; 4]; 4]) {
+        self.dmg_colors = dmg_colors;
+    }
+
+    /// Get the RGBA values of the four DMG colors. See [Video::set_dmg_colors].
+    pub fn dmg_colors(&self) -> [[u8; 4]; 4] {
+        self.dmg_colors
+    }
+
+    /// Render with the CGB color palettes instead of the DMG palettes.
+    ///
+    /// The palettes are written through [BACKGROUND_PALETTE_DATA_ADDRESS] and [OBJECT_PALETTE_DATA_ADDRESS]. Objects use their [cgb_palette](ObjectAttributes::cgb_palette).
+    /// The background and window use the palette from the tile attributes in the second VRAM bank. The other attributes are not emulated yet.
+    ///
+    /// This only changes the video. Use [MemoryController::set_cgb_mode](crate::memory::MemoryController::set_cgb_mode) to run the whole system as a CGB.
+    pub fn set_cgb_mode(&mut self, cgb_mode: bool) {
+        self.cgb_mode = cgb_mode;
+    }
+
+    /// Check if the CGB color palettes are used for rendering. See [Video::set_cgb_mode].
+    pub fn is_cgb_mode(&self) -> bool {
+        self.cgb_mode
+    }
+
+    /// Get the value of the VRAM bank register at [VIDEO_RAM_BANK_ADDRESS]
+    pub fn read_video_ram_bank(&self) -> u8 {
+        0b11111110 | self.second_video_ram_bank_selected as u8
+    }
+
+    /// Check if the CPU accesses the second VRAM bank instead of the [VIDEO_RAM_AREA] in [Memory]. Only possible in CGB mode.
+    pub fn second_video_ram_bank_active(&self) -> bool {
+        self.cgb_mode && self.second_video_ram_bank_selected
+    }
+
+    /// Read a byte at an address in the [VIDEO_RAM_AREA] from the second VRAM bank
+    pub fn read_second_video_ram_bank(&self, address: usize) -> u8 {
+        self.second_video_ram_bank[address - VIDEO_RAM_AREA.start()]
+    }
+
+    /// Get the CGB tile attributes for a tilemap. They are stored in the second VRAM bank, at the same position as the tile indices.
+    fn get_attribute_map(&self, area: &BackgroundTilemapArea) -> TileMap {
+        let memory_area = area.get_memory_area();
+        let start = memory_area.start() - VIDEO_RAM_AREA.start();
+        let end = memory_area.end() - VIDEO_RAM_AREA.start();
+        let tiles: [u8; 1024] = self.second_video_ram_bank[start..=end]
+            .try_into()
+            .expect("Incorrect length. Should not happen.");
+        TileMap { tiles }
+    }
+
+    /// Get the number of frames that were finished since the PPU was created.
+    ///
+    /// A frame is finished when the PPU enters VBlank and calls [DisplayConnection::finish_frame].
+    pub fn finished_frames(&self) -> u64 {
+        self.finished_frames
+    }
+
+    /// Get the mode the PPU is currently in. This is the mode that is reported in the LCD status register.
+    pub fn ppu_mode(&self) -> PpuMode {
+        self.current_lcd_status.ppu_mode
+    }
+
+    /// Get the line the PPU is currently on. This is the value of the LY register.
+    pub fn current_line(&self) -> u8 {
+        self.current_line
+    }
+
+    /// Render the whole active background tilemap into a [BACKGROUND_SIZE]x[BACKGROUND_SIZE] RGBA buffer.
+    ///
+    /// Uses the current tile data area and background palette, but ignores scrolling, the window and objects. Intended for VRAM viewers.
+    pub fn render_full_background(&self, memory: &Memory) -> Vec<u8> {
+        let tilemap = memory.get_tile_map(&self.current_lcd_control.background_tilemap);
+        let attribute_map = self.get_attribute_map(&self.current_lcd_control.background_tilemap);
+        let window_background_tile_data = &self.current_lcd_control.window_bg_tile_data;
+        let tiles = self.get_cached_tile_data(memory);
+
+        let mut buffer = Vec::with_capacity(BACKGROUND_SIZE * BACKGROUND_SIZE * 4);
+        for y in 0..BACKGROUND_SIZE {
+            let relevant_tiles = tilemap.get_tiles_for_line(y as u8);
+            let relevant_attributes = attribute_map.get_tiles_for_line(y as u8);
+            for x in 0..BACKGROUND_SIZE {
+                let tile = relevant_tiles[x / 8];
+                let tile_data = &tiles[window_background_tile_data.get_tile_position(tile)];
+                let pixel = tile_data.get_line(y % 8)[x % 8];
+                let (red, green, blue, alpha) = self
+                    .get_background_palette(relevant_attributes[x / 8])
+                    .get_color(pixel as usize)
+                    .get_rgba_with(&self.dmg_colors);
+                buffer.extend_from_slice(&[red, green, blue, alpha]);
+            }
+        }
+        buffer
+    }
+
+    /// Render all 384 tiles of the [TILE_DATA_AREA] into a [TILE_ATLAS_WIDTH]x[TILE_ATLAS_HEIGHT] RGBA buffer.
+    ///
+    /// The tiles are laid out in rows of 16 tiles, ordered by their address, and drawn with the current background palette. Intended for VRAM viewers.
+    pub fn render_tile_atlas(&self, memory: &Memory) -> Vec<u8> {
+        let tiles = self.get_cached_tile_data(memory);
+        let background_palette = self.get_background_palette(0);
+
+        let mut buffer = Vec::with_capacity(TILE_ATLAS_WIDTH * TILE_ATLAS_HEIGHT * 4);
+        for y in 0..TILE_ATLAS_HEIGHT {
+            for x in 0..TILE_ATLAS_WIDTH {
+                let tile_data = &tiles[(y / 8) * TILE_ATLAS_COLUMNS + x / 8];
+                let pixel = tile_data.get_line(y % 8)[x % 8];
+                let (red, green, blue, alpha) = background_palette
+                    .get_color(pixel as usize)
+                    .get_rgba_with(&self.dmg_colors);
+                buffer.extend_from_slice(&[red, green, blue, alpha]);
+            }
+        }
+        buffer
+    }
+
+    /// Check if the PPU is paused. See [Video::set_paused].
+    pub fn is_paused(&self) -> bool {
+        self.paused
     }
 
     /// Advance to the next line
@@ -101,6 +456,7 @@ impl<T: DisplayConnection> Video<T> {
 
         if self.current_line >= 154 {
             self.current_line = 0;
+            self.window_line = 0;
             self.current_lcd_status.ppu_mode = PpuMode::Oam;
             return;
         }
@@ -111,14 +467,39 @@ impl<T: DisplayConnection> Video<T> {
         self.current_lcd_status.ppu_mode = PpuMode::Oam;
     }
 
+    /// Turn the LCD off. The PPU stops on line 0 in [PpuMode::HBlank] and the display is cleared.
+    fn disable_lcd(&mut self, memory: &mut Memory) {
+        self.current_line = 0;
+        self.window_line = 0;
+        self.cycles_on_current_line = 0;
+        self.current_lcd_status.ppu_mode = PpuMode::HBlank;
+        memory.data[LCD_STATUS_ADDRESS] = (&self.current_lcd_status).into();
+        memory.data[CURRENT_LINE_ADDRESS] = self.current_line;
+        self.display_connection.clear();
+        self.display_connection.finish_frame();
+    }
+
+    /// Turn the LCD on. The PPU starts with the OAM scan of line 0.
+    fn enable_lcd(&mut self, memory: &mut Memory) {
+        self.current_lcd_status.ppu_mode = PpuMode::Oam;
+        self.compare_line_y(memory);
+        memory.data[LCD_STATUS_ADDRESS] = (&self.current_lcd_status).into();
+    }
+
     /// Handle writes to the video memory
     ///
     /// Returns [Ok] if the write was handled
     pub fn write(&mut self, memory: &mut Memory, address: u16, value: u8) -> Option<()> {
         match address as usize {
             LCD_CONTROL_ADDRESS => {
+                let was_enabled = self.current_lcd_control.lcd_ppu_enable;
                 self.current_lcd_control = value.into();
                 memory.data[LCD_CONTROL_ADDRESS] = value;
+                match (was_enabled, self.current_lcd_control.lcd_ppu_enable) {
+                    (true, false) => self.disable_lcd(memory),
+                    (false, true) => self.enable_lcd(memory),
+                    _ => {}
+                }
                 return Some(());
             }
             LCD_STATUS_ADDRESS => {
@@ -128,9 +509,18 @@ impl<T: DisplayConnection> Video<T> {
                 memory.data[LCD_STATUS_ADDRESS] = new_value;
                 return Some(());
             }
-            CURRENT_LINE_ADDRESS => Some(()),
+            CURRENT_LINE_ADDRESS => {
+                // LY is read only and always reflects the line the PPU is on
+                memory.data[CURRENT_LINE_ADDRESS] = self.current_line;
+                Some(())
+            }
             INTERRUPT_LINE_ADDRESS => {
                 memory.data[INTERRUPT_LINE_ADDRESS] = value;
+                // The comparison also happens when LYC changes in the middle of a line
+                if self.current_lcd_control.lcd_ppu_enable {
+                    self.compare_line_y(memory);
+                    memory.data[LCD_STATUS_ADDRESS] = (&self.current_lcd_status).into();
+                }
                 Some(())
             }
             BACKGROUND_PALETTE_ADDRESS => {
@@ -148,12 +538,45 @@ impl<T: DisplayConnection> Video<T> {
                 memory.data[SECOND_OBJECT_PALETTE_ADDRESS] = value;
                 return Some(());
             }
+            BACKGROUND_PALETTE_SPECIFICATION_ADDRESS => {
+                self.background_color_palettes.write_specification(value);
+                self.update_color_palette_registers(memory);
+                Some(())
+            }
+            BACKGROUND_PALETTE_DATA_ADDRESS => {
+                self.background_color_palettes.write_data(value);
+                self.update_color_palette_registers(memory);
+                Some(())
+            }
+            OBJECT_PALETTE_SPECIFICATION_ADDRESS => {
+                self.object_color_palettes.write_specification(value);
+                self.update_color_palette_registers(memory);
+                Some(())
+            }
+            OBJECT_PALETTE_DATA_ADDRESS => {
+                self.object_color_palettes.write_data(value);
+                self.update_color_palette_registers(memory);
+                Some(())
+            }
+            VIDEO_RAM_BANK_ADDRESS => {
+                self.second_video_ram_bank_selected = value & 0b00000001 != 0;
+                Some(())
+            }
+            address if self.second_video_ram_bank_active() && VIDEO_RAM_AREA.contains(&address) => {
+                self.second_video_ram_bank[address - VIDEO_RAM_AREA.start()] = value;
+                Some(())
+            }
+            address if TILE_DATA_AREA.contains(&address) => {
+                self.invalidate_tile_cache();
+                memory.data[address] = value;
+                Some(())
+            }
             INITIATE_OBJECT_ATTRIBUTE_MEMORY_TRANSFER_ADDRESS => {
                 self.current_transfer = Some(ObjectAttributeMemoryTransfer {
                     current_source_address: u16::from_be_bytes([value, 0]) as usize,
-                    current_target_address: 0xFF00,
+                    current_target_address: *OBJECT_ATTRIBUTE_MEMORY_AREA.start(),
                 });
-                memory.data[SECOND_OBJECT_PALETTE_ADDRESS] = value;
+                memory.data[INITIATE_OBJECT_ATTRIBUTE_MEMORY_TRANSFER_ADDRESS] = value;
                 return Some(());
             }
             _ => None,
@@ -163,20 +586,7 @@ impl<T: DisplayConnection> Video<T> {
     pub fn cycle(&mut self, memory: &mut Memory) {
         const CYCLES_PER_LINE: usize = 114;
 
-        match &mut self.current_transfer {
-            Some(transfer) => {
-                memory.data[transfer.current_target_address] =
-                    memory.data[transfer.current_source_address];
-                transfer.current_source_address += 1;
-                transfer.current_target_address += 1;
-                if transfer.current_target_address > 0xFF9F {
-                    self.current_transfer = None;
-                }
-            }
-            None => {}
-        }
-
-        if !self.current_lcd_control.lcd_ppu_enable {
+        if !self.current_lcd_control.lcd_ppu_enable || self.paused {
             return;
         }
 
@@ -200,65 +610,859 @@ impl<T: DisplayConnection> Video<T> {
                 }
                 if self.cycles_on_current_line >= 70 {
                     self.current_lcd_status.ppu_mode = PpuMode::HBlank;
+                    self.request_stat_interrupt(memory, StatInterruptSource::HBlank);
                     memory.data[LCD_STATUS_ADDRESS] = (&self.current_lcd_status).into();
                 }
             }
             PpuMode::HBlank => {
                 if self.cycles_on_current_line >= CYCLES_PER_LINE {
                     self.advance_to_next_line();
-                    memory.data[LCD_STATUS_ADDRESS] = (&self.current_lcd_status).into();
-                    memory.data[CURRENT_LINE_ADDRESS] = self.current_line;
+                    self.finish_line_change(memory);
                 }
             }
             PpuMode::VBlank => {
                 if self.current_line == 144 && self.cycles_on_current_line == 1 {
                     self.display_connection.finish_frame();
+                    self.finished_frames += 1;
                     memory.write_interrupt_flag(Interrupt::VBlank, true);
                 }
                 if self.cycles_on_current_line >= CYCLES_PER_LINE {
                     self.advance_to_next_line();
-                    memory.data[LCD_STATUS_ADDRESS] = (&self.current_lcd_status).into();
-                    memory.data[CURRENT_LINE_ADDRESS] = self.current_line;
+                    self.finish_line_change(memory);
                 }
             }
         }
     }
 
+    /// Write the current values of the CGB palette registers to memory, so they can be read
+    fn update_color_palette_registers(&self, memory: &mut Memory) {
+        memory.data[BACKGROUND_PALETTE_SPECIFICATION_ADDRESS] =
+            self.background_color_palettes.read_specification();
+        memory.data[BACKGROUND_PALETTE_DATA_ADDRESS] = self.background_color_palettes.read_data();
+        memory.data[OBJECT_PALETTE_SPECIFICATION_ADDRESS] =
+            self.object_color_palettes.read_specification();
+        memory.data[OBJECT_PALETTE_DATA_ADDRESS] = self.object_color_palettes.read_data();
+    }
+
     /// Render the current line into the video connection.
+    ///
+    /// The background map is 256x256 pixels big. SCX and SCY select the visible area, which wraps around at the edges.
+    ///
+    /// The window is drawn on top of the background, starting at WX - 7 and WY. It does not scroll and uses its own line counter.
+    ///
+    /// If [background_window_enable](LcdControl::background_window_enable) is not set, the background and the window are blank and only objects are drawn. In CGB mode the bit has a different meaning and is ignored.
     pub fn render_line(&mut self, memory: &mut Memory) {
-        // let background_tilemap =
-        //     self.get_tile_map(&self.current_lcd_control.background_tilemap);
+        let background_tilemap = memory.get_tile_map(&self.current_lcd_control.background_tilemap);
         let window_tilemap = memory.get_tile_map(&self.current_lcd_control.window_tilemap);
-        let window_background_tile_data =
-            memory.get_tile_data(&self.current_lcd_control.window_bg_tile_data);
-        // let object_tile_data = self.get_tile_data(&TileDataArea::First);
-        // let window_palette = &self.background_palette;
-        let background_palette = &self.background_palette;
+        let background_attribute_map =
+            self.get_attribute_map(&self.current_lcd_control.background_tilemap);
+        let window_attribute_map = self.get_attribute_map(&self.current_lcd_control.window_tilemap);
+        let window_background_tile_data = &self.current_lcd_control.window_bg_tile_data;
+        let tiles = self
+            .tile_cache
+            .take()
+            .unwrap_or_else(|| memory.get_all_tile_data());
 
         let line = self.current_line;
+        let scroll_y = memory.data[SCROLL_Y_ADDRESS];
+        let scroll_x = memory.data[SCROLL_X_ADDRESS];
+        let window_y = memory.data[WINDOW_Y_ADDRESS];
+        let window_x = memory.data[WINDOW_X_ADDRESS] as usize;
+
+        let background_y = line.wrapping_add(scroll_y);
+        let relevant_background_tiles = background_tilemap.get_tiles_for_line(background_y);
+        let relevant_background_attributes =
+            background_attribute_map.get_tiles_for_line(background_y);
+        let y_offset_in_tile = background_y % 8;
+
+        let background_enabled = self.current_lcd_control.background_window_enable || self.cgb_mode;
+        let window_visible = background_enabled
+            && self.current_lcd_control.window_enable
+            && line >= window_y
+            && window_x <= 166;
+        let relevant_window_tiles = window_tilemap.get_tiles_for_line(self.window_line);
+        let relevant_window_attributes = window_attribute_map.get_tiles_for_line(self.window_line);
+        let y_offset_in_window_tile = self.window_line % 8;
+
+        let mut background_pixels = [0u8; SCREEN_WIDTH];
+        let mut background_attributes = [0u8; SCREEN_WIDTH];
+        for (x, (pixel, attributes)) in background_pixels
+            .iter_mut()
+            .zip(background_attributes.iter_mut())
+            .enumerate()
+        {
+            (*pixel, *attributes) = if !background_enabled {
+                (0, 0)
+            } else if window_visible && x + 7 >= window_x {
+                let window_position_x = x + 7 - window_x;
+                let tile = relevant_window_tiles[window_position_x / 8];
+                let tile_data = &tiles[window_background_tile_data.get_tile_position(tile)];
+                (
+                    tile_data.get_line(y_offset_in_window_tile as usize)[window_position_x % 8],
+                    relevant_window_attributes[window_position_x / 8],
+                )
+            } else {
+                let background_x = (x as u8).wrapping_add(scroll_x);
+                let tile = relevant_background_tiles[(background_x / 8) as usize];
+                let tile_data = &tiles[window_background_tile_data.get_tile_position(tile)];
+                (
+                    tile_data.get_line(y_offset_in_tile as usize)[(background_x % 8) as usize],
+                    relevant_background_attributes[(background_x / 8) as usize],
+                )
+            };
+        }
+
+        let mut colors = std::array::from_fn(|x| {
+            self.get_background_palette(background_attributes[x])
+                .get_color(background_pixels[x] as usize)
+                .get_rgba_with(&self.dmg_colors)
+        });
+        if self.current_lcd_control.object_enable {
+            self.render_objects(&tiles, &background_pixels, &mut colors);
+        }
+        self.tile_cache = Some(tiles);
+        for (x, color) in colors.into_iter().enumerate() {
+            self.display_connection.set_pixel(x, line as usize, color)
+        }
+
+        if window_visible {
+            self.window_line += 1;
+        }
+    }
+
+    /// Get the palette that is used to draw a background or window tile with the CGB tile `attributes`.
+    ///
+    /// The attributes are ignored outside of CGB mode.
+    fn get_background_palette(&self, attributes: u8) -> &Palette {
+        if self.cgb_mode {
+            let palette = attributes & ATTRIBUTES_PALETTE_MASK;
+            return self
+                .background_color_palettes
+                .get_background_palette(palette as usize);
+        }
+        &self.background_palette
+    }
+
+    /// Get the palette that is used to draw `object`
+    fn get_object_palette(&self, object: &ObjectAttributes) -> &Palette {
+        if self.cgb_mode {
+            return self
+                .object_color_palettes
+                .get_object_palette(object.cgb_palette as usize);
+        }
+        match object.palette {
+            ObjectPalette::First => &self.first_object_palette,
+            ObjectPalette::Second => &self.second_object_palette,
+        }
+    }
 
-        // let relevant_window_tiles = window_tilemap.get_tiles_for_line(line);
-        let relevant_background_tiles = window_tilemap.get_tiles_for_line(line);
-
-        // for x in 0..160 {
-        //     let window_tile_index = x / 8;
-        //     let window_tile_row = x % 8;
-        // }
-        let y_offset_in_tile = line % 8;
-        for (index, tile) in relevant_background_tiles.iter().enumerate() {
-            let tile_data = &window_background_tile_data[*tile as usize];
-            let pixels = tile_data.get_line(y_offset_in_tile as usize);
-            for (pixel_index, pixel) in pixels.iter().enumerate() {
-                let x = (index * 8) + pixel_index;
-                if x >= 160 {
-                    break;
+    /// Draw the [current_objects](Video::current_objects) on top of the colors of the current line.
+    ///
+    /// Objects that come first in [current_objects](Video::current_objects) are drawn on top of later ones. Color index 0 is transparent.
+    /// Objects with [draw_under_bg_and_window](ObjectAttributes::draw_under_bg_and_window) are only visible where `background_pixels` is 0.
+    fn render_objects(
+        &self,
+        tiles: &[TileData],
+        background_pixels: &[u8; SCREEN_WIDTH],
+        colors: &mut [(u8, u8, u8, u8); SCREEN_WIDTH],
+    ) {
+        let object_height = self.current_lcd_control.object_size.get_height() as usize;
+        let line = self.current_line as usize;
+
+        for object in self.current_objects.iter().rev() {
+            // The object size can change after the OAM scan, so the object may not cover this line anymore
+            let Some(mut row) = (line + 16).checked_sub(object.y_position as usize) else {
+                continue;
+            };
+            if row >= object_height {
+                continue;
+            }
+            if object.y_flip {
+                row = object_height - 1 - row;
+            }
+            // In 8x16 mode the lowest bit of the tile index selects the upper or lower half
+            let tile = match self.current_lcd_control.object_size {
+                ObjectSize::EightByEight => object.tile,
+                ObjectSize::EightBySixteen => (object.tile & 0b11111110) | (row / 8) as u8,
+            };
+            let mut pixels = tiles[TileDataArea::First.get_tile_position(tile)].get_line(row % 8);
+            if object.x_flip {
+                pixels.reverse();
+            }
+            let palette = self.get_object_palette(object);
+            for (pixel_index, pixel) in pixels.into_iter().enumerate() {
+                let Some(x) = (object.x_position as usize + pixel_index).checked_sub(8) else {
+                    continue;
+                };
+                if x >= SCREEN_WIDTH || pixel == 0 {
+                    continue;
                 }
-                let color = background_palette.get_color(*pixel as usize).get_rgba();
-                if *pixel != 0 {
-                    let _x = 8;
+                if object.draw_under_bg_and_window && background_pixels[x] != 0 {
+                    continue;
                 }
-                self.display_connection.set_pixel(x, line as usize, color)
+                colors[x] = palette
+                    .get_color(pixel as usize)
+                    .get_rgba_with(&self.dmg_colors);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::display_connection::{BufferDisplayConnection, SCREEN_WIDTH};
+    use super::lcd_control::TileDataArea;
+    use super::lcd_status::{PpuMode, StatInterruptSource};
+    use super::palette::{Color, Palette};
+    use super::tile::TileData;
+    use super::{
+        Video, BACKGROUND_SIZE, DEFAULT_MAX_SPRITES_PER_LINE, TILE_ATLAS_HEIGHT, TILE_ATLAS_WIDTH,
+    };
+    use crate::cpu::interrupt_controller::InterruptController;
+    use crate::cpu::{CpuState, Interrupt};
+    use crate::emulator::Emulator;
+    use crate::memory::memory_addresses::{
+        BACKGROUND_PALETTE_DATA_ADDRESS, BACKGROUND_PALETTE_SPECIFICATION_ADDRESS,
+        CURRENT_LINE_ADDRESS, FIRST_BG_TILE_DATA_AREA, FIRST_BG_TILE_MAP_AREA,
+        INTERRUPT_LINE_ADDRESS, LCD_CONTROL_ADDRESS, LCD_STATUS_ADDRESS,
+        OBJECT_ATTRIBUTE_MEMORY_AREA, OBJECT_TILE_DATA_AREA, SCROLL_Y_ADDRESS,
+        SECOND_BG_TILE_MAP_AREA, VIDEO_RAM_BANK_ADDRESS, WINDOW_X_ADDRESS, WINDOW_Y_ADDRESS,
+    };
+    use crate::memory::{Memory, MemoryController, MemoryDevice};
+
+    /// Create a video with the default palettes that renders `line` with the objects on that line
+    fn setup_video(memory: &Memory, lcd_control: u8, line: u8) -> Video<BufferDisplayConnection> {
+        let mut video = Video::new(BufferDisplayConnection::new());
+        video.current_lcd_control = lcd_control.into();
+        video.background_palette = Palette::from_background_register(0b11100100);
+        video.first_object_palette = Palette::from_object_register(0b11100100);
+        video.second_object_palette = Palette::from_object_register(0b01100100);
+        video.current_line = line;
+        video.current_objects = memory.get_relevant_object_attributes(&video, line);
+        video
+    }
+
+    /// Get the pixels of a line of the frame that is currently drawn
+    fn get_line(video: &Video<BufferDisplayConnection>, line: usize) -> Vec<(u8, u8, u8, u8)> {
+        let line_size = SCREEN_WIDTH * 4;
+        video.display_connection.current_frame()[line * line_size..(line + 1) * line_size]
+            .chunks_exact(4)
+            .map(|pixel| (pixel[0], pixel[1], pixel[2], pixel[3]))
+            .collect()
+    }
+
+    #[test]
+    fn scroll_y_moves_the_background_down_by_whole_tiles() {
+        let mut memory = Memory::new();
+        // The second row of the tile map consists of the black tile 1
+        let tile_map_start = *FIRST_BG_TILE_MAP_AREA.start();
+        memory.data[tile_map_start + 32..tile_map_start + 64].fill(1);
+        let tile_data_start = *FIRST_BG_TILE_DATA_AREA.start();
+        memory.data[tile_data_start + 16..tile_data_start + 32].fill(0xFF);
+
+        let mut video = setup_video(&memory, 0b10010001, 0);
+
+        video.render_line(&mut memory);
+        assert!(get_line(&video, 0)
+            .iter()
+            .all(|pixel| *pixel == Color::White.get_rgba()));
+
+        memory.data[SCROLL_Y_ADDRESS] = 8;
+        video.render_line(&mut memory);
+        assert!(get_line(&video, 0)
+            .iter()
+            .all(|pixel| *pixel == Color::Black.get_rgba()));
+    }
+
+    #[test]
+    fn tile_cache_matches_the_tile_data_in_memory() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(0x8010, 0xFF);
+        memory.write(0x9000, 0x0F);
+
+        for area in [TileDataArea::First, TileDataArea::Second] {
+            let uncached = memory.memory.get_tile_data(&area);
+            let cached = memory.graphics.get_tile_data(&memory.memory);
+            for index in 0..=255u8 {
+                assert_eq!(
+                    cached[area.get_tile_position(index)].pixels,
+                    uncached[index as usize].pixels
+                );
+            }
+        }
+
+        // Writing the tile data invalidates the cache
+        memory.write(0x8010, 0x00);
+        let cached = memory.graphics.get_tile_data(&memory.memory);
+        assert_eq!(cached[1].pixels, [0; 64]);
+    }
+
+    #[test]
+    fn custom_dmg_colors_are_used_for_rendering() {
+        let mut memory = Memory::new();
+        let tile_data_start = *FIRST_BG_TILE_DATA_AREA.start();
+        memory.data[tile_data_start..tile_data_start + 2].copy_from_slice(&[0xFF, 0x00]);
+
+        let mut video = setup_video(&memory, 0b10010001, 0);
+        video.set_dmg_colors([
+            [0x9B, 0xBC, 0x0F, 0xFF],
+            [0x8B, 0xAC, 0x0F, 0xFF],
+            [0x30, 0x62, 0x30, 0xFF],
+            [0x0F, 0x38, 0x0F, 0xFF],
+        ]);
+
+        video.render_line(&mut memory);
+        assert_eq!(get_line(&video, 0)[0], (0x8B, 0xAC, 0x0F, 0xFF));
+    }
+
+    #[test]
+    fn cgb_mode_renders_with_the_color_palettes() {
+        let mut memory = Memory::new();
+        let mut video = setup_video(&memory, 0b10010001, 0);
+        video.set_cgb_mode(true);
+
+        // Set the first color of the first background palette to red
+        let specification = BACKGROUND_PALETTE_SPECIFICATION_ADDRESS as u16;
+        video.write(&mut memory, specification, 0b10000000);
+        video.write(&mut memory, BACKGROUND_PALETTE_DATA_ADDRESS as u16, 0x1F);
+        video.write(&mut memory, BACKGROUND_PALETTE_DATA_ADDRESS as u16, 0x00);
+
+        video.render_line(&mut memory);
+        assert!(get_line(&video, 0)
+            .iter()
+            .all(|pixel| *pixel == (0xFF, 0x00, 0x00, 0xFF)));
+    }
+
+    #[test]
+    fn cgb_background_uses_the_palette_from_the_tile_attributes() {
+        let mut memory = Memory::new();
+        let mut video = setup_video(&memory, 0b10010001, 0);
+        video.set_cgb_mode(true);
+
+        // Set the first color of the second background palette to red
+        let specification = BACKGROUND_PALETTE_SPECIFICATION_ADDRESS as u16;
+        video.write(&mut memory, specification, 0b10001000);
+        video.write(&mut memory, BACKGROUND_PALETTE_DATA_ADDRESS as u16, 0x1F);
+        video.write(&mut memory, BACKGROUND_PALETTE_DATA_ADDRESS as u16, 0x00);
+
+        // The second tile of the first row uses the second palette
+        let tile_map_start = *FIRST_BG_TILE_MAP_AREA.start();
+        video.write(&mut memory, VIDEO_RAM_BANK_ADDRESS as u16, 1);
+        video.write(&mut memory, tile_map_start as u16 + 1, 1);
+        video.write(&mut memory, VIDEO_RAM_BANK_ADDRESS as u16, 0);
+        assert_eq!(memory.data[tile_map_start + 1], 0);
+
+        video.render_line(&mut memory);
+        let line = get_line(&video, 0);
+        assert_eq!(line[..8], [(0xFF, 0xFF, 0xFF, 0xFF); 8]);
+        assert_eq!(line[8..16], [(0xFF, 0x00, 0x00, 0xFF); 8]);
+
+        // The CGB mode and the attributes are part of the state
+        let mut restored = Video::new(BufferDisplayConnection::new());
+        restored.load_state(&video.save_state());
+        restored.render_line(&mut memory);
+        assert_eq!(get_line(&restored, 0), line);
+    }
+
+    #[test]
+    fn window_is_drawn_over_the_background() {
+        let mut memory = Memory::new();
+        // The window uses the second tile map, which consists of the black tile 1
+        memory.data[SECOND_BG_TILE_MAP_AREA].fill(1);
+        let tile_data_start = *FIRST_BG_TILE_DATA_AREA.start();
+        memory.data[tile_data_start + 16..tile_data_start + 32].fill(0xFF);
+        memory.data[WINDOW_X_ADDRESS] = 7 + 80;
+        memory.data[WINDOW_Y_ADDRESS] = 1;
+
+        let mut video = setup_video(&memory, 0b11110001, 0);
+
+        video.render_line(&mut memory);
+        assert!(get_line(&video, 0)
+            .iter()
+            .all(|pixel| *pixel == Color::White.get_rgba()));
+        assert_eq!(video.window_line, 0);
+
+        video.current_line = 1;
+        video.render_line(&mut memory);
+        let line = get_line(&video, 1);
+        assert!(line[..80]
+            .iter()
+            .all(|pixel| *pixel == Color::White.get_rgba()));
+        assert!(line[80..]
+            .iter()
+            .all(|pixel| *pixel == Color::Black.get_rgba()));
+        assert_eq!(video.window_line, 1);
+
+        memory.data[WINDOW_X_ADDRESS] = 7;
+        video.current_line = 2;
+        video.render_line(&mut memory);
+        assert_eq!(get_line(&video, 2)[0], Color::Black.get_rgba());
+    }
+
+    #[test]
+    fn objects_are_drawn_on_top_of_the_background() {
+        let mut memory = Memory::new();
+        let tile_data_start = *OBJECT_TILE_DATA_AREA.start();
+        memory.data[tile_data_start + 16..tile_data_start + 32].fill(0xFF);
+        // A single object with the black tile 1 at x = 10, y = 0
+        let object_start = *OBJECT_ATTRIBUTE_MEMORY_AREA.start();
+        memory.data[object_start..object_start + 4].copy_from_slice(&[16, 8 + 10, 1, 0]);
+
+        let mut video = setup_video(&memory, 0b10010011, 0);
+
+        video.render_line(&mut memory);
+        let line = get_line(&video, 0);
+        assert_eq!(line[9], Color::White.get_rgba());
+        assert!(line[10..18]
+            .iter()
+            .all(|pixel| *pixel == Color::Black.get_rgba()));
+        assert_eq!(line[18], Color::White.get_rgba());
+
+        // Objects are not drawn if they are disabled
+        video.current_lcd_control = 0b10010001.into();
+        video.render_line(&mut memory);
+        assert!(get_line(&video, 0)
+            .iter()
+            .all(|pixel| *pixel == Color::White.get_rgba()));
+    }
+
+    #[test]
+    fn disabled_background_is_blank_but_objects_are_drawn() {
+        let mut memory = Memory::new();
+        // The whole background consists of the black tile 0, the object uses the light gray tile 1
+        let tile_data_start = *OBJECT_TILE_DATA_AREA.start();
+        memory.data[tile_data_start..tile_data_start + 16].fill(0xFF);
+        for row in 0..8 {
+            let row_start = tile_data_start + 16 + row * 2;
+            memory.data[row_start..row_start + 2].copy_from_slice(&[0xFF, 0x00]);
+        }
+        let object_start = *OBJECT_ATTRIBUTE_MEMORY_AREA.start();
+        memory.data[object_start..object_start + 4].copy_from_slice(&[16, 8 + 10, 1, 0]);
+
+        let mut video = setup_video(&memory, 0b10010011, 0);
+
+        video.render_line(&mut memory);
+        assert_eq!(get_line(&video, 0)[0], Color::Black.get_rgba());
+
+        video.current_lcd_control = 0b10010010.into();
+        video.render_line(&mut memory);
+        let line = get_line(&video, 0);
+        assert!(line[..10]
+            .iter()
+            .chain(&line[18..])
+            .all(|pixel| *pixel == Color::White.get_rgba()));
+        assert_eq!(line[10..18], [Color::LightGray.get_rgba(); 8]);
+    }
+
+    #[test]
+    fn full_background_contains_the_whole_tilemap() {
+        let mut memory = Memory::new();
+        // Tile 1 is black and placed at tile position (0,0), everything else uses the white tile 0
+        let tile_data_start = *OBJECT_TILE_DATA_AREA.start();
+        memory.data[tile_data_start + 16..tile_data_start + 32].fill(0xFF);
+        memory.data[*FIRST_BG_TILE_MAP_AREA.start()] = 1;
+        // Scrolling is ignored
+        memory.data[SCROLL_Y_ADDRESS] = 0x42;
+
+        let video = setup_video(&memory, 0b10010001, 0);
+
+        let buffer = video.render_full_background(&memory);
+        assert_eq!(buffer.len(), BACKGROUND_SIZE * BACKGROUND_SIZE * 4);
+        let pixel = |x: usize, y: usize| {
+            let start = (y * BACKGROUND_SIZE + x) * 4;
+            (
+                buffer[start],
+                buffer[start + 1],
+                buffer[start + 2],
+                buffer[start + 3],
+            )
+        };
+        assert_eq!(pixel(0, 0), Color::Black.get_rgba());
+        assert_eq!(pixel(7, 7), Color::Black.get_rgba());
+        assert_eq!(pixel(8, 0), Color::White.get_rgba());
+        assert_eq!(pixel(0, 8), Color::White.get_rgba());
+        assert_eq!(pixel(255, 255), Color::White.get_rgba());
+    }
+
+    #[test]
+    fn tile_atlas_contains_all_tiles() {
+        let mut memory = Memory::new();
+        let tile: [u8; 16] = [
+            0x3C, 0x7E, 0x42, 0x42, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x5E, 0x7E, 0x0A, 0x7C, 0x56,
+            0x38, 0x7C,
+        ];
+        // Tile 0x117 is in the second half of the tile data, in row 17 and column 7 of the atlas
+        let tile_start = *FIRST_BG_TILE_DATA_AREA.start() + 0x117 * 16;
+        memory.data[tile_start..tile_start + 16].copy_from_slice(&tile);
+
+        let video = setup_video(&memory, 0b10010001, 0);
+
+        let buffer = video.render_tile_atlas(&memory);
+        assert_eq!((TILE_ATLAS_WIDTH, TILE_ATLAS_HEIGHT), (128, 192));
+        assert_eq!(buffer.len(), TILE_ATLAS_WIDTH * TILE_ATLAS_HEIGHT * 4);
+
+        let decoded = TileData::from(tile);
+        for y in 0..8 {
+            for x in 0..8 {
+                let start = ((17 * 8 + y) * TILE_ATLAS_WIDTH + 7 * 8 + x) * 4;
+                let (red, green, blue, alpha) = video
+                    .background_palette
+                    .get_color(decoded.get_line(y)[x] as usize)
+                    .get_rgba();
+                assert_eq!(buffer[start..start + 4], [red, green, blue, alpha]);
             }
         }
     }
+
+    #[test]
+    fn only_ten_objects_are_drawn_and_smaller_x_wins() {
+        let mut memory = Memory::new();
+        let tile_data_start = *OBJECT_TILE_DATA_AREA.start();
+        memory.data[tile_data_start + 16..tile_data_start + 32].fill(0xFF);
+        let objects: [[u8; 4]; 12] = [
+            // The first object in OAM overlaps the second one, but has a larger X coordinate
+            [16, 8 + 24, 1, 0b00010000],
+            [16, 8 + 20, 1, 0b00000000],
+            [16, 8 + 40, 1, 0b00000000],
+            [16, 8 + 50, 1, 0b00000000],
+            [16, 8 + 60, 1, 0b00000000],
+            [16, 8 + 70, 1, 0b00000000],
+            [16, 8 + 80, 1, 0b00000000],
+            [16, 8 + 90, 1, 0b00000000],
+            [16, 8 + 100, 1, 0b00000000],
+            [16, 8 + 110, 1, 0b00000000],
+            // These are past the limit and not drawn
+            [16, 8, 1, 0b00000000],
+            [16, 8 + 120, 1, 0b00000000],
+        ];
+        let object_start = *OBJECT_ATTRIBUTE_MEMORY_AREA.start();
+        memory.data[object_start..object_start + 48].copy_from_slice(&objects.concat());
+
+        let mut video = setup_video(&memory, 0b10010011, 0);
+        assert_eq!(video.current_objects.len(), 10);
+
+        video.render_line(&mut memory);
+        let line = get_line(&video, 0);
+        let black = Color::Black.get_rgba();
+        let light_gray = Color::LightGray.get_rgba();
+        let white = Color::White.get_rgba();
+        assert_eq!(line[..8], [white; 8]);
+        assert_eq!(line[20..28], [black; 8]);
+        assert_eq!(line[28..32], [light_gray; 4]);
+        assert_eq!(line[110..118], [black; 8]);
+        assert_eq!(line[120..128], [white; 8]);
+    }
+
+    #[test]
+    fn tall_objects_consist_of_two_tiles() {
+        let mut memory = Memory::new();
+        let tile_data_start = *OBJECT_TILE_DATA_AREA.start();
+        // Tile 2 is black and tile 3 is light gray
+        memory.data[tile_data_start + 32..tile_data_start + 48].fill(0xFF);
+        for row in 0..8 {
+            let row_start = tile_data_start + 48 + row * 2;
+            memory.data[row_start..row_start + 2].copy_from_slice(&[0xFF, 0x00]);
+        }
+        // The lowest bit of the tile index is ignored
+        let object_start = *OBJECT_ATTRIBUTE_MEMORY_AREA.start();
+        memory.data[object_start..object_start + 4].copy_from_slice(&[16, 8, 3, 0]);
+
+        let mut video = setup_video(&memory, 0b10010111, 0);
+        for line in 0..17 {
+            video.current_line = line;
+            video.current_objects = memory.get_relevant_object_attributes(&video, line);
+            video.render_line(&mut memory);
+        }
+
+        assert_eq!(get_line(&video, 0)[..8], [Color::Black.get_rgba(); 8]);
+        assert_eq!(get_line(&video, 7)[..8], [Color::Black.get_rgba(); 8]);
+        assert_eq!(get_line(&video, 8)[..8], [Color::LightGray.get_rgba(); 8]);
+        assert_eq!(get_line(&video, 15)[..8], [Color::LightGray.get_rgba(); 8]);
+        assert_eq!(get_line(&video, 16)[..8], [Color::White.get_rgba(); 8]);
+    }
+
+    /// Render `line` with a single object with `flags` in the top left corner.
+    ///
+    /// Only the left half of the first row of the object is set. If `background` is set, the first row of the background is light gray.
+    fn render_single_object(flags: u8, line: u8, background: bool) -> Vec<(u8, u8, u8, u8)> {
+        let mut memory = Memory::new();
+        let tile_data_start = *OBJECT_TILE_DATA_AREA.start();
+        if background {
+            memory.data[tile_data_start..tile_data_start + 2].copy_from_slice(&[0xFF, 0x00]);
+        }
+        memory.data[tile_data_start + 16..tile_data_start + 18].copy_from_slice(&[0xF0, 0xF0]);
+        let object_start = *OBJECT_ATTRIBUTE_MEMORY_AREA.start();
+        memory.data[object_start..object_start + 4].copy_from_slice(&[16, 8, 1, flags]);
+
+        let mut video = setup_video(&memory, 0b10010011, line);
+
+        video.render_line(&mut memory);
+        get_line(&video, line as usize)[..8].to_vec()
+    }
+
+    #[test]
+    fn object_x_flip_reverses_the_row() {
+        let black = Color::Black.get_rgba();
+        let white = Color::White.get_rgba();
+        assert_eq!(
+            render_single_object(0b00000000, 0, false),
+            [black, black, black, black, white, white, white, white]
+        );
+        assert_eq!(
+            render_single_object(0b00100000, 0, false),
+            [white, white, white, white, black, black, black, black]
+        );
+    }
+
+    #[test]
+    fn object_y_flip_inverts_the_rows() {
+        let white = Color::White.get_rgba();
+        assert_eq!(render_single_object(0b01000000, 0, false), [white; 8]);
+        assert_eq!(
+            render_single_object(0b01000000, 7, false)[0],
+            Color::Black.get_rgba()
+        );
+    }
+
+    #[test]
+    fn object_palette_flag_selects_the_second_palette() {
+        assert_eq!(
+            render_single_object(0b00010000, 0, false)[0],
+            Color::LightGray.get_rgba()
+        );
+    }
+
+    #[test]
+    fn object_priority_flag_hides_it_behind_the_background() {
+        let black = Color::Black.get_rgba();
+        let light_gray = Color::LightGray.get_rgba();
+        assert_eq!(render_single_object(0b00000000, 0, true)[0], black);
+        assert_eq!(render_single_object(0b10000000, 0, true), [light_gray; 8]);
+        // The object is still visible where the background has color 0
+        assert_eq!(
+            render_single_object(0b10000000, 0, false)[0],
+            Color::Black.get_rgba()
+        );
+    }
+
+    #[test]
+    fn objects_that_are_too_small_after_the_oam_scan_are_skipped() {
+        let mut memory = Memory::new();
+        // The objects use the light gray tile 0
+        let tile_data_start = *OBJECT_TILE_DATA_AREA.start();
+        for row in 0..8 {
+            let row_start = tile_data_start + row * 2;
+            memory.data[row_start..row_start + 2].copy_from_slice(&[0xFF, 0x00]);
+        }
+        let object_start = *OBJECT_ATTRIBUTE_MEMORY_AREA.start();
+        memory.data[object_start..object_start + 8]
+            .copy_from_slice(&[16, 8, 0, 0b01000000, 16, 16, 0, 0]);
+
+        // The OAM scan uses 8x16 objects, but they are drawn after switching to 8x8 objects
+        let mut video = setup_video(&memory, 0b10010110, 10);
+        assert_eq!(video.current_objects.len(), 2);
+        video.current_lcd_control = 0b10010010.into();
+
+        video.render_line(&mut memory);
+        assert!(get_line(&video, 10)
+            .iter()
+            .all(|pixel| *pixel == Color::White.get_rgba()));
+    }
+
+    #[test]
+    fn stat_interrupt_reports_line_y_compare_source() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(INTERRUPT_LINE_ADDRESS as u16, 5);
+        // Only enable the LYC source
+        memory.write(LCD_STATUS_ADDRESS as u16, 0b01000000);
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+
+        while memory.graphics.current_line < 5 {
+            assert!(!memory.read_interrupt_flag(Interrupt::LcdStat));
+            memory.process_cycle();
+        }
+
+        assert!(memory.read_interrupt_flag(Interrupt::LcdStat));
+        assert_eq!(
+            memory.graphics.last_stat_source(),
+            Some(StatInterruptSource::LineYCompare)
+        );
+        assert_eq!(
+            memory.read(LCD_STATUS_ADDRESS as u16) & 0b00000100,
+            0b00000100
+        );
+    }
+
+    #[test]
+    fn writing_the_current_line_to_lyc_sets_the_flag() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(INTERRUPT_LINE_ADDRESS as u16, 100);
+        memory.write(LCD_STATUS_ADDRESS as u16, 0b01000000);
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+        for _ in 0..114 * 3 + 10 {
+            memory.process_cycle();
+        }
+        assert!(!memory.read_interrupt_flag(Interrupt::LcdStat));
+
+        memory.write(INTERRUPT_LINE_ADDRESS as u16, 3);
+        assert!(memory.read_interrupt_flag(Interrupt::LcdStat));
+        assert_eq!(
+            memory.read(LCD_STATUS_ADDRESS as u16) & 0b00000100,
+            0b00000100
+        );
+
+        // The interrupt is only requested when the flag becomes set
+        memory.write_interrupt_flag(Interrupt::LcdStat, false);
+        memory.write(INTERRUPT_LINE_ADDRESS as u16, 3);
+        assert!(!memory.read_interrupt_flag(Interrupt::LcdStat));
+
+        memory.write(INTERRUPT_LINE_ADDRESS as u16, 4);
+        assert_eq!(memory.read(LCD_STATUS_ADDRESS as u16) & 0b00000100, 0);
+    }
+
+    #[test]
+    fn current_line_register_counts_through_all_lines() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+        // LY is read only
+        memory.write(CURRENT_LINE_ADDRESS as u16, 0x42);
+
+        for line in 0..154 {
+            for _ in 0..114 {
+                assert_eq!(memory.read(CURRENT_LINE_ADDRESS as u16), line);
+                memory.process_cycle();
+            }
+        }
+        assert_eq!(memory.read(CURRENT_LINE_ADDRESS as u16), 0);
+    }
+
+    #[test]
+    fn disabling_the_lcd_resets_the_current_line() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+        for _ in 0..114 * 3 + 50 {
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(CURRENT_LINE_ADDRESS as u16), 3);
+
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b00000000);
+        for _ in 0..114 * 3 {
+            assert_eq!(memory.read(CURRENT_LINE_ADDRESS as u16), 0);
+            assert_eq!(memory.read(LCD_STATUS_ADDRESS as u16) & 0b11, 0);
+            memory.process_cycle();
+        }
+
+        // The PPU restarts with the OAM scan of the first line
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+        assert_eq!(memory.read(LCD_STATUS_ADDRESS as u16) & 0b11, 2);
+        for _ in 0..114 {
+            assert_eq!(memory.read(CURRENT_LINE_ADDRESS as u16), 0);
+            memory.process_cycle();
+        }
+        assert_eq!(memory.read(CURRENT_LINE_ADDRESS as u16), 1);
+    }
+
+    #[test]
+    fn ppu_goes_through_the_modes_of_a_line() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+
+        let mut modes = vec![memory.ppu_mode()];
+        for _ in 0..114 {
+            memory.process_cycle();
+            if modes.last() != Some(&memory.ppu_mode()) {
+                modes.push(memory.ppu_mode());
+            }
+        }
+        assert_eq!(
+            modes,
+            [
+                PpuMode::Oam,
+                PpuMode::TransferringData,
+                PpuMode::HBlank,
+                PpuMode::Oam
+            ]
+        );
+        assert_eq!(memory.current_line(), 1);
+        assert_eq!(memory.graphics.current_line(), 1);
+    }
+
+    #[test]
+    fn stat_interrupt_reports_mode_source() {
+        let mut memory = MemoryController::new_for_tests();
+        // Only enable the HBlank source
+        memory.write(LCD_STATUS_ADDRESS as u16, 0b00001000);
+        memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+        assert_eq!(memory.graphics.last_stat_source(), None);
+
+        for _ in 0..114 * 2 {
+            memory.process_cycle();
+        }
+
+        assert!(memory.read_interrupt_flag(Interrupt::LcdStat));
+        assert_eq!(
+            memory.graphics.last_stat_source(),
+            Some(StatInterruptSource::HBlank)
+        );
+    }
+
+    #[test]
+    fn paused_ppu_does_not_advance() {
+        let mut emulator = Emulator::new(CpuState::new(), MemoryController::new_for_tests());
+        emulator
+            .memory
+            .write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+        emulator.run_cycles(114 * 3 + 10);
+        assert_eq!(emulator.memory.graphics.current_line, 3);
+
+        emulator.memory.graphics.set_paused(true);
+        let cycles_on_current_line = emulator.memory.graphics.cycles_on_current_line;
+        emulator.run_cycles(114 * 10);
+        assert_eq!(emulator.memory.graphics.current_line, 3);
+        assert_eq!(
+            emulator.memory.graphics.cycles_on_current_line,
+            cycles_on_current_line
+        );
+
+        emulator.memory.graphics.set_paused(false);
+        emulator.run_cycles(114 - 10);
+        assert_eq!(emulator.memory.graphics.current_line, 4);
+    }
+
+    #[test]
+    fn max_sprites_per_line_limits_the_oam_scan() {
+        let scan_second_line = |max_sprites_per_line: Option<usize>| {
+            let mut memory = MemoryController::new_for_tests();
+            if let Some(max_sprites_per_line) = max_sprites_per_line {
+                memory
+                    .graphics
+                    .set_max_sprites_per_line(max_sprites_per_line);
+            }
+            // Place all 40 objects next to each other on the first lines
+            for (index, object) in memory.memory.data[OBJECT_ATTRIBUTE_MEMORY_AREA]
+                .chunks_exact_mut(4)
+                .enumerate()
+            {
+                object.copy_from_slice(&[16, 8 + index as u8, 0, 0]);
+            }
+            memory.write(LCD_CONTROL_ADDRESS as u16, 0b10000000);
+            // Wait for the OAM scan of the second line
+            for _ in 0..114 + 20 {
+                memory.process_cycle();
+            }
+            assert_eq!(memory.graphics.current_line, 1);
+            memory.graphics.current_objects
+        };
+
+        let objects = scan_second_line(None);
+        assert_eq!(objects.len(), DEFAULT_MAX_SPRITES_PER_LINE);
+        assert_eq!(objects[9].x_position, 8 + 9);
+
+        let objects = scan_second_line(Some(40));
+        assert_eq!(objects.len(), 40);
+        assert!(objects
+            .iter()
+            .enumerate()
+            .all(|(index, object)| object.x_position == 8 + index as u8));
+    }
 }
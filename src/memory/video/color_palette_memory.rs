@@ -0,0 +1,156 @@
+use super::palette::Palette;
+
+/// Contains the eight CGB color palettes for either the background or the objects.
+///
+/// Each palette consists of four RGB555 colors with two bytes each, so the memory is 64 bytes big.
+/// It is not mapped into the address space. Instead a specification register selects a byte that can then be accessed with the data register.
+///
+/// The specification register contains the selected index in the lower 6 bits. If bit 7 is set, the index is incremented after each write to the data register.
+///
+/// See <https://gbdev.io/pandocs/Palettes.html#lcd-color-palettes-cgb-only> for more details.
+#[derive(Clone)]
+pub struct ColorPaletteMemory {
+    /// The raw palette memory
+    data: [u8; 64],
+    /// The byte that is accessed with the data register
+    index: u8,
+    /// Increment the index after each write to the data register
+    auto_increment: bool,
+    /// The decoded palettes for the background and window layer
+    background_palettes: [Palette; 8],
+    /// The decoded palettes for the object layer
+    object_palettes: [Palette; 8],
+}
+
+const AUTO_INCREMENT: u8 = 0b10000000;
+const UNUSED_BIT: u8 = 0b01000000;
+const INDEX_MASK: u8 = 0b00111111;
+
+impl ColorPaletteMemory {
+    /// Create a new palette memory with all colors set to white
+    pub fn new() -> ColorPaletteMemory {
+        let mut memory = ColorPaletteMemory {
+            data: [0xFF; 64],
+            index: 0,
+            auto_increment: false,
+            background_palettes: std::array::from_fn(|_| Palette::from_background_register(0)),
+            object_palettes: std::array::from_fn(|_| Palette::from_object_register(0)),
+        };
+        for palette in 0..8 {
+            memory.decode_palette(palette);
+        }
+        memory
+    }
+
+    /// Get the current value of the specification register
+    pub fn read_specification(&self) -> u8 {
+        let auto_increment = if self.auto_increment {
+            AUTO_INCREMENT
+        } else {
+            0
+        };
+        auto_increment | UNUSED_BIT | self.index
+    }
+
+    /// Process a write to the specification register
+    pub fn write_specification(&mut self, value: u8) {
+        self.index = value & INDEX_MASK;
+        self.auto_increment = value & AUTO_INCREMENT != 0;
+    }
+
+    /// Get the byte at the selected index
+    pub fn read_data(&self) -> u8 {
+        self.data[self.index as usize]
+    }
+
+    /// Write the byte at the selected index and increment the index if auto increment is enabled
+    pub fn write_data(&mut self, value: u8) {
+        self.data[self.index as usize] = value;
+        self.decode_palette(self.index as usize / 8);
+        if self.auto_increment {
+            self.index = (self.index + 1) & INDEX_MASK;
+        }
+    }
+
+    /// Get the four colors of one of the eight palettes
+    fn get_colors(&self, palette: usize) -> [u16; 4] {
+        let bytes = &self.data[palette * 8..palette * 8 + 8];
+        [0, 1, 2, 3].map(|color| u16::from_le_bytes([bytes[color * 2], bytes[color * 2 + 1]]))
+    }
+
+    /// Decode a palette again after its colors changed
+    fn decode_palette(&mut self, palette: usize) {
+        let colors = self.get_colors(palette);
+        self.background_palettes[palette] = Palette::from_background_colors(colors);
+        self.object_palettes[palette] = Palette::from_object_colors(colors);
+    }
+
+    /// Get one of the eight palettes for the background and window layer
+    pub fn get_background_palette(&self, palette: usize) -> &Palette {
+        &self.background_palettes[palette]
+    }
+
+    /// Get one of the eight palettes for the object layer. The first color is always transparent.
+    pub fn get_object_palette(&self, palette: usize) -> &Palette {
+        &self.object_palettes[palette]
+    }
+}
+
+impl Default for ColorPaletteMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::memory_addresses::{
+        BACKGROUND_PALETTE_DATA_ADDRESS, BACKGROUND_PALETTE_SPECIFICATION_ADDRESS,
+        OBJECT_PALETTE_DATA_ADDRESS, OBJECT_PALETTE_SPECIFICATION_ADDRESS,
+    };
+    use crate::memory::video::palette::Color;
+    use crate::memory::{MemoryController, MemoryDevice};
+
+    #[test]
+    fn colors_written_to_palette_memory_can_be_read_back() {
+        let mut memory = MemoryController::new_for_tests();
+
+        // Select the second color of palette 1 with auto increment
+        memory.write(BACKGROUND_PALETTE_SPECIFICATION_ADDRESS as u16, 0b10001010);
+        memory.write(BACKGROUND_PALETTE_DATA_ADDRESS as u16, 0x1F);
+        memory.write(BACKGROUND_PALETTE_DATA_ADDRESS as u16, 0x7C);
+        assert_eq!(
+            memory.read(BACKGROUND_PALETTE_SPECIFICATION_ADDRESS as u16),
+            0b11001100
+        );
+
+        memory.write(BACKGROUND_PALETTE_SPECIFICATION_ADDRESS as u16, 0b00001010);
+        assert_eq!(memory.read(BACKGROUND_PALETTE_DATA_ADDRESS as u16), 0x1F);
+        memory.write(BACKGROUND_PALETTE_SPECIFICATION_ADDRESS as u16, 0b00001011);
+        assert_eq!(memory.read(BACKGROUND_PALETTE_DATA_ADDRESS as u16), 0x7C);
+
+        let palette = memory
+            .graphics
+            .background_color_palettes
+            .get_background_palette(1);
+        assert_eq!(*palette.get_color(1), Color::Rgb555(0x7C1F));
+        assert_eq!(palette.get_color(1).get_rgba(), (0xFF, 0x00, 0xFF, 0xFF));
+
+        // The object palettes are separate
+        memory.write(OBJECT_PALETTE_SPECIFICATION_ADDRESS as u16, 0b00001010);
+        assert_eq!(memory.read(OBJECT_PALETTE_DATA_ADDRESS as u16), 0xFF);
+        let palette = memory.graphics.object_color_palettes.get_object_palette(1);
+        assert_eq!(*palette.get_color(0), Color::Transparent);
+    }
+
+    #[test]
+    fn the_index_wraps_around_after_the_last_byte() {
+        let mut memory = MemoryController::new_for_tests();
+        memory.write(OBJECT_PALETTE_SPECIFICATION_ADDRESS as u16, 0b10111111);
+        memory.write(OBJECT_PALETTE_DATA_ADDRESS as u16, 0x12);
+        assert_eq!(
+            memory.read(OBJECT_PALETTE_SPECIFICATION_ADDRESS as u16),
+            0b11000000
+        );
+    }
+}
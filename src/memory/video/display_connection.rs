@@ -1,4 +1,13 @@
 use image::{ImageBuffer, Rgba, RgbaImage};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+/// The width of the gameboy screen in pixels
+pub const SCREEN_WIDTH: usize = 160;
+/// The height of the gameboy screen in pixels
+pub const SCREEN_HEIGHT: usize = 144;
 
 /// The trait is used to connect to a display
 pub trait DisplayConnection {
@@ -6,6 +15,14 @@ pub trait DisplayConnection {
     fn set_pixel(&mut self, x: usize, y: usize, value: (u8, u8, u8, u8));
     /// Notify the display that the current frame has finished drawing
     fn finish_frame(&mut self);
+    /// Reset all pixels to white, like a display that is turned off
+    fn clear(&mut self) {
+        for y in 0..SCREEN_HEIGHT {
+            for x in 0..SCREEN_WIDTH {
+                self.set_pixel(x, y, (255, 255, 255, 255));
+            }
+        }
+    }
 }
 
 /// A dummy display connection that does nothing
@@ -16,6 +33,87 @@ impl DisplayConnection for DummyDisplayConnection {
     fn finish_frame(&mut self) {}
 }
 
+/// A display connection that forwards everything to two other connections.
+///
+/// Useful to show the output on screen while also recording it. Nest them to use more than two connections.
+pub struct TeeDisplayConnection<A: DisplayConnection, B: DisplayConnection> {
+    /// The first connection
+    pub first: A,
+    /// The second connection
+    pub second: B,
+}
+
+impl<A: DisplayConnection, B: DisplayConnection> TeeDisplayConnection<A, B> {
+    /// Create a new connection that forwards to `first` and `second`
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A: DisplayConnection, B: DisplayConnection> DisplayConnection for TeeDisplayConnection<A, B> {
+    fn set_pixel(&mut self, x: usize, y: usize, value: (u8, u8, u8, u8)) {
+        self.first.set_pixel(x, y, value);
+        self.second.set_pixel(x, y, value);
+    }
+    fn finish_frame(&mut self) {
+        self.first.finish_frame();
+        self.second.finish_frame();
+    }
+    fn clear(&mut self) {
+        self.first.clear();
+        self.second.clear();
+    }
+}
+
+/// The number of bytes in a RGBA frame
+pub const FRAME_SIZE: usize = SCREEN_WIDTH * SCREEN_HEIGHT * 4;
+
+/// A display connection that keeps the frames in memory.
+///
+/// Useful for headless tests and screenshots. The frames contain the RGBA values of all pixels, row by row, just like [save_frame_ppm] expects them.
+pub struct BufferDisplayConnection {
+    current_frame: Box<[u8; FRAME_SIZE]>,
+    last_frame: Box<[u8; FRAME_SIZE]>,
+}
+
+impl BufferDisplayConnection {
+    /// Create a new buffer display connection. All pixels start out transparent black.
+    pub fn new() -> Self {
+        Self {
+            current_frame: Box::new([0; FRAME_SIZE]),
+            last_frame: Box::new([0; FRAME_SIZE]),
+        }
+    }
+
+    /// Get the last completed frame
+    pub fn frame(&self) -> &[u8] {
+        self.last_frame.as_slice()
+    }
+
+    /// Get the frame that is currently being drawn
+    pub fn current_frame(&self) -> &[u8] {
+        self.current_frame.as_slice()
+    }
+}
+
+impl Default for BufferDisplayConnection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DisplayConnection for BufferDisplayConnection {
+    fn set_pixel(&mut self, x: usize, y: usize, value: (u8, u8, u8, u8)) {
+        let (red, green, blue, alpha) = value;
+        let offset = (y * SCREEN_WIDTH + x) * 4;
+        self.current_frame[offset..offset + 4].copy_from_slice(&[red, green, blue, alpha]);
+    }
+    fn finish_frame(&mut self) {
+        self.last_frame
+            .copy_from_slice(self.current_frame.as_slice());
+    }
+}
+
 /// A display connection that creates a png for each frame
 pub struct PngDisplayConnection {
     image: RgbaImage,
@@ -48,9 +146,128 @@ impl DisplayConnection for PngDisplayConnection {
     }
 }
 
+/// Save a frame as a binary PPM (P6) image.
+///
+/// `frame` contains the RGBA values of all [SCREEN_WIDTH] x [SCREEN_HEIGHT] pixels, row by row. The alpha channel is dropped.
+///
+/// PPM is trivial to parse and does not need any extra dependencies, so this is useful for quick visual debugging.
+pub fn save_frame_ppm(frame: &[u8], path: &str) -> io::Result<()> {
+    if frame.len() != FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "A frame should contain {} bytes, but got {}",
+                FRAME_SIZE,
+                frame.len()
+            ),
+        ));
+    }
+    let mut file = BufWriter::new(File::create(path)?);
+    write!(file, "P6\n{} {}\n255\n", SCREEN_WIDTH, SCREEN_HEIGHT)?;
+    for pixel in frame.chunks_exact(4) {
+        file.write_all(&pixel[0..3])?;
+    }
+    file.flush()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{DisplayConnection, PngDisplayConnection};
+    use super::{
+        save_frame_ppm, BufferDisplayConnection, DisplayConnection, PngDisplayConnection,
+        TeeDisplayConnection, FRAME_SIZE, SCREEN_HEIGHT, SCREEN_WIDTH,
+    };
+    use crate::memory::memory_addresses::{FIRST_BG_TILE_DATA_AREA, FIRST_BG_TILE_MAP_AREA};
+    use crate::memory::video::palette::Palette;
+    use crate::memory::video::Video;
+    use crate::memory::Memory;
+
+    #[test]
+    fn tee_forwards_to_both_connections() {
+        let mut tee = TeeDisplayConnection::new(
+            BufferDisplayConnection::new(),
+            BufferDisplayConnection::new(),
+        );
+        tee.clear();
+        tee.set_pixel(0, 0, (1, 2, 3, 4));
+        tee.set_pixel(159, 143, (5, 6, 7, 8));
+        tee.finish_frame();
+
+        let TeeDisplayConnection { first, second } = tee;
+        assert_eq!(first.frame(), second.frame());
+        assert_eq!(first.frame()[0..4], [1, 2, 3, 4]);
+        assert_eq!(first.frame()[FRAME_SIZE - 4..], [5, 6, 7, 8]);
+        assert_eq!(first.frame()[4..8], [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn buffer_keeps_the_rendered_frame() {
+        let mut memory = Memory::new();
+        // The first tile of the first line is black
+        let tile_data_start = *FIRST_BG_TILE_DATA_AREA.start();
+        memory.data[tile_data_start + 16..tile_data_start + 32].fill(0xFF);
+        memory.data[*FIRST_BG_TILE_MAP_AREA.start()] = 1;
+
+        let mut video = Video::new(BufferDisplayConnection::new());
+        video.current_lcd_control = 0b10010001.into();
+        video.background_palette = Palette::from_background_register(0b11100100);
+        video.render_line(&mut memory);
+
+        let display = &mut video.display_connection;
+        assert_eq!(&display.current_frame()[0..4], &[0x00, 0x00, 0x00, 0xFF]);
+        assert_eq!(
+            &display.current_frame()[7 * 4..8 * 4],
+            &[0x00, 0x00, 0x00, 0xFF]
+        );
+        assert_eq!(
+            &display.current_frame()[8 * 4..9 * 4],
+            &[0xFF, 0xFF, 0xFF, 0xFF]
+        );
+        // The second line was not rendered yet
+        let second_line = SCREEN_WIDTH * 4;
+        assert_eq!(
+            &display.current_frame()[second_line..second_line + 4],
+            &[0; 4]
+        );
+        assert!(display.frame().iter().all(|byte| *byte == 0));
+
+        display.finish_frame();
+        assert_eq!(display.frame(), display.current_frame());
+        assert_eq!(display.frame().len(), SCREEN_WIDTH * SCREEN_HEIGHT * 4);
+    }
+
+    #[test]
+    fn frame_can_be_saved_as_ppm() {
+        let mut frame = vec![0xFF; SCREEN_WIDTH * SCREEN_HEIGHT * 4];
+        let pixel_offset = (3 * SCREEN_WIDTH + 2) * 4;
+        frame[pixel_offset..pixel_offset + 4].copy_from_slice(&[0x12, 0x34, 0x56, 0xFF]);
+
+        let path = std::env::temp_dir().join("rust_gameboy_library_test_frame.ppm");
+        save_frame_ppm(&frame, path.to_str().unwrap()).unwrap();
+        let content = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let header = b"P6\n160 144\n255\n";
+        assert_eq!(&content[..header.len()], header);
+        assert_eq!(
+            content.len(),
+            header.len() + SCREEN_WIDTH * SCREEN_HEIGHT * 3
+        );
+        let pixel_offset = header.len() + (3 * SCREEN_WIDTH + 2) * 3;
+        assert_eq!(
+            &content[pixel_offset..pixel_offset + 3],
+            &[0x12, 0x34, 0x56]
+        );
+        assert_eq!(
+            &content[pixel_offset + 3..pixel_offset + 6],
+            &[0xFF, 0xFF, 0xFF]
+        );
+    }
+
+    #[test]
+    fn saving_a_frame_with_the_wrong_size_fails() {
+        let path = std::env::temp_dir().join("rust_gameboy_library_test_invalid_frame.ppm");
+        assert!(save_frame_ppm(&[0; 10], path.to_str().unwrap()).is_err());
+    }
 
     #[test]
     fn test_png_display() {
@@ -2,11 +2,11 @@ use std::ops::RangeInclusive;
 
 use crate::memory::memory_addresses::{
     FIRST_BG_TILE_DATA_AREA, FIRST_BG_TILE_MAP_AREA, SECOND_BG_TILE_DATA_AREA,
-    SECOND_BG_TILE_MAP_AREA,
+    SECOND_BG_TILE_MAP_AREA, TILE_DATA_AREA,
 };
 
 /// Which background map is used for rendering.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum BackgroundTilemapArea {
     /// Use [FIRST_BG_TILE_MAP_AREA] as the source for the tilemap
     First,
@@ -25,7 +25,7 @@ impl BackgroundTilemapArea {
 }
 
 /// Which tile data is used for rendering the background and window.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum TileDataArea {
     /// Use [FIRST_BG_TILE_DATA_AREA] as the source for the tilemap
     ///
@@ -43,10 +43,29 @@ impl TileDataArea {
             TileDataArea::Second => SECOND_BG_TILE_DATA_AREA,
         }
     }
+
+    /// Get the address of the first byte of the tile with the given index
+    ///
+    /// The [TileDataArea::First] uses unsigned indices starting at `0x8000`.
+    /// The [TileDataArea::Second] uses signed indices relative to `0x9000`, so `0x00` is at `0x9000` and `0x80` is at `0x8800`.
+    pub fn get_tile_address(&self, index: u8) -> usize {
+        match self {
+            TileDataArea::First => FIRST_BG_TILE_DATA_AREA.start() + index as usize * 16,
+            TileDataArea::Second => {
+                let base = (SECOND_BG_TILE_DATA_AREA.start() + 0x800) as isize;
+                (base + (index as i8) as isize * 16) as usize
+            }
+        }
+    }
+
+    /// Get the position of the tile with the given index in [Memory::get_all_tile_data](crate::memory::Memory::get_all_tile_data)
+    pub fn get_tile_position(&self, index: u8) -> usize {
+        (self.get_tile_address(index) - TILE_DATA_AREA.start()) / 16
+    }
 }
 
 /// The size of the objects
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum ObjectSize {
     /// A object is one tile big
     EightByEight,
@@ -69,6 +88,7 @@ impl ObjectSize {
 }
 
 /// Represents the LCD control register
+#[derive(Clone)]
 pub struct LcdControl {
     /// Controls whether the LCD is on and the PPU is active
     ///
@@ -26,7 +26,21 @@ pub enum PpuMode {
     TransferringData = 3,
 }
 
+/// The condition that caused a [Interrupt::LcdStat](crate::cpu::Interrupt::LcdStat)
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StatInterruptSource {
+    /// The current line became equal to LYC. Enabled by [LcdStatus::line_y_stat_interrupt_enable]
+    LineYCompare,
+    /// The PPU entered [PpuMode::HBlank]. Enabled by [LcdStatus::hblank_stat_interrupt_enable]
+    HBlank,
+    /// The PPU entered [PpuMode::VBlank]. Enabled by [LcdStatus::vblank_stat_interrupt_enable]
+    VBlank,
+    /// The PPU entered [PpuMode::Oam]. Enabled by [LcdStatus::oam_stat_interrupt_enable]
+    Oam,
+}
+
 /// Represents the LCD status register
+#[derive(Clone)]
 pub struct LcdStatus {
     /// Whether [Interrupt::Stat] is triggered by the [line_y_equal_flag]
     pub line_y_stat_interrupt_enable: bool,
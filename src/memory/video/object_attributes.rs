@@ -3,6 +3,7 @@ use crate::memory::{memory_addresses::OBJECT_ATTRIBUTE_MEMORY_AREA, Memory};
 use super::{display_connection::DisplayConnection, Video};
 
 /// Which color palette should be used for an object
+#[derive(Clone)]
 pub enum ObjectPalette {
     /// The one from [FIRST_OBJECT_PALETTE_ADDRESS]
     First,
@@ -11,11 +12,14 @@ pub enum ObjectPalette {
 }
 
 /// Represents an entry in the object attribute memory
+///
+/// Each entry is four bytes long: the Y position, the X position, the tile index and the flags.
+#[derive(Clone)]
 pub struct ObjectAttributes {
-    /// The x position on screen + 8
-    pub x_position: u8,
     /// The y position on screen + 16
     pub y_position: u8,
+    /// The x position on screen + 8
+    pub x_position: u8,
     /// The index of the tile in the tile data from [OBJECT_TILE_DATA_AREA]
     pub tile: u8,
     /// Draw the object below background and window if this is set to true.
@@ -26,6 +30,8 @@ pub struct ObjectAttributes {
     pub y_flip: bool,
     /// Select the color palette for this object
     pub palette: ObjectPalette,
+    /// Select one of the eight CGB object color palettes for this object. Only used in CGB mode.
+    pub cgb_palette: u8,
 }
 
 impl Into<ObjectAttributes> for &[u8] {
@@ -38,26 +44,28 @@ impl Into<ObjectAttributes> for &[u8] {
 
 impl Into<ObjectAttributes> for [u8; 4] {
     fn into(self) -> ObjectAttributes {
-        let x_position = self[0];
-        let y_position = self[1];
+        let y_position = self[0];
+        let x_position = self[1];
         let tile = self[2];
         let draw_under_bg_and_window = (self[3] & 0b10000000) != 0;
-        let x_flip = (self[3] & 0b01000000) != 0;
-        let y_flip = (self[3] & 0b00100000) != 0;
+        let y_flip = (self[3] & 0b01000000) != 0;
+        let x_flip = (self[3] & 0b00100000) != 0;
         let palette = if (self[3] & 0b00010000) != 0 {
             ObjectPalette::Second
         } else {
             ObjectPalette::First
         };
+        let cgb_palette = self[3] & 0b00000111;
 
         ObjectAttributes {
-            x_position,
             y_position,
+            x_position,
             tile,
             draw_under_bg_and_window,
             x_flip,
             y_flip,
             palette,
+            cgb_palette,
         }
     }
 }
@@ -73,24 +81,73 @@ impl Memory {
         return chunks;
     }
 
-    // TODO: Add tests
-    /// Get the [ObjectAttributes] for all objects that are visible on a given line.
+    /// Get the [ObjectAttributes] for the objects that are visible on a given line.
+    ///
+    /// An object covers the lines from `y_position - 16` to `y_position - 16 + height - 1`, where the height depends on the [ObjectSize](super::lcd_control::ObjectSize).
+    /// Objects with a Y position of 0 or at least 160 are never visible. The X position does not matter, objects that are off-screen horizontally still count towards the limit.
+    ///
+    /// Only the first [Video::max_sprites_per_line] objects in OAM order are returned.
+    /// They are sorted by their X coordinate, because on the DMG the object with the smaller X coordinate is drawn on top. Objects with the same X coordinate keep their OAM order.
     pub fn get_relevant_object_attributes<T: DisplayConnection>(
         &self,
         video: &Video<T>,
         line: u8,
     ) -> Vec<ObjectAttributes> {
         let object_attributes = self.get_object_attributes();
-        let object_height = video.current_lcd_control.object_size.get_height();
-        let filtered_object_attributes = object_attributes
+        let object_height = video.current_lcd_control.object_size.get_height() as usize;
+        let line = line as usize + 16;
+        let mut filtered_object_attributes = object_attributes
             .into_iter()
             .filter(|attributes| {
-                let first_line_visible = attributes.y_position <= (line + 16);
-                let last_line_visible = (attributes.y_position + object_height) > (line + 16);
-                // let x_visible = (attributes.x_position != 0) && (attributes.x_position < 168);
-                return first_line_visible && last_line_visible /* && x_visible */;
+                let first_line = attributes.y_position as usize;
+                (first_line..first_line + object_height).contains(&line)
             })
+            .take(video.max_sprites_per_line())
             .collect::<Vec<ObjectAttributes>>();
+        filtered_object_attributes.sort_by_key(|attributes| attributes.x_position);
         return filtered_object_attributes;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::memory_addresses::OBJECT_ATTRIBUTE_MEMORY_AREA;
+    use crate::memory::video::display_connection::DummyDisplayConnection;
+    use crate::memory::video::Video;
+    use crate::memory::Memory;
+
+    /// Get the Y positions of the objects that are visible on `line`
+    fn visible_objects(memory: &Memory, lcd_control: u8, line: u8) -> Vec<u8> {
+        let mut video = Video::new(DummyDisplayConnection {});
+        video.current_lcd_control = lcd_control.into();
+        video.set_max_sprites_per_line(40);
+        memory
+            .get_relevant_object_attributes(&video, line)
+            .iter()
+            .map(|object| object.y_position)
+            .collect()
+    }
+
+    #[test]
+    fn objects_are_selected_by_their_y_position() {
+        let mut memory = Memory::new();
+        let y_positions = [0, 8, 9, 16, 23, 24, 159, 160, 0xF8];
+        let object_start = *OBJECT_ATTRIBUTE_MEMORY_AREA.start();
+        for (index, y_position) in y_positions.into_iter().enumerate() {
+            // The X position is the same for all objects, so they stay in OAM order
+            let start = object_start + index * 4;
+            memory.data[start..start + 4].copy_from_slice(&[y_position, 8, 0, 0]);
+        }
+
+        // 8x8 objects
+        assert_eq!(visible_objects(&memory, 0b10000010, 0), [9, 16]);
+        assert_eq!(visible_objects(&memory, 0b10000010, 7), [16, 23]);
+        assert_eq!(visible_objects(&memory, 0b10000010, 8), [23, 24]);
+        assert_eq!(visible_objects(&memory, 0b10000010, 143), [159]);
+
+        // 8x16 objects
+        assert_eq!(visible_objects(&memory, 0b10000110, 0), [8, 9, 16]);
+        assert_eq!(visible_objects(&memory, 0b10000110, 8), [9, 16, 23, 24]);
+        assert_eq!(visible_objects(&memory, 0b10000110, 143), [159]);
+    }
+}
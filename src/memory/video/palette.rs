@@ -1,5 +1,13 @@
+/// The RGBA values that are used for the four DMG colors by default, from [Color::White] to [Color::Black].
+pub const DEFAULT_DMG_COLORS: [[u8; 4]; 4] = [
+    [0xFF, 0xFF, 0xFF, 0xFF],
+    [0xB0, 0xB0, 0xB0, 0xFF],
+    [0x60, 0x60, 0x60, 0xFF],
+    [0x00, 0x00, 0x00, 0xFF],
+];
+
 /// Represents a palette color
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 #[repr(u8)]
 pub enum Color {
     /// White.
@@ -12,18 +20,36 @@ pub enum Color {
     Black = 3,
     /// Transparent.
     Transparent,
+    /// A CGB color with five bits per channel. Bits 0-4 contain red, bits 5-9 green and bits 10-14 blue.
+    Rgb555(u16),
 }
 
 impl Color {
-    /// Get the color as rgba tuple
+    /// Get the color as rgba tuple, using the [DEFAULT_DMG_COLORS]
     pub fn get_rgba(&self) -> (u8, u8, u8, u8) {
-        match self {
-            Color::White => (0xFF, 0xFF, 0xFF, 0xFF),
-            Color::LightGray => (0xB0, 0xB0, 0xB0, 0xFF),
-            Color::DarkGray => (0x60, 0x60, 0x60, 0xFF),
-            Color::Black => (0x00, 0x00, 0x00, 0xFF),
-            Color::Transparent => (0x00, 0x00, 0x00, 0x00),
-        }
+        self.get_rgba_with(&DEFAULT_DMG_COLORS)
+    }
+
+    /// Get the color as rgba tuple, using `dmg_colors` for the RGBA values of the four colors
+    ///
+    /// [Color::Transparent] is always fully transparent. [Color::Rgb555] colors do not use `dmg_colors`.
+    pub fn get_rgba_with(&self, dmg_colors: &[[u8; 4]; 4]) -> (u8, u8, u8, u8) {
+        let [red, green, blue, alpha] = match self {
+            Color::White => dmg_colors[0],
+            Color::LightGray => dmg_colors[1],
+            Color::DarkGray => dmg_colors[2],
+            Color::Black => dmg_colors[3],
+            Color::Transparent => [0x00, 0x00, 0x00, 0x00],
+            Color::Rgb555(color) => {
+                // Scale the five bit channels to eight bits, so 0x1F becomes 0xFF
+                let channel = |shift: u16| {
+                    let value = ((color >> shift) & 0b11111) as u8;
+                    (value << 3) | (value >> 2)
+                };
+                [channel(0), channel(5), channel(10), 0xFF]
+            }
+        };
+        (red, green, blue, alpha)
     }
 }
 
@@ -40,6 +66,7 @@ impl Into<Color> for u8 {
 }
 
 /// Represents a color palette
+#[derive(Clone)]
 pub struct Palette {
     /// The color palette
     pub colors: [Color; 4],
@@ -76,6 +103,26 @@ impl Palette {
             colors: [Color::Transparent, second_color, third_color, fourth_color],
         }
     }
+    /// Create a palette from four RGB555 colors in the CGB background palette memory.
+    ///
+    /// See [ColorPaletteMemory](super::color_palette_memory::ColorPaletteMemory)
+    pub fn from_background_colors(colors: [u16; 4]) -> Palette {
+        Palette {
+            colors: colors.map(|color| Color::Rgb555(color & 0x7FFF)),
+        }
+    }
+
+    /// Create a palette from four RGB555 colors in the CGB object palette memory.
+    ///
+    /// This is similar to [from_background_colors], but the first color is always [Color::Transparent].
+    pub fn from_object_colors(colors: [u16; 4]) -> Palette {
+        let [_, second_color, third_color, fourth_color] =
+            Self::from_background_colors(colors).colors;
+        Palette {
+            colors: [Color::Transparent, second_color, third_color, fourth_color],
+        }
+    }
+
     /// Get the color for a color index
     pub fn get_color(&self, index: usize) -> &Color {
         return self
@@ -1,4 +1,4 @@
-use crate::memory::Memory;
+use crate::memory::{memory_addresses::TILE_DATA_AREA, Memory};
 
 use super::lcd_control::TileDataArea;
 use std::ops::Range;
@@ -67,6 +67,8 @@ impl TileData {
 #[cfg(test)]
 mod tests {
     use super::TileData;
+    use crate::memory::video::lcd_control::TileDataArea;
+    use crate::memory::Memory;
 
     #[test]
     fn decoding_tile_works() {
@@ -84,25 +86,41 @@ mod tests {
         let decoded_tile = TileData::from(original);
         assert_eq!(expected_result, decoded_tile.pixels);
     }
+
+    #[test]
+    fn second_tile_data_area_uses_signed_indices() {
+        let mut memory = Memory::new();
+        memory.data[0x9000..0x9010].fill(0xFF);
+        memory.data[0x8800..0x8810].copy_from_slice(&[0xFF, 0x00].repeat(8));
+        memory.data[0x97F0..0x9800].copy_from_slice(&[0x00, 0xFF].repeat(8));
+
+        let tile_data = memory.get_tile_data(&TileDataArea::Second);
+        assert_eq!(tile_data[0x00].pixels, [0b11; 64]);
+        assert_eq!(tile_data[0x80].pixels, [0b01; 64]);
+        assert_eq!(tile_data[0x7F].pixels, [0b10; 64]);
+        assert_eq!(TileDataArea::Second.get_tile_address(0xFF), 0x8FF0);
+        assert_eq!(TileDataArea::First.get_tile_address(0x80), 0x8800);
+    }
 }
 
 impl Memory {
-    /// Get tile data for an area
-    pub fn get_tile_data(&self, area: &TileDataArea) -> Vec<TileData> {
-        let video_ram = &self.data[area.get_memory_area()];
-        let mut chunks = video_ram
+    /// Get all 384 tiles in the [TILE_DATA_AREA], ordered by their address
+    pub fn get_all_tile_data(&self) -> Vec<TileData> {
+        self.data[TILE_DATA_AREA]
             .chunks_exact(16)
-            .map(|chunk| TileData::from(chunk.try_into().unwrap()))
-            .collect::<Vec<TileData>>();
+            .map(|bytes| TileData::from(bytes.try_into().unwrap()))
+            .collect::<Vec<TileData>>()
+    }
 
-        match area {
-            TileDataArea::First => chunks,
-            TileDataArea::Second => {
-                // The second data area starts in the middle and then wraps around to the first elements
-                let (first_part, second_part) = chunks.split_at_mut(128);
-                first_part.swap_with_slice(second_part);
-                chunks
-            }
-        }
+    /// Get tile data for an area
+    ///
+    /// The returned tiles are ordered by their index, see [TileDataArea::get_tile_address].
+    pub fn get_tile_data(&self, area: &TileDataArea) -> Vec<TileData> {
+        (0..=255u8)
+            .map(|index| {
+                let address = area.get_tile_address(index);
+                TileData::from(self.data[address..address + 16].try_into().unwrap())
+            })
+            .collect::<Vec<TileData>>()
     }
 }
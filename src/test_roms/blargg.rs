@@ -4,7 +4,7 @@ mod memory_timing;
 
 #[cfg(test)]
 use crate::{
-    cpu::{instruction::Instruction, Cpu, CpuState},
+    cpu::{instruction::Instruction, CpuState},
     memory::{
         cartridge::Cartridge, serial::serial_connection::LineBasedConnection, MemoryController,
     },
@@ -16,8 +16,8 @@ use std::cell::RefCell;
 fn test_blargg_rom(path: &str, cycles: usize) {
     let passed_counter = RefCell::new(0);
 
-    let cartridge = Cartridge::load(path);
-    let mut cpu = CpuState::new();
+    let cartridge = Cartridge::load(path).unwrap();
+    let mut cpu = CpuState::new_post_boot();
     let mut closure = |line: &String| {
         if line.contains("Passed") {
             let mut passed = passed_counter.borrow_mut();
@@ -30,7 +30,6 @@ fn test_blargg_rom(path: &str, cycles: usize) {
         MemoryController::new_with_connections(Some(LineBasedConnection::new(&mut closure)));
     cartridge.place_into_memory(&mut memory.memory);
     memory.cartridge = cartridge;
-    cpu.write_program_counter(0x0100);
     let mut instruction = cpu.load_instruction(&mut memory);
     for _id in 1..cycles {
         instruction = instruction.execute(&mut cpu, &mut memory);
@@ -12,8 +12,8 @@ use crate::{
 fn test_mooneye_rom(path: &str, cycles: usize) {
     use crate::{cpu::Register, memory::video::display_connection::PngDisplayConnection};
 
-    let cartridge = Cartridge::load(path);
-    let mut cpu = CpuState::new();
+    let cartridge = Cartridge::load(path).unwrap();
+    let mut cpu = CpuState::new_post_boot();
     let mut closure = |line: &String| println!("Serial: {}", line);
 
     let mut memory = MemoryController::new_with_video_connections(
@@ -22,7 +22,6 @@ fn test_mooneye_rom(path: &str, cycles: usize) {
     );
     cartridge.place_into_memory(&mut memory.memory);
     memory.cartridge = cartridge;
-    cpu.write_program_counter(0x0100);
     let mut instruction = cpu.load_instruction(&mut memory);
     for _id in 1..cycles {
         instruction = instruction.execute(&mut cpu, &mut memory);